@@ -7,7 +7,9 @@
 ///   CH4: ノイズ(LFSR) (0xFF20-0xFF23)
 ///
 /// マスター制御: NR50(0xFF24), NR51(0xFF25), NR52(0xFF26)
-/// Frame Sequencer: 512 Hz（2048 M-cycle ごと）
+/// Frame Sequencer: 512 Hz（タイマーの DIV bit4 の立ち下がりで [`Apu::clock_div_apu`] が呼ばれる）
+
+use crate::savestate::{Snapshot, StateReader, StateWriter};
 
 // デューティ波形テーブル (CH1/CH2)
 const DUTY_TABLE: [[u8; 8]; 4] = [
@@ -23,9 +25,6 @@ const DUTY_TABLE: [[u8; 8]; 4] = [
 const CPU_M_CYCLES_PER_SEC: u32 = 1_048_576;
 const SAMPLE_RATE: u32 = 44100;
 
-// Frame Sequencer: 512 Hz = 2048 M-cycles ごとに1ステップ
-const FS_PERIOD: u32 = 2048;
-
 // ─── 共通サブ構造体 ───────────────────────────────────────────
 
 struct LengthCounter {
@@ -671,8 +670,7 @@ pub struct Apu {
 
     powered: bool, // NR52 bit7
 
-    // Frame Sequencer
-    fs_counter: u32,
+    // Frame Sequencer（DIV-APU イベントで進める）
     fs_step: u8,
 
     // サンプリング（分数カウンタ方式）
@@ -696,7 +694,6 @@ impl Apu {
             nr50: 0,
             nr51: 0,
             powered: false,
-            fs_counter: 0,
             fs_step: 0,
             sample_frac: 0,
             acc_l: 0.0,
@@ -720,26 +717,19 @@ impl Apu {
             return None;
         }
 
-        // 1. Frame Sequencer クロック
-        self.fs_counter += 1;
-        if self.fs_counter >= FS_PERIOD {
-            self.fs_counter = 0;
-            self.clock_frame_sequencer();
-        }
-
-        // 2. 各チャンネルの周波数タイマーを進める
+        // 1. 各チャンネルの周波数タイマーを進める
         self.ch1.tick();
         self.ch2.tick();
         self.ch3.tick();
         self.ch4.tick();
 
-        // 3. ミキサー出力を毎サイクル積算 (ボックスフィルタ。struct のコメント参照)
+        // 2. ミキサー出力を毎サイクル積算 (ボックスフィルタ。struct のコメント参照)
         let (l, r) = self.mix();
         self.acc_l += l;
         self.acc_r += r;
         self.acc_n += 1;
 
-        // 4. サンプリング判定: 前回サンプル以降の平均を出力する
+        // 3. サンプリング判定: 前回サンプル以降の平均を出力する
         self.sample_frac += SAMPLE_RATE;
         if self.sample_frac >= CPU_M_CYCLES_PER_SEC {
             self.sample_frac -= CPU_M_CYCLES_PER_SEC;
@@ -753,6 +743,13 @@ impl Apu {
         None
     }
 
+    /// DIV-APU イベント（DIV bit4 の立ち下がり）。電源 OFF 中は Frame Sequencer を進めない。
+    pub fn clock_div_apu(&mut self) {
+        if self.powered {
+            self.clock_frame_sequencer();
+        }
+    }
+
     fn clock_frame_sequencer(&mut self) {
         match self.fs_step {
             0 | 4 => {
@@ -868,7 +865,6 @@ impl Apu {
                 } else if !was_powered && self.powered {
                     // 電源 ON: Frame Sequencer リセット
                     self.fs_step = 0;
-                    self.fs_counter = 0;
                 }
                 return;
             }
@@ -897,3 +893,167 @@ impl Apu {
         }
     }
 }
+
+// ─── セーブステート ───────────────────────────────────────────
+
+impl Snapshot for LengthCounter {
+    fn save(&self, w: &mut StateWriter) {
+        w.bool(self.enabled);
+        w.u16(self.counter);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        self.enabled = r.bool();
+        self.counter = r.u16();
+    }
+}
+
+impl Snapshot for VolumeEnvelope {
+    fn save(&self, w: &mut StateWriter) {
+        for v in [self.initial_vol, self.current_vol, self.add as u8, self.pace, self.timer] {
+            w.u8(v);
+        }
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        self.initial_vol = r.u8();
+        self.current_vol = r.u8();
+        self.add = r.bool();
+        self.pace = r.u8();
+        self.timer = r.u8();
+    }
+}
+
+impl Snapshot for Channel1 {
+    fn save(&self, w: &mut StateWriter) {
+        for v in [self.nr10, self.nr11, self.nr12, self.nr13, self.nr14] {
+            w.u8(v);
+        }
+        w.bool(self.enabled);
+        w.u8(self.duty_pos);
+        w.u16(self.freq_timer);
+        self.length.save(w);
+        self.envelope.save(w);
+        w.u8(self.sweep_timer);
+        w.bool(self.sweep_enabled);
+        w.u16(self.sweep_shadow);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        for v in [&mut self.nr10, &mut self.nr11, &mut self.nr12, &mut self.nr13, &mut self.nr14] {
+            *v = r.u8();
+        }
+        self.enabled = r.bool();
+        self.duty_pos = r.u8();
+        self.freq_timer = r.u16();
+        self.length.load(r);
+        self.envelope.load(r);
+        self.sweep_timer = r.u8();
+        self.sweep_enabled = r.bool();
+        self.sweep_shadow = r.u16();
+    }
+}
+
+impl Snapshot for Channel2 {
+    fn save(&self, w: &mut StateWriter) {
+        for v in [self.nr21, self.nr22, self.nr23, self.nr24] {
+            w.u8(v);
+        }
+        w.bool(self.enabled);
+        w.u8(self.duty_pos);
+        w.u16(self.freq_timer);
+        self.length.save(w);
+        self.envelope.save(w);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        for v in [&mut self.nr21, &mut self.nr22, &mut self.nr23, &mut self.nr24] {
+            *v = r.u8();
+        }
+        self.enabled = r.bool();
+        self.duty_pos = r.u8();
+        self.freq_timer = r.u16();
+        self.length.load(r);
+        self.envelope.load(r);
+    }
+}
+
+impl Snapshot for Channel3 {
+    fn save(&self, w: &mut StateWriter) {
+        for v in [self.nr30, self.nr31, self.nr32, self.nr33, self.nr34] {
+            w.u8(v);
+        }
+        w.bool(self.enabled);
+        w.u8(self.wave_pos);
+        w.u16(self.freq_timer);
+        self.length.save(w);
+        w.bytes(&self.wave_ram);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        for v in [&mut self.nr30, &mut self.nr31, &mut self.nr32, &mut self.nr33, &mut self.nr34] {
+            *v = r.u8();
+        }
+        self.enabled = r.bool();
+        self.wave_pos = r.u8();
+        self.freq_timer = r.u16();
+        self.length.load(r);
+        r.bytes(&mut self.wave_ram);
+    }
+}
+
+impl Snapshot for Channel4 {
+    fn save(&self, w: &mut StateWriter) {
+        for v in [self.nr41, self.nr42, self.nr43, self.nr44] {
+            w.u8(v);
+        }
+        w.bool(self.enabled);
+        w.u16(self.lfsr);
+        w.u32(self.freq_timer);
+        self.length.save(w);
+        self.envelope.save(w);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        for v in [&mut self.nr41, &mut self.nr42, &mut self.nr43, &mut self.nr44] {
+            *v = r.u8();
+        }
+        self.enabled = r.bool();
+        self.lfsr = r.u16();
+        self.freq_timer = r.u32();
+        self.length.load(r);
+        self.envelope.load(r);
+    }
+}
+
+impl Snapshot for Apu {
+    fn save(&self, w: &mut StateWriter) {
+        self.ch1.save(w);
+        self.ch2.save(w);
+        self.ch3.save(w);
+        self.ch4.save(w);
+        w.u8(self.nr50);
+        w.u8(self.nr51);
+        w.bool(self.powered);
+        w.u8(self.fs_step);
+        w.u32(self.sample_frac);
+        w.f32(self.acc_l);
+        w.f32(self.acc_r);
+        w.u32(self.acc_n);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        self.ch1.load(r);
+        self.ch2.load(r);
+        self.ch3.load(r);
+        self.ch4.load(r);
+        self.nr50 = r.u8();
+        self.nr51 = r.u8();
+        self.powered = r.bool();
+        self.fs_step = r.u8();
+        self.sample_frac = r.u32();
+        self.acc_l = r.f32();
+        self.acc_r = r.f32();
+        self.acc_n = r.u32();
+    }
+}
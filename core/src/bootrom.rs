@@ -1,26 +1,58 @@
-//! DMG BootROM (0x0000–0x00FF) の保持。
+//! BootROM の保持。DMG (0x0000–0x00FF) と CGB (0x0000–0x08FF) に対応する。
 //! バイト列はプラットフォーム側が供給する（host=ファイル, teensy=`include_bytes!`）。
+//!
+//! CGB BootROM は 0x0100–0x01FF がカートリッジヘッダの「穴」で、その範囲は
+//! BootROM 実行中もカートリッジが見える。
+
+use crate::savestate::{Snapshot, StateReader, StateWriter};
+
+/// DMG BootROM のサイズ
+pub const DMG_BOOTROM_SIZE: usize = 0x100;
+/// CGB BootROM のサイズ
+pub const CGB_BOOTROM_SIZE: usize = 0x900;
 
 pub struct Bootrom {
-    rom: [u8; 0x100],
+    rom: [u8; CGB_BOOTROM_SIZE],
+    /// CGB BootROM か
+    cgb: bool,
     active: bool,
 }
 
 impl Bootrom {
-    /// BootROM バイト列から有効状態で生成する。
-    pub fn from_bytes(rom: [u8; 0x100]) -> Self {
-        Self { rom, active: true }
+    /// DMG BootROM バイト列から有効状態で生成する。
+    pub fn from_bytes(rom: [u8; DMG_BOOTROM_SIZE]) -> Self {
+        let mut buf = [0; CGB_BOOTROM_SIZE];
+        buf[..DMG_BOOTROM_SIZE].copy_from_slice(&rom);
+        Self { rom: buf, cgb: false, active: true }
+    }
+
+    /// CGB BootROM バイト列から有効状態で生成する。
+    pub fn from_cgb_bytes(rom: [u8; CGB_BOOTROM_SIZE]) -> Self {
+        Self { rom, cgb: true, active: true }
     }
 
     /// BootROM 無効状態で生成する（DMG 初期値を別途適用する想定）。
     pub fn disabled() -> Self {
-        Self { rom: [0; 0x100], active: false }
+        Self { rom: [0; CGB_BOOTROM_SIZE], cgb: false, active: false }
     }
 
     pub fn is_active(&self) -> bool {
         self.active
     }
 
+    pub fn is_cgb(&self) -> bool {
+        self.cgb
+    }
+
+    /// `addr` が現在 BootROM にマップされているか
+    pub fn maps(&self, addr: u16) -> bool {
+        match addr {
+            0x0000..=0x00FF => self.active,
+            0x0200..=0x08FF => self.active && self.cgb,
+            _ => false,
+        }
+    }
+
     pub fn write(&mut self, _: u16, val: u8) {
         self.active &= val == 0;
     }
@@ -29,3 +61,14 @@ impl Bootrom {
         self.rom[addr as usize]
     }
 }
+
+/// ROM 本体はプラットフォームが供給するので、マップ状態だけを保存する。
+impl Snapshot for Bootrom {
+    fn save(&self, w: &mut StateWriter) {
+        w.bool(self.active);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        self.active = r.bool();
+    }
+}
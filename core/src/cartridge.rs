@@ -0,0 +1,500 @@
+//! カートリッジのマッパ（MBC）。host と teensy で共有する。
+//!
+//! 各マッパはバンクレジスタだけを持つ状態機械で、ROM と外部 RAM は呼び出し側が渡す
+//! スライスを読み書きする（ヒープを使わない）。[`MbcCart`] が ROM・外部 RAM の記憶域と
+//! マッパをまとめて [`CartridgeBus`] を実装する。記憶域は `AsRef<[u8]>` / `AsMut<[u8]>` なので
+//! host は `Vec<u8>`、teensy は Flash 上の `&'static [u8]` と静的バッファをそのまま使える。
+
+mod header;
+mod huc1;
+mod mbc1;
+mod mbc2;
+mod mbc3;
+mod mbc5;
+mod mbc7;
+mod mmm01;
+mod rtc;
+
+pub use header::{
+    HEADER_END, Header, HeaderError, HeaderWarning, Licensee, NINTENDO_LOGO, global_checksum,
+    header_checksum, new_licensee_name,
+};
+pub use huc1::HuC1;
+pub use mbc1::Mbc1;
+pub use mbc2::Mbc2;
+pub use mbc3::Mbc3;
+pub use mbc5::Mbc5;
+pub use mbc7::Mbc7;
+pub use mmm01::Mmm01;
+pub use rtc::{RTC_FOOTER_LEN, Rtc};
+
+use crate::platform::CartridgeBus;
+use crate::savestate::{StateReader, StateWriter};
+
+/// ROM ヘッダ 0x0147 のカートリッジタイプ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CartridgeType {
+    RomOnly,
+    Mbc1,
+    Mbc1Ram,
+    Mbc1RamBattery,
+    Mbc2,
+    Mbc2Battery,
+    Mmm01,
+    Mmm01Ram,
+    Mmm01RamBattery,
+    Mbc3,
+    Mbc3Ram,
+    Mbc3RamBattery,
+    Mbc3Timer,
+    Mbc3TimerRam,
+    Mbc5,
+    Mbc5Ram,
+    Mbc5RamBattery,
+    Mbc7SensorRumbleRamBattery,
+    PocketCamera,
+    HuC1RamBattery,
+    /// 未対応のタイプ（HuC3・MBC6・TAMA5 など）。ヘッダの値をそのまま持つ
+    Unknown(u8),
+}
+
+impl From<u8> for CartridgeType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => CartridgeType::RomOnly,
+            0x01 => CartridgeType::Mbc1,
+            0x02 => CartridgeType::Mbc1Ram,
+            0x03 => CartridgeType::Mbc1RamBattery,
+            0x05 => CartridgeType::Mbc2,
+            0x06 => CartridgeType::Mbc2Battery,
+            0x0B => CartridgeType::Mmm01,
+            0x0C => CartridgeType::Mmm01Ram,
+            0x0D => CartridgeType::Mmm01RamBattery,
+            0x0F => CartridgeType::Mbc3Timer,
+            0x10 => CartridgeType::Mbc3TimerRam,
+            0x11 => CartridgeType::Mbc3,
+            0x12 => CartridgeType::Mbc3Ram,
+            0x13 => CartridgeType::Mbc3RamBattery,
+            0x19 => CartridgeType::Mbc5,
+            0x1A => CartridgeType::Mbc5Ram,
+            0x1B => CartridgeType::Mbc5RamBattery,
+            0x22 => CartridgeType::Mbc7SensorRumbleRamBattery,
+            0xFC => CartridgeType::PocketCamera,
+            0xFF => CartridgeType::HuC1RamBattery,
+            other => CartridgeType::Unknown(other),
+        }
+    }
+}
+
+impl CartridgeType {
+    /// ヘッダ 0x0147 の値
+    pub fn code(self) -> u8 {
+        match self {
+            CartridgeType::RomOnly => 0x00,
+            CartridgeType::Mbc1 => 0x01,
+            CartridgeType::Mbc1Ram => 0x02,
+            CartridgeType::Mbc1RamBattery => 0x03,
+            CartridgeType::Mbc2 => 0x05,
+            CartridgeType::Mbc2Battery => 0x06,
+            CartridgeType::Mmm01 => 0x0B,
+            CartridgeType::Mmm01Ram => 0x0C,
+            CartridgeType::Mmm01RamBattery => 0x0D,
+            CartridgeType::Mbc3 => 0x11,
+            CartridgeType::Mbc3Ram => 0x12,
+            CartridgeType::Mbc3RamBattery => 0x13,
+            CartridgeType::Mbc3Timer => 0x0F,
+            CartridgeType::Mbc3TimerRam => 0x10,
+            CartridgeType::Mbc5 => 0x19,
+            CartridgeType::Mbc5Ram => 0x1A,
+            CartridgeType::Mbc5RamBattery => 0x1B,
+            CartridgeType::Mbc7SensorRumbleRamBattery => 0x22,
+            CartridgeType::PocketCamera => 0xFC,
+            CartridgeType::HuC1RamBattery => 0xFF,
+            CartridgeType::Unknown(code) => code,
+        }
+    }
+
+    /// バッテリーバックアップ付き（.sav に保存すべき）か
+    pub fn has_battery(self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc1RamBattery
+                | CartridgeType::Mbc2Battery
+                | CartridgeType::Mmm01RamBattery
+                | CartridgeType::Mbc3RamBattery
+                | CartridgeType::Mbc3Timer
+                | CartridgeType::Mbc3TimerRam
+                | CartridgeType::Mbc5RamBattery
+                | CartridgeType::Mbc7SensorRumbleRamBattery
+                | CartridgeType::PocketCamera
+                | CartridgeType::HuC1RamBattery
+        )
+    }
+
+    /// 外部 RAM（MBC2 の内蔵 RAM、MBC7 の EEPROM を含む）を持つか
+    pub fn has_ram(self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc1Ram
+                | CartridgeType::Mbc1RamBattery
+                | CartridgeType::Mbc2
+                | CartridgeType::Mbc2Battery
+                | CartridgeType::Mmm01Ram
+                | CartridgeType::Mmm01RamBattery
+                | CartridgeType::Mbc3Ram
+                | CartridgeType::Mbc3RamBattery
+                | CartridgeType::Mbc3TimerRam
+                | CartridgeType::Mbc5Ram
+                | CartridgeType::Mbc5RamBattery
+                | CartridgeType::Mbc7SensorRumbleRamBattery
+                | CartridgeType::PocketCamera
+                | CartridgeType::HuC1RamBattery
+        )
+    }
+
+    /// 外部 RAM（MBC2 は内蔵 RAM、MBC7 は EEPROM）に必要なバイト数。
+    /// `code` は ROM ヘッダ 0x0149 の RAM サイズコード。
+    pub fn ram_size(self, code: u8) -> usize {
+        match self {
+            CartridgeType::Mbc2 | CartridgeType::Mbc2Battery => mbc2::RAM_SIZE,
+            CartridgeType::Mbc7SensorRumbleRamBattery => mbc7::EEPROM_SIZE,
+            CartridgeType::PocketCamera => 0x20000,
+            _ => match code {
+                0x01 => 0x800,   // 2KB
+                0x02 => 0x2000,  // 8KB
+                0x03 => 0x8000,  // 32KB
+                0x04 => 0x20000, // 128KB
+                0x05 => 0x10000, // 64KB
+                _ => 0,
+            },
+        }
+    }
+}
+
+/// MBC3 RTC の時刻源（UNIX 時刻・秒）
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+/// 時刻源なし（常に 0 を返すので RTC は止まったままになる）
+pub struct NoClock;
+
+impl Clock for NoClock {
+    fn now(&self) -> u64 {
+        0
+    }
+}
+
+/// `bank` 番の 16KB ROM バンクのうち `addr` に対応するバイト（範囲外は 0xFF）
+fn rom_byte(rom: &[u8], bank: usize, addr: u16) -> u8 {
+    rom.get(bank * 0x4000 + (addr as usize & 0x3FFF)).copied().unwrap_or(0xFF)
+}
+
+/// `bank` 番の 8KB RAM バンクのうち `addr` に対応する添字（範囲外は None）
+fn ram_index(ram: &[u8], bank: usize, addr: u16) -> Option<usize> {
+    let offset = bank * 0x2000 + (addr as usize - 0xA000);
+    (offset < ram.len()).then_some(offset)
+}
+
+/// カートリッジタイプごとのマッパ（列挙で静的にディスパッチする）
+pub enum Mapper {
+    RomOnly,
+    Mbc1(Mbc1),
+    Mbc2(Mbc2),
+    Mbc3(Mbc3),
+    Mbc5(Mbc5),
+    Mmm01(Mmm01),
+    HuC1(HuC1),
+    Mbc7(Mbc7),
+}
+
+impl Mapper {
+    /// カートリッジタイプに対応するマッパ。ポケットカメラは画像の供給元が要るので None、
+    /// 未対応のタイプも None。
+    /// `now` は MBC3 RTC の初期時刻。
+    pub fn new(cartridge_type: CartridgeType, now: u64) -> Option<Self> {
+        use CartridgeType as T;
+        Some(match cartridge_type {
+            T::RomOnly => Mapper::RomOnly,
+            T::Mbc1 | T::Mbc1Ram | T::Mbc1RamBattery => Mapper::Mbc1(Mbc1::new()),
+            T::Mbc2 | T::Mbc2Battery => Mapper::Mbc2(Mbc2::new()),
+            T::Mmm01 | T::Mmm01Ram | T::Mmm01RamBattery => Mapper::Mmm01(Mmm01::new()),
+            T::Mbc3 | T::Mbc3Ram | T::Mbc3RamBattery => Mapper::Mbc3(Mbc3::new(None)),
+            T::Mbc3Timer | T::Mbc3TimerRam => Mapper::Mbc3(Mbc3::new(Some(Rtc::new(now)))),
+            T::Mbc5 | T::Mbc5Ram | T::Mbc5RamBattery => Mapper::Mbc5(Mbc5::new()),
+            T::Mbc7SensorRumbleRamBattery => Mapper::Mbc7(Mbc7::new()),
+            T::HuC1RamBattery => Mapper::HuC1(HuC1::new()),
+            T::PocketCamera | T::Unknown(_) => return None,
+        })
+    }
+
+    pub fn read(&self, rom: &[u8], ram: &[u8], addr: u16) -> u8 {
+        match self {
+            Mapper::RomOnly => match addr {
+                0x0000..=0x7FFF => rom.get(addr as usize).copied().unwrap_or(0xFF),
+                _ => 0xFF,
+            },
+            Mapper::Mbc1(m) => m.read(rom, ram, addr),
+            Mapper::Mbc2(m) => m.read(rom, ram, addr),
+            Mapper::Mbc3(m) => m.read(rom, ram, addr),
+            Mapper::Mbc5(m) => m.read(rom, ram, addr),
+            Mapper::Mmm01(m) => m.read(rom, ram, addr),
+            Mapper::HuC1(m) => m.read(rom, ram, addr),
+            Mapper::Mbc7(m) => m.read(rom, ram, addr),
+        }
+    }
+
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク
+    pub fn rom_bank_at(&self, rom: &[u8], addr: u16) -> usize {
+        match self {
+            Mapper::RomOnly => (addr >= 0x4000) as usize,
+            Mapper::Mbc1(m) => m.rom_bank_at(rom, addr),
+            Mapper::Mbc2(m) => m.rom_bank_at(rom, addr),
+            Mapper::Mbc3(m) => m.rom_bank_at(rom, addr),
+            Mapper::Mbc5(m) => m.rom_bank_at(rom, addr),
+            Mapper::Mmm01(m) => m.rom_bank_at(rom, addr),
+            Mapper::HuC1(m) => m.rom_bank_at(rom, addr),
+            Mapper::Mbc7(m) => m.rom_bank_at(rom, addr),
+        }
+    }
+
+    /// 0xA000–0xBFFF に割り当て中の RAM バンク（バンク切替がなければ 0）
+    pub fn ram_bank(&self) -> usize {
+        match self {
+            Mapper::RomOnly | Mapper::Mbc2(_) | Mapper::Mbc7(_) => 0,
+            Mapper::Mbc1(m) => m.ram_bank(),
+            Mapper::Mbc3(m) => m.ram_bank(),
+            Mapper::Mbc5(m) => m.ram_bank(),
+            Mapper::Mmm01(m) => m.ram_bank(),
+            Mapper::HuC1(m) => m.ram_bank(),
+        }
+    }
+
+    /// 外部 RAM（MBC3 は RTC、MBC7 は EEPROM を含む）を書き換えたら true。
+    /// `now` は RTC を持つ MBC3 だけが使う
+    pub fn write(&mut self, ram: &mut [u8], addr: u16, value: u8, now: u64) -> bool {
+        match self {
+            Mapper::RomOnly => false,
+            Mapper::Mbc1(m) => m.write(ram, addr, value),
+            Mapper::Mbc2(m) => m.write(ram, addr, value),
+            Mapper::Mbc3(m) => m.write(ram, addr, value, now),
+            Mapper::Mbc5(m) => m.write(ram, addr, value),
+            Mapper::Mmm01(m) => m.write(ram, addr, value),
+            Mapper::HuC1(m) => m.write(ram, addr, value),
+            Mapper::Mbc7(m) => m.write(ram, addr, value),
+        }
+    }
+
+    /// RTC を持つ場合はその参照
+    pub fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        match self {
+            Mapper::Mbc3(m) => m.rtc.as_mut(),
+            _ => None,
+        }
+    }
+
+    pub fn set_tilt(&mut self, x: f32, y: f32) {
+        if let Mapper::Mbc7(m) = self {
+            m.set_tilt(x, y);
+        }
+    }
+
+    /// バンクレジスタ等（RAM の内容は含めない）
+    pub fn save_state(&self, w: &mut StateWriter) {
+        match self {
+            Mapper::RomOnly => {}
+            Mapper::Mbc1(m) => m.save_state(w),
+            Mapper::Mbc2(m) => m.save_state(w),
+            Mapper::Mbc3(m) => m.save_state(w),
+            Mapper::Mbc5(m) => m.save_state(w),
+            Mapper::Mmm01(m) => m.save_state(w),
+            Mapper::HuC1(m) => m.save_state(w),
+            Mapper::Mbc7(m) => m.save_state(w),
+        }
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) {
+        match self {
+            Mapper::RomOnly => {}
+            Mapper::Mbc1(m) => m.load_state(r),
+            Mapper::Mbc2(m) => m.load_state(r),
+            Mapper::Mbc3(m) => m.load_state(r),
+            Mapper::Mbc5(m) => m.load_state(r),
+            Mapper::Mmm01(m) => m.load_state(r),
+            Mapper::HuC1(m) => m.load_state(r),
+            Mapper::Mbc7(m) => m.load_state(r),
+        }
+    }
+}
+
+/// ROM・外部 RAM の記憶域とマッパをまとめたカートリッジ。
+///
+/// `ram` は [`CartridgeType::ram_size`] のバイト数を渡す（短ければその先は 0xFF / 書き込み無視）。
+/// MBC7 の EEPROM も `ram` に入るので、`.sav` には `ram` の内容をそのまま保存すればよい。
+pub struct MbcCart<R, S, C = NoClock> {
+    rom: R,
+    ram: S,
+    mapper: Mapper,
+    clock: C,
+}
+
+impl<R: AsRef<[u8]>, S: AsRef<[u8]> + AsMut<[u8]>, C: Clock> MbcCart<R, S, C> {
+    /// ROM ヘッダのカートリッジタイプからマッパを選ぶ。
+    /// 未対応のタイプ（とポケットカメラ）はそのバイト値をエラーとして返す。
+    /// MBC7 の EEPROM は消去状態 (0xFF) で始まるので `ram` を埋める（`.sav` はこの後で読み込む）。
+    pub fn new(rom: R, mut ram: S, clock: C) -> Result<Self, u8> {
+        let code = rom.as_ref().get(0x0147).copied().unwrap_or(0x00);
+        let mapper = Mapper::new(CartridgeType::from(code), clock.now()).ok_or(code)?;
+        if let Mapper::Mbc7(_) = mapper {
+            ram.as_mut().fill(0xFF);
+        }
+        Ok(Self { rom, ram, mapper, clock })
+    }
+
+    pub fn rom(&self) -> &[u8] {
+        self.rom.as_ref()
+    }
+
+    pub fn ram(&self) -> &[u8] {
+        self.ram.as_ref()
+    }
+
+    pub fn ram_mut(&mut self) -> &mut [u8] {
+        self.ram.as_mut()
+    }
+
+    pub fn mapper(&self) -> &Mapper {
+        &self.mapper
+    }
+
+    /// RTC を持つ場合はその参照と時刻源の現在値
+    pub fn rtc_mut(&mut self) -> Option<(&mut Rtc, u64)> {
+        let now = self.clock.now();
+        self.mapper.rtc_mut().map(|rtc| (rtc, now))
+    }
+
+    /// [`CartridgeBus::write`] と同じ書き込み。外部 RAM（RTC・EEPROM を含む）を
+    /// 書き換えたら true（バッテリーバックアップの保存要否の判定に使う）。
+    pub fn write_tracked(&mut self, addr: u16, val: u8) -> bool {
+        // 時刻源の問い合わせは RTC を持つカートだけ
+        let now = if matches!(&self.mapper, Mapper::Mbc3(m) if m.rtc.is_some()) {
+            self.clock.now()
+        } else {
+            0
+        };
+        self.mapper.write(self.ram.as_mut(), addr, val, now)
+    }
+}
+
+impl<R: AsRef<[u8]>, S: AsRef<[u8]> + AsMut<[u8]>, C: Clock> CartridgeBus for MbcCart<R, S, C> {
+    fn read(&self, addr: u16) -> u8 {
+        self.mapper.read(self.rom.as_ref(), self.ram.as_ref(), addr)
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.write_tracked(addr, val);
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        self.mapper.save_state(w);
+        w.bytes(self.ram.as_ref());
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) {
+        self.mapper.load_state(r);
+        r.bytes(self.ram.as_mut());
+    }
+
+    fn rom_bank_at(&self, addr: u16) -> usize {
+        self.mapper.rom_bank_at(self.rom.as_ref(), addr)
+    }
+
+    fn ram_bank(&self) -> usize {
+        self.mapper.ram_bank()
+    }
+
+    fn set_tilt(&mut self, x: f32, y: f32) {
+        self.mapper.set_tilt(x, y);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// 各 16KB バンクの先頭バイトがバンク番号の ROM（0x0147 にカートリッジタイプ）
+    pub(crate) fn banked_rom(banks: usize, cartridge_type: u8) -> Vec<u8> {
+        let mut rom = vec![0; banks * 0x4000];
+        for (i, bank) in rom.chunks_mut(0x4000).enumerate() {
+            bank[0] = i as u8;
+        }
+        rom[0x0147] = cartridge_type;
+        rom
+    }
+
+    #[test]
+    fn unsupported_cartridge_type_is_an_error() {
+        assert_eq!(CartridgeType::from(0x06), CartridgeType::Mbc2Battery);
+        assert_eq!(CartridgeType::from(0xFF), CartridgeType::HuC1RamBattery);
+        assert_eq!(CartridgeType::from(0x20), CartridgeType::Unknown(0x20));
+        for code in 0..=0xFF {
+            assert_eq!(CartridgeType::from(code).code(), code);
+        }
+        assert_eq!(MbcCart::new(banked_rom(2, 0x20), [], NoClock).err(), Some(0x20));
+        // ポケットカメラは画像の供給元が必要なので MbcCart では作れない
+        assert!(MbcCart::new(banked_rom(2, 0xFC), [], NoClock).is_err());
+    }
+
+    #[test]
+    fn has_ram_follows_the_type() {
+        assert!(!CartridgeType::Mbc1.has_ram());
+        assert!(!CartridgeType::Mbc3Timer.has_ram());
+        assert!(CartridgeType::Mbc3TimerRam.has_ram());
+        assert!(CartridgeType::Mbc2.has_ram());
+        assert!(!CartridgeType::Unknown(0x20).has_ram());
+    }
+
+    #[test]
+    fn write_tracked_reports_ram_and_rtc_changes() {
+        let mut cart = MbcCart::new(banked_rom(2, 0x10), vec![0u8; 0x2000], NoClock).unwrap();
+        // RAM 無効中の書き込みとバンクレジスタは数えない
+        assert!(!cart.write_tracked(0xA000, 0x12));
+        assert!(!cart.write_tracked(0x0000, 0x0A));
+        assert!(cart.write_tracked(0xA000, 0x12));
+        // RTC レジスタ（秒）の書き込みは .sav の RTC フッタが変わる
+        assert!(!cart.write_tracked(0x4000, 0x08));
+        assert!(cart.write_tracked(0xA000, 0x30));
+        // RAM の無いバンク
+        assert!(!cart.write_tracked(0x4000, 0x03));
+        assert!(!cart.write_tracked(0xA000, 0x12));
+    }
+
+    #[test]
+    fn borrowed_storage_and_save_state() {
+        let rom = banked_rom(4, 0x03);
+        let mut ram = [0u8; 0x2000];
+        let mut cart = MbcCart::new(&rom[..], &mut ram[..], NoClock).unwrap();
+        cart.write(0x0000, 0x0A);
+        cart.write(0x2000, 0x02);
+        cart.write(0xA010, 0x5A);
+        assert_eq!(cart.read(0x4000), 2);
+        assert_eq!(cart.read(0xA010), 0x5A);
+
+        let mut buf = [0u8; 0x2100];
+        let mut w = StateWriter::new(&mut buf);
+        cart.save_state(&mut w);
+        let len = w.finish().unwrap();
+
+        let mut ram2 = [0u8; 0x2000];
+        {
+            let mut restored = MbcCart::new(&rom[..], &mut ram2[..], NoClock).unwrap();
+            let mut r = StateReader::new(&buf[..len]);
+            restored.load_state(&mut r);
+            r.finish().unwrap();
+            assert_eq!(restored.read(0x4000), 2);
+            assert_eq!(restored.read(0xA010), 0x5A);
+        }
+        // RAM は借用したバッファに入っている
+        assert_eq!(ram2[0x10], 0x5A);
+    }
+}
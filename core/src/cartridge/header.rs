@@ -0,0 +1,522 @@
+//! ROM ヘッダ（0x0100–0x014F）の解析と検証。
+//!
+//! 起動できないもの（ROM が短い・未対応のカートリッジタイプ）は [`HeaderError`]、
+//! 実機では問題になりうるが動かせるもの（ロゴ・チェックサム・サイズの不一致）は
+//! [`HeaderWarning`] として [`Header::warnings`] に集める。
+
+use core::fmt;
+
+use super::CartridgeType;
+
+/// ヘッダを含む最小の ROM 長
+pub const HEADER_END: usize = 0x150;
+
+/// 0x0104–0x0133 の Nintendo ロゴ（BootROM が照合する）
+pub const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+/// 起動できないヘッダ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderError {
+    /// ROM がヘッダ末尾 (0x150) に届かない（実際の長さ）
+    TooSmall(usize),
+    /// 未対応のカートリッジタイプ (0x0147)
+    UnsupportedType(u8),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::TooSmall(len) => write!(f, "ROM file too small ({} bytes)", len),
+            HeaderError::UnsupportedType(t) => write!(f, "Unsupported cartridge type: 0x{:02X}", t),
+        }
+    }
+}
+
+impl core::error::Error for HeaderError {}
+
+/// 動作はするが正規の ROM とは食い違う点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderWarning {
+    /// Nintendo ロゴが一致しない（実機の BootROM はここで止まる）
+    BadLogo,
+    /// ヘッダチェックサム (0x014D) の不一致（実機の BootROM はここで止まる）
+    HeaderChecksum { stored: u8, computed: u8 },
+    /// グローバルチェックサム (0x014E–0x014F) の不一致（実機は検査しない）
+    GlobalChecksum { stored: u16, computed: u16 },
+    /// 未知の ROM サイズコード (0x0148)
+    UnknownRomSize(u8),
+    /// 未知の RAM サイズコード (0x0149)
+    UnknownRamSize(u8),
+    /// ROM サイズコードと実際のファイル長が合わない
+    RomSizeMismatch { header: usize, file: usize },
+}
+
+impl fmt::Display for HeaderWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            HeaderWarning::BadLogo => write!(f, "Nintendo logo mismatch"),
+            HeaderWarning::HeaderChecksum { stored, computed } => write!(
+                f,
+                "header checksum mismatch (stored 0x{:02X}, computed 0x{:02X})",
+                stored, computed
+            ),
+            HeaderWarning::GlobalChecksum { stored, computed } => write!(
+                f,
+                "global checksum mismatch (stored 0x{:04X}, computed 0x{:04X})",
+                stored, computed
+            ),
+            HeaderWarning::UnknownRomSize(code) => {
+                write!(f, "unknown ROM size code 0x{:02X}", code)
+            }
+            HeaderWarning::UnknownRamSize(code) => {
+                write!(f, "unknown RAM size code 0x{:02X}", code)
+            }
+            HeaderWarning::RomSizeMismatch { header, file } => write!(
+                f,
+                "ROM size mismatch (header {} bytes, file {} bytes)",
+                header, file
+            ),
+        }
+    }
+}
+
+/// 発売元コード。0x014B が 0x33 なら 0x0144–0x0145 の 2 文字コードを使う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Licensee {
+    Old(u8),
+    New([u8; 2]),
+}
+
+impl Licensee {
+    /// 既知の発売元なら社名
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            Licensee::Old(code) => old_licensee_name(code),
+            Licensee::New(code) => new_licensee_name(code),
+        }
+    }
+}
+
+impl fmt::Display for Licensee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Licensee::Old(code) => write!(f, "0x{:02X}", code)?,
+            Licensee::New([a, b]) => write!(f, "\"{}{}\"", *a as char, *b as char)?,
+        }
+        if let Some(name) = self.name() {
+            write!(f, " ({})", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析済みの ROM ヘッダ
+#[derive(Debug, Clone)]
+pub struct Header {
+    /// 0x0134–0x0142 のタイトル（NUL 埋め）
+    title: [u8; 15],
+    /// 0x0143: 0x80=CGB 対応、0xC0=CGB 専用、その他=DMG
+    pub cgb_flag: u8,
+    pub licensee: Licensee,
+    /// 0x0146: 0x03 なら SGB 機能を使う
+    pub sgb_flag: u8,
+    pub cartridge_type: CartridgeType,
+    /// 0x0148 の ROM サイズコード
+    pub rom_size: u8,
+    /// 0x0149 の RAM サイズコード
+    pub ram_size: u8,
+    /// 0x014A: 0x00=日本、0x01=海外
+    pub destination: u8,
+    /// 0x014C: マスク ROM のバージョン
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+    warnings: heapless::Vec<HeaderWarning, 8>,
+}
+
+impl Header {
+    /// ROM イメージ全体からヘッダを解析し、ロゴ・チェックサム・サイズを検証する。
+    /// 未対応のカートリッジタイプも [`CartridgeType::Unknown`] として読む（起動時に弾く）。
+    pub fn parse(rom: &[u8]) -> Result<Self, HeaderError> {
+        if rom.len() < HEADER_END {
+            return Err(HeaderError::TooSmall(rom.len()));
+        }
+        let cartridge_type = CartridgeType::from(rom[0x147]);
+        let mut title = [0; 15];
+        title.copy_from_slice(&rom[0x134..0x143]);
+        let licensee = match rom[0x14B] {
+            0x33 => Licensee::New([rom[0x144], rom[0x145]]),
+            code => Licensee::Old(code),
+        };
+        let mut header = Header {
+            title,
+            cgb_flag: rom[0x143],
+            licensee,
+            sgb_flag: rom[0x146],
+            cartridge_type,
+            rom_size: rom[0x148],
+            ram_size: rom[0x149],
+            destination: rom[0x14A],
+            version: rom[0x14C],
+            header_checksum: rom[0x14D],
+            global_checksum: u16::from_be_bytes([rom[0x14E], rom[0x14F]]),
+            warnings: heapless::Vec::new(),
+        };
+        header.validate(rom);
+        Ok(header)
+    }
+
+    fn validate(&mut self, rom: &[u8]) {
+        let mut warnings = heapless::Vec::new();
+        let mut warn = |w| {
+            // 容量は警告の種類数より多いので溢れない
+            let _ = warnings.push(w);
+        };
+        if rom[0x104..0x134] != NINTENDO_LOGO {
+            warn(HeaderWarning::BadLogo);
+        }
+        let computed = header_checksum(rom);
+        if computed != self.header_checksum {
+            warn(HeaderWarning::HeaderChecksum { stored: self.header_checksum, computed });
+        }
+        let computed = global_checksum(rom);
+        if computed != self.global_checksum {
+            warn(HeaderWarning::GlobalChecksum { stored: self.global_checksum, computed });
+        }
+        match rom_size_bytes(self.rom_size) {
+            None => warn(HeaderWarning::UnknownRomSize(self.rom_size)),
+            Some(header) if header != rom.len() => {
+                warn(HeaderWarning::RomSizeMismatch { header, file: rom.len() })
+            }
+            Some(_) => {}
+        }
+        if self.ram_size > 0x05 {
+            warn(HeaderWarning::UnknownRamSize(self.ram_size));
+        }
+        self.warnings = warnings;
+    }
+
+    /// タイトル（NUL 以降と ASCII でない部分を除く）
+    pub fn title(&self) -> &str {
+        let len = self.title.iter().position(|&b| b == 0).unwrap_or(self.title.len());
+        let bytes = &self.title[..len];
+        let ascii = bytes.iter().position(|b| !b.is_ascii()).unwrap_or(len);
+        // ASCII だけなので必ず UTF-8 として正しい
+        core::str::from_utf8(&bytes[..ascii]).unwrap_or("")
+    }
+
+    /// CGB 機能を使うソフトか（0x80 / 0xC0）
+    pub fn is_cgb(&self) -> bool {
+        matches!(self.cgb_flag, 0x80 | 0xC0)
+    }
+
+    /// SGB 機能を使うソフトか
+    pub fn is_sgb(&self) -> bool {
+        self.sgb_flag == 0x03
+    }
+
+    /// 日本向けか
+    pub fn is_japanese(&self) -> bool {
+        self.destination == 0x00
+    }
+
+    /// ヘッダが示す ROM のバイト数（未知のコードなら None）
+    pub fn rom_bytes(&self) -> Option<usize> {
+        rom_size_bytes(self.rom_size)
+    }
+
+    /// マッパが使う外部 RAM のバイト数
+    pub fn ram_bytes(&self) -> usize {
+        self.cartridge_type.ram_size(self.ram_size)
+    }
+
+    /// 検証で見つかった問題（無ければ空）
+    pub fn warnings(&self) -> &[HeaderWarning] {
+        &self.warnings
+    }
+}
+
+/// 0x0134–0x014C から計算したヘッダチェックサム
+pub fn header_checksum(rom: &[u8]) -> u8 {
+    rom[0x134..0x14D].iter().fold(0u8, |x, &b| x.wrapping_sub(b).wrapping_sub(1))
+}
+
+/// 0x014E–0x014F 以外の全バイトの和
+pub fn global_checksum(rom: &[u8]) -> u16 {
+    rom.iter()
+        .enumerate()
+        .filter(|&(i, _)| i != 0x14E && i != 0x14F)
+        .fold(0u16, |sum, (_, &b)| sum.wrapping_add(b as u16))
+}
+
+/// ROM サイズコード → バイト数（32KB << code）
+fn rom_size_bytes(code: u8) -> Option<usize> {
+    (code <= 0x08).then(|| 0x8000 << code)
+}
+
+/// 2 文字の発売元コード（GB の新コード・GBA のメーカーコード共通）
+pub fn new_licensee_name(code: [u8; 2]) -> Option<&'static str> {
+    Some(match &code {
+        b"00" => "None",
+        b"01" => "Nintendo",
+        b"08" => "Capcom",
+        b"13" => "Electronic Arts",
+        b"18" => "Hudson Soft",
+        b"19" => "B-AI",
+        b"20" => "KSS",
+        b"22" => "POW",
+        b"24" => "PCM Complete",
+        b"25" => "San-X",
+        b"28" => "Kemco Japan",
+        b"29" => "SETA",
+        b"30" => "Viacom",
+        b"31" => "Nintendo",
+        b"32" => "Bandai",
+        b"33" => "Ocean/Acclaim",
+        b"34" => "Konami",
+        b"35" => "Hector",
+        b"37" => "Taito",
+        b"38" => "Hudson",
+        b"39" => "Banpresto",
+        b"41" => "Ubi Soft",
+        b"42" => "Atlus",
+        b"44" => "Malibu",
+        b"46" => "Angel",
+        b"47" => "Bullet-Proof",
+        b"49" => "Irem",
+        b"50" => "Absolute",
+        b"51" => "Acclaim",
+        b"52" => "Activision",
+        b"53" => "American Sammy",
+        b"54" => "Konami",
+        b"55" => "Hi Tech Entertainment",
+        b"56" => "LJN",
+        b"57" => "Matchbox",
+        b"58" => "Mattel",
+        b"59" => "Milton Bradley",
+        b"60" => "Titus",
+        b"61" => "Virgin",
+        b"64" => "LucasArts",
+        b"67" => "Ocean",
+        b"69" => "Electronic Arts",
+        b"70" => "Infogrames",
+        b"71" => "Interplay",
+        b"72" => "Broderbund",
+        b"73" => "Sculptured",
+        b"75" => "SCI",
+        b"78" => "THQ",
+        b"79" => "Accolade",
+        b"80" => "Misawa",
+        b"83" => "Lozc",
+        b"86" => "Tokuma Shoten",
+        b"87" => "Tsukuda Original",
+        b"91" => "Chunsoft",
+        b"92" => "Video System",
+        b"93" => "Ocean/Acclaim",
+        b"95" => "Varie",
+        b"96" => "Yonezawa/s'pal",
+        b"97" => "Kaneko",
+        b"99" => "Pack-In-Soft",
+        b"A4" => "Konami",
+        _ => return None,
+    })
+}
+
+/// 0x014B の旧発売元コード
+fn old_licensee_name(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0x00 => "None",
+        0x01 | 0x31 => "Nintendo",
+        0x08 | 0x38 => "Capcom",
+        0x09 => "Hot-B",
+        0x0A | 0xE0 => "Jaleco",
+        0x0B => "Coconuts",
+        0x0C | 0x6E => "Elite Systems",
+        0x13 | 0x69 => "Electronic Arts",
+        0x18 => "Hudson Soft",
+        0x19 => "ITC Entertainment",
+        0x1A => "Yanoman",
+        0x1D => "Clary",
+        0x1F | 0x4A | 0x61 => "Virgin",
+        0x24 => "PCM Complete",
+        0x25 => "San-X",
+        0x28 => "Kotobuki Systems",
+        0x29 => "SETA",
+        0x30 | 0x70 => "Infogrames",
+        0x32 | 0xA2 | 0xB2 => "Bandai",
+        0x34 | 0xA4 => "Konami",
+        0x35 => "Hector",
+        0x39 | 0x9D | 0xD9 => "Banpresto",
+        0x3C => "Entertainment i",
+        0x3E => "Gremlin",
+        0x41 => "Ubi Soft",
+        0x42 | 0xEB => "Atlus",
+        0x44 | 0x4D => "Malibu",
+        0x46 | 0xCF => "Angel",
+        0x47 => "Spectrum Holobyte",
+        0x49 => "Irem",
+        0x4F => "U.S. Gold",
+        0x50 => "Absolute",
+        0x51 | 0xB0 => "Acclaim",
+        0x52 => "Activision",
+        0x53 => "American Sammy",
+        0x54 => "GameTek",
+        0x55 => "Park Place",
+        0x56 | 0xDB | 0xFF => "LJN",
+        0x57 => "Matchbox",
+        0x59 => "Milton Bradley",
+        0x5A => "Mindscape",
+        0x5B => "Romstar",
+        0x5C | 0xD6 => "Naxat Soft",
+        0x5D => "Tradewest",
+        0x60 => "Titus",
+        0x67 => "Ocean",
+        0x6F => "Electro Brain",
+        0x71 => "Interplay",
+        0x72 | 0xAA => "Broderbund",
+        0x73 => "Sculptured Soft",
+        0x75 => "The Sales Curve",
+        0x78 => "THQ",
+        0x79 => "Accolade",
+        0x7A => "Triffix Entertainment",
+        0x7C => "Microprose",
+        0x7F | 0xC2 => "Kemco",
+        0x80 => "Misawa Entertainment",
+        0x83 => "Lozc",
+        0x86 | 0xC4 => "Tokuma Shoten",
+        0x8B => "Bullet-Proof Software",
+        0x8C => "Vic Tokai",
+        0x8E => "Ape",
+        0x8F => "I'Max",
+        0x91 => "Chunsoft",
+        0x92 => "Video System",
+        0x93 => "Tsubaraya Productions",
+        0x95 | 0xE3 => "Varie",
+        0x96 => "Yonezawa/s'pal",
+        0x97 => "Kaneko",
+        0x99 => "Arc",
+        0x9A => "Nihon Bussan",
+        0x9B => "Tecmo",
+        0x9C => "Imagineer",
+        0x9F => "Nova",
+        0xA1 => "Hori Electric",
+        0xA6 => "Kawada",
+        0xA7 => "Takara",
+        0xA9 => "Technos Japan",
+        0xAC => "Toei Animation",
+        0xAD => "Toho",
+        0xAF => "Namco",
+        0xB1 => "ASCII / Nexsoft",
+        0xB4 => "Enix",
+        0xB6 => "HAL Laboratory",
+        0xB7 => "SNK",
+        0xB9 | 0xCE => "Pony Canyon",
+        0xBA => "Culture Brain",
+        0xBB => "Sunsoft",
+        0xBD => "Sony Imagesoft",
+        0xBF => "Sammy",
+        0xC0 | 0xD0 => "Taito",
+        0xC3 => "Squaresoft",
+        0xC5 => "Data East",
+        0xC6 => "Tonkin House",
+        0xC8 => "Koei",
+        0xC9 => "UFL",
+        0xCA => "Ultra",
+        0xCB => "Vap",
+        0xCC => "Use",
+        0xCD => "Meldac",
+        0xD1 => "Sofel",
+        0xD2 => "Quest",
+        0xD3 => "Sigma Enterprises",
+        0xD4 => "ASK Kodansha",
+        0xD7 => "Copya System",
+        0xDA => "Tomy",
+        0xDD => "NCS",
+        0xDE => "Human",
+        0xDF => "Altron",
+        0xE1 => "Towa Chiki",
+        0xE2 => "Yutaka",
+        0xE5 => "Epoch",
+        0xE7 => "Athena",
+        0xE8 => "Asmik",
+        0xE9 => "Natsume",
+        0xEA => "King Records",
+        0xEC => "Epic / Sony Records",
+        0xEE => "IGS",
+        0xF0 => "A Wave",
+        0xF3 => "Extreme Entertainment",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ロゴとチェックサムが正しい 32KB の ROM
+    fn valid_rom() -> Vec<u8> {
+        let mut rom = vec![0; 0x8000];
+        rom[0x104..0x134].copy_from_slice(&NINTENDO_LOGO);
+        rom[0x134..0x13A].copy_from_slice(b"TETRIS");
+        rom[0x14B] = 0x01;
+        fix_checksums(&mut rom);
+        rom
+    }
+
+    fn fix_checksums(rom: &mut [u8]) {
+        rom[0x14D] = header_checksum(rom);
+        let [hi, lo] = global_checksum(rom).to_be_bytes();
+        rom[0x14E] = hi;
+        rom[0x14F] = lo;
+    }
+
+    #[test]
+    fn valid_header_has_no_warnings() {
+        let header = Header::parse(&valid_rom()).unwrap();
+        assert_eq!(header.title(), "TETRIS");
+        assert_eq!(header.cartridge_type, CartridgeType::RomOnly);
+        assert_eq!(header.licensee.name(), Some("Nintendo"));
+        assert_eq!(header.rom_bytes(), Some(0x8000));
+        assert!(header.is_japanese() && !header.is_cgb() && !header.is_sgb());
+        assert!(header.warnings().is_empty(), "{:?}", header.warnings());
+    }
+
+    #[test]
+    fn new_licensee_code_is_used_when_old_code_is_0x33() {
+        let mut rom = valid_rom();
+        rom[0x14B] = 0x33;
+        rom[0x144..0x146].copy_from_slice(b"52");
+        let header = Header::parse(&rom).unwrap();
+        assert_eq!(header.licensee, Licensee::New(*b"52"));
+        assert_eq!(header.licensee.name(), Some("Activision"));
+    }
+
+    #[test]
+    fn broken_header_is_reported_as_warnings() {
+        let mut rom = valid_rom();
+        rom[0x104] = 0;
+        rom[0x148] = 0x01; // 64KB と申告するが実際は 32KB
+        rom[0x149] = 0x09;
+        let header = Header::parse(&rom).unwrap();
+        let w = header.warnings();
+        assert!(w.contains(&HeaderWarning::BadLogo));
+        assert!(w.iter().any(|w| matches!(w, HeaderWarning::HeaderChecksum { .. })));
+        assert!(w.iter().any(|w| matches!(w, HeaderWarning::GlobalChecksum { .. })));
+        assert!(w.contains(&HeaderWarning::RomSizeMismatch { header: 0x10000, file: 0x8000 }));
+        assert!(w.contains(&HeaderWarning::UnknownRamSize(0x09)));
+    }
+
+    #[test]
+    fn short_rom_is_an_error_but_unknown_type_is_kept() {
+        assert_eq!(Header::parse(&[0; 0x100]).unwrap_err(), HeaderError::TooSmall(0x100));
+        let mut rom = valid_rom();
+        rom[0x147] = 0x20; // MBC6
+        let header = Header::parse(&rom).unwrap();
+        assert_eq!(header.cartridge_type, CartridgeType::Unknown(0x20));
+        assert_eq!(header.cartridge_type.code(), 0x20);
+    }
+}
@@ -0,0 +1,103 @@
+//! HuC1: MBC1 に似たバンク切り替えと赤外線通信ポート。
+//!
+//! 0x0000–0x1FFF に 0x0E を書くと 0xA000–0xBFFF が赤外線ポートになり、それ以外の値で RAM に戻る。
+//! 赤外線は相手がいないものとして常に「受光なし」を返す。
+
+use super::{ram_index, rom_byte};
+use crate::savestate::{StateReader, StateWriter};
+
+/// 赤外線ポートの読み出し値（受光なし）
+const IR_NO_LIGHT: u8 = 0xC0;
+
+pub struct HuC1 {
+    rom_bank: u8,
+    ram_bank: u8,
+    ir_mode: bool,
+}
+
+impl HuC1 {
+    pub fn new() -> Self {
+        Self { rom_bank: 1, ram_bank: 0, ir_mode: false }
+    }
+
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク
+    pub fn rom_bank_at(&self, _rom: &[u8], addr: u16) -> usize {
+        if addr < 0x4000 { 0 } else { self.rom_bank as usize }
+    }
+
+    /// 0xA000–0xBFFF に割り当て中の RAM バンク
+    pub fn ram_bank(&self) -> usize {
+        self.ram_bank as usize
+    }
+
+    pub fn read(&self, rom: &[u8], ram: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => rom_byte(rom, self.rom_bank_at(rom, addr), addr),
+            0xA000..=0xBFFF if self.ir_mode => IR_NO_LIGHT,
+            0xA000..=0xBFFF => {
+                ram_index(ram, self.ram_bank as usize, addr).map_or(0xFF, |i| ram[i])
+            }
+            _ => 0xFF,
+        }
+    }
+
+    /// 外部 RAM を書き換えたら true
+    pub fn write(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        match addr {
+            0x0000..=0x1FFF => self.ir_mode = value == 0x0E,
+            0x2000..=0x3FFF => self.rom_bank = (value & 0x3F).max(1),
+            0x4000..=0x5FFF => self.ram_bank = value & 0x03,
+            // IR モード中の書き込みは送信 LED（相手がいないので無視）
+            0xA000..=0xBFFF if !self.ir_mode => {
+                if let Some(i) = ram_index(ram, self.ram_bank as usize, addr) {
+                    ram[i] = value;
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    pub(super) fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.rom_bank);
+        w.u8(self.ram_bank);
+        w.bool(self.ir_mode);
+    }
+
+    pub(super) fn load_state(&mut self, r: &mut StateReader) {
+        self.rom_bank = r.u8();
+        self.ram_bank = r.u8();
+        self.ir_mode = r.bool();
+    }
+}
+
+impl Default for HuC1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::tests::banked_rom;
+
+    #[test]
+    fn switches_between_ram_and_ir() {
+        let rom = banked_rom(64, 0xFF);
+        let mut ram = [0u8; 0x8000];
+        let mut mbc = HuC1::new();
+        mbc.write(&mut ram, 0x2000, 0x21);
+        assert_eq!(mbc.read(&rom, &ram, 0x4000), 0x21);
+        mbc.write(&mut ram, 0x4000, 0x02);
+        mbc.write(&mut ram, 0xA000, 0x12);
+        assert_eq!(ram[0x4000], 0x12);
+        // IR モードでは受光なし、書き込みは RAM に届かない
+        mbc.write(&mut ram, 0x0000, 0x0E);
+        assert_eq!(mbc.read(&rom, &ram, 0xA000), IR_NO_LIGHT);
+        mbc.write(&mut ram, 0xA000, 0x01);
+        mbc.write(&mut ram, 0x0000, 0x00);
+        assert_eq!(mbc.read(&rom, &ram, 0xA000), 0x12);
+    }
+}
@@ -0,0 +1,137 @@
+//! MBC1: ROM 最大 2MB / RAM 最大 32KB。
+
+use super::{ram_index, rom_byte};
+use crate::savestate::{StateReader, StateWriter};
+
+/// 0x4000 の 2 bit レジスタが ROM バンクの上位ビットに使われる ROM サイズの下限（1MB 以上）
+const LARGE_ROM: usize = 0x80000;
+
+pub struct Mbc1 {
+    /// ROM バンク下位 5 bit（書き込んだ値のまま。0 の補正は読み出し時）
+    rom_bank: u8,
+    /// 0x4000 の 2 bit レジスタ（RAM バンク、または 1MB 以上の ROM のバンク bit5–6）
+    ram_bank: u8,
+    ram_enabled: bool,
+    /// false = ROM banking mode, true = RAM banking mode
+    mode: bool,
+}
+
+impl Mbc1 {
+    pub fn new() -> Self {
+        Self { rom_bank: 1, ram_bank: 0, ram_enabled: false, mode: false }
+    }
+
+    fn upper_bits(&self, rom: &[u8]) -> usize {
+        if rom.len() > LARGE_ROM { (self.ram_bank as usize & 0x03) << 5 } else { 0 }
+    }
+
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク
+    pub fn rom_bank_at(&self, rom: &[u8], addr: u16) -> usize {
+        if addr < 0x4000 {
+            // モード 1 では 0x0000–0x3FFF にも上位ビットが効く
+            if self.mode { self.upper_bits(rom) } else { 0 }
+        } else {
+            // 下位 5 bit の 0→1 補正は上位ビットと OR する前に行う
+            // (0x00/0x20/0x40/0x60 はそれぞれ 0x01/0x21/0x41/0x61 になる)
+            (self.rom_bank & 0x1F).max(1) as usize | self.upper_bits(rom)
+        }
+    }
+
+    /// 0xA000–0xBFFF に割り当て中の RAM バンク
+    pub fn ram_bank(&self) -> usize {
+        self.ram_bank_for_ram()
+    }
+
+    pub fn read(&self, rom: &[u8], ram: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => rom_byte(rom, self.rom_bank_at(rom, addr), addr),
+            0xA000..=0xBFFF if self.ram_enabled => {
+                ram_index(ram, self.ram_bank_for_ram(), addr).map_or(0xFF, |i| ram[i])
+            }
+            _ => 0xFF,
+        }
+    }
+
+    /// 外部 RAM を書き換えたら true
+    pub fn write(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = value & 0x1F,
+            0x4000..=0x5FFF => self.ram_bank = value & 0x03,
+            0x6000..=0x7FFF => self.mode = (value & 0x01) != 0,
+            0xA000..=0xBFFF if self.ram_enabled => {
+                if let Some(i) = ram_index(ram, self.ram_bank_for_ram(), addr) {
+                    ram[i] = value;
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn ram_bank_for_ram(&self) -> usize {
+        if self.mode { self.ram_bank as usize } else { 0 }
+    }
+
+    pub(super) fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.rom_bank);
+        w.u8(self.ram_bank);
+        w.bool(self.ram_enabled);
+        w.bool(self.mode);
+    }
+
+    pub(super) fn load_state(&mut self, r: &mut StateReader) {
+        self.rom_bank = r.u8();
+        self.ram_bank = r.u8();
+        self.ram_enabled = r.bool();
+        self.mode = r.bool();
+    }
+}
+
+impl Default for Mbc1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::tests::banked_rom;
+
+    #[test]
+    fn bank_zero_fixup_happens_before_upper_bits() {
+        let rom = banked_rom(128, 0x01);
+        let mut mbc = Mbc1::new();
+        mbc.write(&mut [], 0x2000, 0x00);
+        assert_eq!(mbc.read(&rom, &[], 0x4000), 0x01);
+        // 上位ビットはモード 0 でも 0x4000–0x7FFF に効く
+        mbc.write(&mut [], 0x4000, 0x01);
+        assert_eq!(mbc.read(&rom, &[], 0x4000), 0x21);
+        assert_eq!(mbc.read(&rom, &[], 0x0000), 0x00);
+        // モード 1 では 0x0000–0x3FFF も切り替わる
+        mbc.write(&mut [], 0x6000, 0x01);
+        assert_eq!(mbc.read(&rom, &[], 0x0000), 0x20);
+        mbc.write(&mut [], 0x2000, 0x05);
+        assert_eq!(mbc.read(&rom, &[], 0x4000), 0x25);
+    }
+
+    #[test]
+    fn ram_banks_only_in_mode_1() {
+        let mut ram = [0u8; 0x8000];
+        let mut mbc = Mbc1::new();
+        mbc.write(&mut ram, 0xA000, 0x11);
+        assert_eq!(ram[0], 0x00);
+        mbc.write(&mut ram, 0x0000, 0x0A);
+        mbc.write(&mut ram, 0x4000, 0x02);
+        mbc.write(&mut ram, 0xA000, 0x22);
+        assert_eq!(ram[0], 0x22);
+        assert_eq!(mbc.ram_bank(), 0);
+        mbc.write(&mut ram, 0x6000, 0x01);
+        mbc.write(&mut ram, 0xA000, 0x33);
+        assert_eq!(ram[0x4000], 0x33);
+        assert_eq!(mbc.ram_bank(), 2);
+        assert_eq!(mbc.read(&[], &ram, 0xA000), 0x33);
+    }
+}
@@ -0,0 +1,101 @@
+//! MBC2: ROM 最大 256KB と 512×4 bit の内蔵 RAM。
+//!
+//! 0x0000–0x3FFF への書き込みはアドレスの bit8 でレジスタを選ぶ（0=RAM 有効化, 1=ROM バンク）。
+//! 内蔵 RAM は 0xA000–0xA1FF で、0xBFFF まで繰り返し見える。上位 4 bit は読むと 1。
+
+use super::rom_byte;
+use crate::savestate::{StateReader, StateWriter};
+
+/// 内蔵 RAM のサイズ（4 bit × 512）
+pub(super) const RAM_SIZE: usize = 0x200;
+
+pub struct Mbc2 {
+    rom_bank: u8,
+    ram_enabled: bool,
+}
+
+impl Mbc2 {
+    pub fn new() -> Self {
+        Self { rom_bank: 1, ram_enabled: false }
+    }
+
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク
+    pub fn rom_bank_at(&self, _rom: &[u8], addr: u16) -> usize {
+        if addr < 0x4000 { 0 } else { self.rom_bank as usize }
+    }
+
+    pub fn read(&self, rom: &[u8], ram: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => rom_byte(rom, self.rom_bank_at(rom, addr), addr),
+            0xA000..=0xBFFF if self.ram_enabled => {
+                ram.get(addr as usize & (RAM_SIZE - 1)).map_or(0xFF, |v| v | 0xF0)
+            }
+            _ => 0xFF,
+        }
+    }
+
+    /// 外部 RAM を書き換えたら true
+    pub fn write(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        match addr {
+            0x0000..=0x3FFF => {
+                if addr & 0x0100 == 0 {
+                    self.ram_enabled = (value & 0x0F) == 0x0A;
+                } else {
+                    self.rom_bank = (value & 0x0F).max(1);
+                }
+            }
+            0xA000..=0xBFFF if self.ram_enabled => {
+                if let Some(v) = ram.get_mut(addr as usize & (RAM_SIZE - 1)) {
+                    *v = value & 0x0F;
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    pub(super) fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.rom_bank);
+        w.bool(self.ram_enabled);
+    }
+
+    pub(super) fn load_state(&mut self, r: &mut StateReader) {
+        self.rom_bank = r.u8();
+        self.ram_enabled = r.bool();
+    }
+}
+
+impl Default for Mbc2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::tests::banked_rom;
+
+    #[test]
+    fn selects_register_by_address_bit8_and_mirrors_nibble_ram() {
+        let rom = banked_rom(16, 0x05);
+        let mut ram = [0u8; RAM_SIZE];
+        let mut mbc = Mbc2::new();
+        // bit8=1: ROM バンク
+        mbc.write(&mut ram, 0x2100, 0x03);
+        assert_eq!(mbc.read(&rom, &ram, 0x4000), 3);
+        mbc.write(&mut ram, 0x0100, 0x00);
+        assert_eq!(mbc.read(&rom, &ram, 0x4000), 1);
+        // bit8=0: RAM 有効化（ROM バンクは変わらない）
+        mbc.write(&mut ram, 0x0000, 0x0A);
+        assert_eq!(mbc.read(&rom, &ram, 0x4000), 1);
+        mbc.write(&mut ram, 0xA005, 0xAB);
+        assert_eq!(mbc.read(&rom, &ram, 0xA005), 0xFB);
+        // 512 バイトごとに繰り返し見える
+        assert_eq!(mbc.read(&rom, &ram, 0xA205), 0xFB);
+        assert_eq!(mbc.read(&rom, &ram, 0xBE05), 0xFB);
+        mbc.write(&mut ram, 0x0000, 0x00);
+        assert_eq!(mbc.read(&rom, &ram, 0xA005), 0xFF);
+    }
+}
@@ -0,0 +1,202 @@
+//! MBC3: ROM 最大 2MB / RAM 32KB と、MBC3+TIMER のリアルタイムクロック。
+
+use super::{Rtc, ram_index, rom_byte};
+use crate::savestate::{StateReader, StateWriter};
+
+pub struct Mbc3 {
+    rom_bank: u8,
+    /// 0x00–0x03 は RAM バンク、0x08–0x0C は RTC レジスタ
+    ram_bank: u8,
+    ram_enabled: bool,
+    /// MBC3+TIMER のみ
+    pub(super) rtc: Option<Rtc>,
+}
+
+impl Mbc3 {
+    pub fn new(rtc: Option<Rtc>) -> Self {
+        Self { rom_bank: 1, ram_bank: 0, ram_enabled: false, rtc }
+    }
+
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク
+    pub fn rom_bank_at(&self, _rom: &[u8], addr: u16) -> usize {
+        if addr < 0x4000 { 0 } else { self.rom_bank as usize }
+    }
+
+    /// 0xA000–0xBFFF に割り当て中の RAM バンク
+    pub fn ram_bank(&self) -> usize {
+        self.ram_bank as usize
+    }
+
+    pub fn read(&self, rom: &[u8], ram: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => rom_byte(rom, self.rom_bank_at(rom, addr), addr),
+            0xA000..=0xBFFF if self.ram_enabled => match (self.ram_bank, &self.rtc) {
+                (0x00..=0x03, _) => {
+                    ram_index(ram, self.ram_bank as usize, addr).map_or(0xFF, |i| ram[i])
+                }
+                (0x08..=0x0C, Some(rtc)) => rtc.read(self.ram_bank),
+                _ => 0xFF,
+            },
+            _ => 0xFF,
+        }
+    }
+
+    /// `now` は RTC の時刻源の現在値（RTC なしなら使わない）
+    /// 外部 RAM か RTC レジスタを書き換えたら true
+    pub fn write(&mut self, ram: &mut [u8], addr: u16, value: u8, now: u64) -> bool {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = (value & 0x7F).max(1),
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            0x6000..=0x7FFF => {
+                if let Some(rtc) = &mut self.rtc {
+                    rtc.write_latch(value, now);
+                }
+            }
+            0xA000..=0xBFFF if self.ram_enabled => match (self.ram_bank, &mut self.rtc) {
+                (0x00..=0x03, _) => {
+                    if let Some(i) = ram_index(ram, self.ram_bank as usize, addr) {
+                        ram[i] = value;
+                        return true;
+                    }
+                }
+                (0x08..=0x0C, Some(rtc)) => {
+                    rtc.write(self.ram_bank, value, now);
+                    return true;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        false
+    }
+
+    pub(super) fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.rom_bank);
+        w.u8(self.ram_bank);
+        w.bool(self.ram_enabled);
+        if let Some(rtc) = &self.rtc {
+            rtc.save_state(w);
+        }
+    }
+
+    pub(super) fn load_state(&mut self, r: &mut StateReader) {
+        self.rom_bank = r.u8();
+        self.ram_bank = r.u8();
+        self.ram_enabled = r.bool();
+        if let Some(rtc) = &mut self.rtc {
+            rtc.load_state(r);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rtc::{DH_CARRY, DH_DAY_HI, DH_HALT};
+    use super::super::{Clock, MbcCart};
+    use crate::platform::CartridgeBus;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// テストから進められる時刻源
+    struct FakeClock(Rc<Cell<u64>>);
+
+    impl Clock for FakeClock {
+        fn now(&self) -> u64 {
+            self.0.get()
+        }
+    }
+
+    type RtcCart = MbcCart<Vec<u8>, Vec<u8>, FakeClock>;
+
+    fn rtc_cart() -> (RtcCart, Rc<Cell<u64>>) {
+        let time = Rc::new(Cell::new(1_000_000));
+        let mut rom = vec![0; 0x8000];
+        rom[0x0147] = 0x10;
+        let cart = MbcCart::new(rom, vec![0; 0x2000], FakeClock(time.clone())).unwrap();
+        (cart, time)
+    }
+
+    fn latch(cart: &mut RtcCart) {
+        cart.write(0x6000, 0x00);
+        cart.write(0x6000, 0x01);
+    }
+
+    fn read_rtc(cart: &mut RtcCart, reg: u8) -> u8 {
+        cart.write(0x4000, reg);
+        cart.read(0xA000)
+    }
+
+    #[test]
+    fn rtc_counts_and_latches() {
+        let (mut cart, time) = rtc_cart();
+        cart.write(0x0000, 0x0A);
+        time.set(time.get() + 3 * 86400 + 5 * 3600 + 7 * 60 + 9);
+        // ラッチ前は古い値のまま
+        assert_eq!(read_rtc(&mut cart, 0x08), 0);
+        latch(&mut cart);
+        assert_eq!(read_rtc(&mut cart, 0x08), 9);
+        assert_eq!(read_rtc(&mut cart, 0x09), 7);
+        assert_eq!(read_rtc(&mut cart, 0x0A), 5);
+        assert_eq!(read_rtc(&mut cart, 0x0B), 3);
+        // 0x01 を続けて書いてもラッチし直さない
+        time.set(time.get() + 10);
+        cart.write(0x6000, 0x01);
+        assert_eq!(read_rtc(&mut cart, 0x08), 9);
+    }
+
+    #[test]
+    fn rtc_halt_and_day_carry() {
+        let (mut cart, time) = rtc_cart();
+        cart.write(0x0000, 0x0A);
+        // 511 日 23:59:59 に設定
+        cart.write(0x4000, 0x0C);
+        cart.write(0xA000, DH_HALT | DH_DAY_HI);
+        for (reg, val) in [(0x08, 59), (0x09, 59), (0x0A, 23), (0x0B, 0xFF)] {
+            cart.write(0x4000, reg);
+            cart.write(0xA000, val);
+        }
+        // 停止中は進まない
+        time.set(time.get() + 100);
+        latch(&mut cart);
+        assert_eq!(read_rtc(&mut cart, 0x08), 59);
+        // 再開して 1 秒で 0 日に戻り、桁あふれフラグが立つ
+        cart.write(0x4000, 0x0C);
+        cart.write(0xA000, DH_DAY_HI);
+        time.set(time.get() + 1);
+        latch(&mut cart);
+        assert_eq!(read_rtc(&mut cart, 0x08), 0);
+        assert_eq!(read_rtc(&mut cart, 0x0B), 0);
+        assert_eq!(read_rtc(&mut cart, 0x0C), DH_CARRY);
+    }
+
+    #[test]
+    fn rtc_footer_resumes_with_elapsed_time() {
+        let (mut cart, time) = rtc_cart();
+        cart.write(0x0000, 0x0A);
+        time.set(time.get() + 30);
+        let (rtc, now) = cart.rtc_mut().unwrap();
+        let footer = rtc.to_footer(now);
+
+        // 1 時間後に別セッションで読み込む
+        let (mut resumed, later) = rtc_cart();
+        later.set(now + 3600);
+        resumed.write(0x0000, 0x0A);
+        assert!(resumed.rtc_mut().unwrap().0.load_footer(&footer));
+        latch(&mut resumed);
+        assert_eq!(read_rtc(&mut resumed, 0x08), 30);
+        assert_eq!(read_rtc(&mut resumed, 0x0A), 1);
+    }
+
+    #[test]
+    fn rtc_out_of_range_seconds_wrap_without_carry() {
+        let (mut cart, time) = rtc_cart();
+        cart.write(0x0000, 0x0A);
+        cart.write(0x4000, 0x08);
+        cart.write(0xA000, 62);
+        time.set(time.get() + 2);
+        latch(&mut cart);
+        assert_eq!(read_rtc(&mut cart, 0x08), 0);
+        assert_eq!(read_rtc(&mut cart, 0x09), 0);
+    }
+}
@@ -0,0 +1,94 @@
+//! MBC5: 9 bit ROM バンク（最大 8MB）と 4 bit RAM バンク（最大 128KB）。
+
+use super::{ram_index, rom_byte};
+use crate::savestate::{StateReader, StateWriter};
+
+pub struct Mbc5 {
+    rom_bank: u16,
+    ram_bank: u8,
+    ram_enabled: bool,
+}
+
+impl Mbc5 {
+    pub fn new() -> Self {
+        Self { rom_bank: 1, ram_bank: 0, ram_enabled: false }
+    }
+
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク。
+    /// MBC5 だけはバンク 0 もそのまま選べる
+    pub fn rom_bank_at(&self, _rom: &[u8], addr: u16) -> usize {
+        if addr < 0x4000 { 0 } else { self.rom_bank as usize }
+    }
+
+    /// 0xA000–0xBFFF に割り当て中の RAM バンク
+    pub fn ram_bank(&self) -> usize {
+        self.ram_bank as usize
+    }
+
+    pub fn read(&self, rom: &[u8], ram: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => rom_byte(rom, self.rom_bank_at(rom, addr), addr),
+            0xA000..=0xBFFF if self.ram_enabled => {
+                ram_index(ram, self.ram_bank as usize, addr).map_or(0xFF, |i| ram[i])
+            }
+            _ => 0xFF,
+        }
+    }
+
+    /// 外部 RAM を書き換えたら true
+    pub fn write(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => {
+                self.rom_bank = (self.rom_bank & 0x0FF) | ((value as u16 & 0x01) << 8);
+            }
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            0xA000..=0xBFFF if self.ram_enabled => {
+                if let Some(i) = ram_index(ram, self.ram_bank as usize, addr) {
+                    ram[i] = value;
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    pub(super) fn save_state(&self, w: &mut StateWriter) {
+        w.u16(self.rom_bank);
+        w.u8(self.ram_bank);
+        w.bool(self.ram_enabled);
+    }
+
+    pub(super) fn load_state(&mut self, r: &mut StateReader) {
+        self.rom_bank = r.u16();
+        self.ram_bank = r.u8();
+        self.ram_enabled = r.bool();
+    }
+}
+
+impl Default for Mbc5 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::tests::banked_rom;
+
+    #[test]
+    fn nine_bit_rom_bank_and_bank_zero() {
+        let rom = banked_rom(512, 0x19);
+        let mut mbc = Mbc5::new();
+        mbc.write(&mut [], 0x2000, 0x00);
+        assert_eq!(mbc.read(&rom, &[], 0x4000), 0x00);
+        mbc.write(&mut [], 0x2000, 0x34);
+        mbc.write(&mut [], 0x3000, 0x01);
+        // 先頭バイトはバンク番号の下位 8 bit
+        assert_eq!(mbc.read(&rom, &[], 0x4000), 0x34);
+        assert_eq!(mbc.rom_bank, 0x134);
+    }
+}
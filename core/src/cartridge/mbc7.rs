@@ -0,0 +1,400 @@
+//! MBC7: 2 軸加速度センサと 93LC56 シリアル EEPROM を持つカート（コロコロカービィ等）。
+//!
+//! 0xA000–0xAFFF は RAM 有効化 (0x0000 に 0x0A、0x4000 に 0x40) の後、アドレスの bit4–7 で
+//! レジスタを選ぶ: 0x55→0xAA の書き込みでセンサ値をラッチし、Ax2x–Ax5x で X/Y を読む。
+//! Ax8x は EEPROM のピン (bit7=CS, bit6=CLK, bit1=DI, bit0=DO)。
+
+use super::rom_byte;
+use crate::savestate::{StateReader, StateWriter};
+
+/// EEPROM (93LC56, 16 bit × 128 ワード) のサイズ
+pub(super) const EEPROM_SIZE: usize = 0x100;
+/// 水平時の加速度センサの値と 1G あたりの変化量
+const ACCEL_CENTER: f32 = 0x81D0 as f32;
+const ACCEL_1G: f32 = 0x70 as f32;
+
+/// 93LC56 のコマンド処理状態
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EepromState {
+    /// スタートビット (1) 待ち
+    Idle,
+    /// オペコード 2 bit + アドレス 8 bit の受信中
+    Command { bits: u8, value: u16 },
+    /// READ: データを上位ビットから出力中
+    Reading { bits: u8, value: u16 },
+    /// WRITE / WRAL: 書き込むデータの受信中（`addr` が None なら全ワード）
+    Writing { addr: Option<u8>, bits: u8, value: u16 },
+    /// コマンド完了。CS が下がるまで入力を無視する
+    Done,
+}
+
+/// 93LC56 シリアル EEPROM。CS・CLK・DI をソフトウェアで操作し、CLK の立ち上がりで 1 bit ずつ進む。
+/// 内容はカートの RAM スライス（16 bit ワードをリトルエンディアンで並べた 256 バイト）に置く。
+struct Eeprom {
+    cs: bool,
+    clk: bool,
+    di: bool,
+    dout: bool,
+    write_enabled: bool,
+    state: EepromState,
+}
+
+impl Eeprom {
+    fn new() -> Self {
+        Self {
+            cs: false,
+            clk: false,
+            di: false,
+            dout: true,
+            write_enabled: false,
+            state: EepromState::Idle,
+        }
+    }
+
+    fn word(data: &[u8], addr: u8) -> u16 {
+        let i = (addr as usize & 0x7F) * 2;
+        match data.get(i..i + 2) {
+            Some(w) => u16::from_le_bytes([w[0], w[1]]),
+            None => 0xFFFF,
+        }
+    }
+
+    fn set_word(data: &mut [u8], addr: u8, value: u16) {
+        let i = (addr as usize & 0x7F) * 2;
+        if let Some(w) = data.get_mut(i..i + 2) {
+            w.copy_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn read(&self) -> u8 {
+        (self.cs as u8) << 7 | (self.clk as u8) << 6 | (self.di as u8) << 1 | self.dout as u8
+    }
+
+    /// ピンへの書き込み。EEPROM の内容を書き換えたら true
+    fn write(&mut self, data: &mut [u8], value: u8) -> bool {
+        let cs = value & 0x80 != 0;
+        let clk = value & 0x40 != 0;
+        self.di = value & 0x02 != 0;
+        let mut changed = false;
+        if !cs {
+            // CS を下げるとコマンドは中断され、DO は書き込み完了（レディ）を示す
+            self.state = EepromState::Idle;
+            self.dout = true;
+        } else if !self.clk && clk {
+            changed = self.clock_bit(data, self.di);
+        }
+        self.cs = cs;
+        self.clk = clk;
+        changed
+    }
+
+    fn clock_bit(&mut self, data: &mut [u8], bit: bool) -> bool {
+        let mut changed = false;
+        self.state = match self.state {
+            EepromState::Idle if bit => EepromState::Command { bits: 0, value: 0 },
+            EepromState::Idle => EepromState::Idle,
+            EepromState::Command { bits, value } => {
+                let value = value << 1 | bit as u16;
+                if bits + 1 < 10 {
+                    EepromState::Command { bits: bits + 1, value }
+                } else {
+                    let (state, written) = self.command(data, (value >> 8) as u8, value as u8);
+                    changed = written;
+                    state
+                }
+            }
+            EepromState::Reading { bits, value } => {
+                self.dout = value & 0x8000 != 0;
+                if bits > 1 {
+                    EepromState::Reading { bits: bits - 1, value: value << 1 }
+                } else {
+                    EepromState::Done
+                }
+            }
+            EepromState::Writing { addr, bits, value } => {
+                let value = value << 1 | bit as u16;
+                if bits + 1 < 16 {
+                    EepromState::Writing { addr, bits: bits + 1, value }
+                } else {
+                    if self.write_enabled {
+                        match addr {
+                            Some(addr) => Self::set_word(data, addr, value),
+                            None => (0..0x80).for_each(|a| Self::set_word(data, a, value)),
+                        }
+                        changed = true;
+                    }
+                    EepromState::Done
+                }
+            }
+            EepromState::Done => EepromState::Done,
+        };
+        changed
+    }
+
+    /// 10 bit 受信したコマンドを実行する。EEPROM の内容を書き換えたら 2 番目が true
+    fn command(&mut self, data: &mut [u8], opcode: u8, addr: u8) -> (EepromState, bool) {
+        match opcode {
+            // READ: ダミーの 0 に続けて 16 bit を出力
+            0b10 => {
+                self.dout = false;
+                (EepromState::Reading { bits: 16, value: Self::word(data, addr) }, false)
+            }
+            0b01 => (EepromState::Writing { addr: Some(addr), bits: 0, value: 0 }, false),
+            // ERASE
+            0b11 => {
+                if self.write_enabled {
+                    Self::set_word(data, addr, 0xFFFF);
+                }
+                (EepromState::Done, self.write_enabled)
+            }
+            // アドレス上位 2 bit で EWDS / WRAL / ERAL / EWEN
+            _ => match addr >> 6 {
+                0b00 => {
+                    self.write_enabled = false;
+                    (EepromState::Done, false)
+                }
+                0b01 => (EepromState::Writing { addr: None, bits: 0, value: 0 }, false),
+                0b10 => {
+                    if self.write_enabled {
+                        data.fill(0xFF);
+                    }
+                    (EepromState::Done, self.write_enabled)
+                }
+                _ => {
+                    self.write_enabled = true;
+                    (EepromState::Done, false)
+                }
+            },
+        }
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        for v in [self.cs, self.clk, self.di, self.dout, self.write_enabled] {
+            w.bool(v);
+        }
+        let (tag, addr, bits, value) = match self.state {
+            EepromState::Idle => (0, 0, 0, 0),
+            EepromState::Command { bits, value } => (1, 0, bits, value),
+            EepromState::Reading { bits, value } => (2, 0, bits, value),
+            EepromState::Writing { addr: Some(addr), bits, value } => (3, addr, bits, value),
+            EepromState::Writing { addr: None, bits, value } => (4, 0, bits, value),
+            EepromState::Done => (5, 0, 0, 0),
+        };
+        w.u8(tag);
+        w.u8(addr);
+        w.u8(bits);
+        w.u16(value);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) {
+        for v in [&mut self.cs, &mut self.clk, &mut self.di, &mut self.dout, &mut self.write_enabled] {
+            *v = r.bool();
+        }
+        let (tag, addr, bits, value) = (r.u8(), r.u8(), r.u8(), r.u16());
+        self.state = match tag {
+            0 => EepromState::Idle,
+            1 => EepromState::Command { bits, value },
+            2 => EepromState::Reading { bits, value },
+            3 => EepromState::Writing { addr: Some(addr), bits, value },
+            4 => EepromState::Writing { addr: None, bits, value },
+            5 => EepromState::Done,
+            _ => {
+                r.invalid();
+                EepromState::Idle
+            }
+        };
+    }
+}
+
+pub struct Mbc7 {
+    rom_bank: u8,
+    ram_enabled: bool,
+    ram_enabled2: bool,
+    /// 現在の傾き（ホストから毎フレーム更新。セーブステートには含めない）
+    tilt: (f32, f32),
+    /// 0x55 を書いてラッチ待ち
+    latch_armed: bool,
+    accel_x: u16,
+    accel_y: u16,
+    eeprom: Eeprom,
+}
+
+impl Mbc7 {
+    pub fn new() -> Self {
+        Self {
+            rom_bank: 1,
+            ram_enabled: false,
+            ram_enabled2: false,
+            tilt: (0.0, 0.0),
+            latch_armed: false,
+            accel_x: 0x8000,
+            accel_y: 0x8000,
+            eeprom: Eeprom::new(),
+        }
+    }
+
+    fn accel(tilt: f32) -> u16 {
+        (ACCEL_CENTER + ACCEL_1G * tilt.clamp(-1.0, 1.0)) as u16
+    }
+
+    pub fn set_tilt(&mut self, x: f32, y: f32) {
+        self.tilt = (x, y);
+    }
+
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク
+    pub fn rom_bank_at(&self, _rom: &[u8], addr: u16) -> usize {
+        if addr < 0x4000 { 0 } else { self.rom_bank as usize }
+    }
+
+    pub fn read(&self, rom: &[u8], _ram: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => rom_byte(rom, self.rom_bank_at(rom, addr), addr),
+            0xA000..=0xAFFF if self.ram_enabled && self.ram_enabled2 => match (addr >> 4) & 0x0F {
+                0x2 => self.accel_x as u8,
+                0x3 => (self.accel_x >> 8) as u8,
+                0x4 => self.accel_y as u8,
+                0x5 => (self.accel_y >> 8) as u8,
+                0x6 => 0x00,
+                0x8 => self.eeprom.read(),
+                _ => 0xFF,
+            },
+            _ => 0xFF,
+        }
+    }
+
+    /// EEPROM を書き換えたら true
+    pub fn write(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = value & 0x7F,
+            0x4000..=0x5FFF => self.ram_enabled2 = value == 0x40,
+            0xA000..=0xAFFF if self.ram_enabled && self.ram_enabled2 => match (addr >> 4) & 0x0F {
+                0x0 if value == 0x55 => {
+                    // ラッチを消去してから 0xAA で取り込む
+                    self.accel_x = 0x8000;
+                    self.accel_y = 0x8000;
+                    self.latch_armed = true;
+                }
+                0x1 if value == 0xAA && self.latch_armed => {
+                    self.accel_x = Self::accel(self.tilt.0);
+                    self.accel_y = Self::accel(self.tilt.1);
+                    self.latch_armed = false;
+                }
+                0x8 => return self.eeprom.write(ram, value),
+                _ => {}
+            },
+            _ => {}
+        }
+        false
+    }
+
+    pub(super) fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.rom_bank);
+        for v in [self.ram_enabled, self.ram_enabled2, self.latch_armed] {
+            w.bool(v);
+        }
+        w.u16(self.accel_x);
+        w.u16(self.accel_y);
+        self.eeprom.save_state(w);
+    }
+
+    pub(super) fn load_state(&mut self, r: &mut StateReader) {
+        self.rom_bank = r.u8();
+        for v in [&mut self.ram_enabled, &mut self.ram_enabled2, &mut self.latch_armed] {
+            *v = r.bool();
+        }
+        self.accel_x = r.u16();
+        self.accel_y = r.u16();
+        self.eeprom.load_state(r);
+    }
+}
+
+impl Default for Mbc7 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{MbcCart, NoClock};
+    use crate::cartridge::tests::banked_rom;
+    use crate::platform::CartridgeBus;
+
+    type Cart = MbcCart<Vec<u8>, Vec<u8>>;
+
+    fn mbc7() -> Cart {
+        let ram = vec![0; super::EEPROM_SIZE];
+        let mut cart = MbcCart::new(banked_rom(4, 0x22), ram, NoClock).unwrap();
+        cart.write(0x0000, 0x0A);
+        cart.write(0x4000, 0x40);
+        cart
+    }
+
+    /// EEPROM へ CS=1 のまま `bits` の下位 `n` bit を上位から送る。内容が変わったら true
+    fn eeprom_send(cart: &mut Cart, bits: u32, n: u32) -> bool {
+        let mut changed = false;
+        for i in (0..n).rev() {
+            let di = ((bits >> i) & 1) as u8 * 0x02;
+            changed |= cart.write_tracked(0xA080, 0x80 | di);
+            changed |= cart.write_tracked(0xA080, 0xC0 | di);
+        }
+        changed
+    }
+
+    fn eeprom_read_word(cart: &mut Cart, addr: u32) -> u16 {
+        cart.write(0xA080, 0x00);
+        eeprom_send(cart, 0b110 << 8 | addr, 11);
+        // ダミービット
+        assert_eq!(cart.read(0xA080) & 0x01, 0);
+        let mut value = 0;
+        for _ in 0..16 {
+            cart.write(0xA080, 0x80);
+            cart.write(0xA080, 0xC0);
+            value = value << 1 | (cart.read(0xA080) & 0x01) as u16;
+        }
+        cart.write(0xA080, 0x00);
+        value
+    }
+
+    #[test]
+    fn accelerometer_latch() {
+        let mut cart = mbc7();
+        cart.set_tilt(1.0, -0.5);
+        // 0xAA だけではラッチしない
+        cart.write(0xA010, 0xAA);
+        assert_eq!(cart.read(0xA030), 0x80);
+        cart.write(0xA000, 0x55);
+        cart.write(0xA010, 0xAA);
+        let x = u16::from_le_bytes([cart.read(0xA020), cart.read(0xA030)]);
+        let y = u16::from_le_bytes([cart.read(0xA040), cart.read(0xA050)]);
+        assert_eq!(x, 0x81D0 + 0x70);
+        assert_eq!(y, 0x81D0 - 0x38);
+        // RAM 無効中は読めない
+        cart.write(0x4000, 0x00);
+        assert_eq!(cart.read(0xA020), 0xFF);
+    }
+
+    #[test]
+    fn eeprom_write_protect_write_and_read() {
+        let mut cart = mbc7();
+        // 未書き込みの EEPROM は 0xFF
+        assert_eq!(eeprom_read_word(&mut cart, 0x05), 0xFFFF);
+        // EWEN 前の WRITE は無視
+        assert!(!eeprom_send(&mut cart, 0b101 << 24 | 0x05 << 16 | 0x1234, 27));
+        cart.write(0xA080, 0x00);
+        assert_eq!(eeprom_read_word(&mut cart, 0x05), 0xFFFF);
+        // EWEN → WRITE
+        assert!(!eeprom_send(&mut cart, 0b100_1100_0000, 11));
+        cart.write(0xA080, 0x00);
+        assert!(eeprom_send(&mut cart, 0b101 << 24 | 0x05 << 16 | 0x1234, 27));
+        cart.write(0xA080, 0x00);
+        assert_eq!(cart.read(0xA080) & 0x01, 1);
+        assert_eq!(eeprom_read_word(&mut cart, 0x05), 0x1234);
+        assert_eq!(&cart.ram()[0x0A..0x0C], &[0x34, 0x12]);
+        // ERASE
+        assert!(eeprom_send(&mut cart, 0b111 << 8 | 0x05, 11));
+        cart.write(0xA080, 0x00);
+        assert_eq!(eeprom_read_word(&mut cart, 0x05), 0xFFFF);
+    }
+}
@@ -0,0 +1,199 @@
+//! MMM01: 複数の MBC1 風ゲームを 1 本に収めたマルチカート用マッパ。
+//!
+//! 起動直後（未マップ状態）は ROM の最後の 32KB（メニュー）が 0x0000–0x7FFF に見える。
+//! メニューが上位のバンクビット・固定するビットのマスクを設定し、0x0000–0x1FFF に bit6=1 を
+//! 書くとマップされ、以降は選んだゲームを MBC1 として動かす（上位ビットとマスクはリセットまで固定）。
+
+use super::{ram_index, rom_byte};
+use crate::savestate::{StateReader, StateWriter};
+
+pub struct Mmm01 {
+    /// マップ済み（ゲーム起動後）
+    mapped: bool,
+    ram_enabled: bool,
+    /// ROM バンク bit0–4（ゲームが切り替える部分）
+    rom_bank_low: u8,
+    /// ROM バンク bit5–6 / bit7–8（未マップ時のみ書き込める）
+    rom_bank_mid: u8,
+    rom_bank_high: u8,
+    /// rom_bank_low のうちゲームから変更できないビット（bit1–4、未マップ時のみ書き込める）
+    rom_bank_mask: u8,
+    /// RAM バンク bit0–1 / bit2–3
+    ram_bank_low: u8,
+    ram_bank_high: u8,
+    /// ram_bank_low のうちゲームから変更できないビット
+    ram_bank_mask: u8,
+    /// MBC1 のバンキングモード（1 で 0x4000 レジスタが RAM バンクを選ぶ）
+    mode: bool,
+    /// モード切り替えを禁止する
+    mode_locked: bool,
+}
+
+impl Mmm01 {
+    pub fn new() -> Self {
+        Self {
+            mapped: false,
+            ram_enabled: false,
+            rom_bank_low: 0,
+            rom_bank_mid: 0,
+            rom_bank_high: 0,
+            rom_bank_mask: 0,
+            ram_bank_low: 0,
+            ram_bank_high: 0,
+            ram_bank_mask: 0,
+            mode: false,
+            mode_locked: false,
+        }
+    }
+
+    /// 0x0000–0x3FFF / 0x4000–0x7FFF に見える ROM バンク
+    fn rom_banks(&self, rom: &[u8]) -> (usize, usize) {
+        if !self.mapped {
+            let banks = rom.len() / 0x4000;
+            return (banks.saturating_sub(2), banks.saturating_sub(1));
+        }
+        let outer = ((self.rom_bank_high as usize) << 7) | ((self.rom_bank_mid as usize) << 5);
+        // MBC1 と同じく、変更できるビットが全部 0 のときはバンク 1
+        let fixed = (self.rom_bank_mask << 1) & 0x1E;
+        let mut low = self.rom_bank_low & 0x1F;
+        if low & !fixed == 0 {
+            low |= 1;
+        }
+        (outer | (self.rom_bank_low & fixed) as usize, outer | low as usize)
+    }
+
+    fn ram_index(&self, ram: &[u8], addr: u16) -> Option<usize> {
+        if !self.ram_enabled {
+            return None;
+        }
+        ram_index(ram, self.ram_bank(), addr)
+    }
+
+    /// 0xA000–0xBFFF に割り当て中の RAM バンク
+    pub fn ram_bank(&self) -> usize {
+        let low = if self.mode { self.ram_bank_low & 0x03 } else { 0 };
+        ((self.ram_bank_high as usize & 0x03) << 2) | low as usize
+    }
+
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク
+    pub fn rom_bank_at(&self, rom: &[u8], addr: u16) -> usize {
+        let (bank0, bank1) = self.rom_banks(rom);
+        if addr < 0x4000 { bank0 } else { bank1 }
+    }
+
+    pub fn read(&self, rom: &[u8], ram: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => rom_byte(rom, self.rom_bank_at(rom, addr), addr),
+            0xA000..=0xBFFF => self.ram_index(ram, addr).map_or(0xFF, |i| ram[i]),
+            _ => 0xFF,
+        }
+    }
+
+    /// 外部 RAM を書き換えたら true
+    pub fn write(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        match addr {
+            0x0000..=0x1FFF => {
+                self.ram_enabled = (value & 0x0F) == 0x0A;
+                if !self.mapped {
+                    self.ram_bank_mask = (value >> 4) & 0x03;
+                    self.mapped = value & 0x40 != 0;
+                }
+            }
+            0x2000..=0x3FFF => {
+                if !self.mapped {
+                    self.rom_bank_mid = (value >> 5) & 0x03;
+                }
+                let fixed = (self.rom_bank_mask << 1) & 0x1E;
+                self.rom_bank_low = (self.rom_bank_low & fixed) | (value & 0x1F & !fixed);
+            }
+            0x4000..=0x5FFF => {
+                let fixed = self.ram_bank_mask;
+                self.ram_bank_low = (self.ram_bank_low & fixed) | (value & 0x03 & !fixed);
+                if !self.mapped {
+                    self.ram_bank_high = (value >> 2) & 0x03;
+                    self.rom_bank_high = (value >> 4) & 0x03;
+                    self.mode_locked = value & 0x40 != 0;
+                }
+            }
+            0x6000..=0x7FFF => {
+                if !self.mode_locked {
+                    self.mode = value & 0x01 != 0;
+                }
+                if !self.mapped {
+                    self.rom_bank_mask = (value >> 2) & 0x0F;
+                }
+            }
+            0xA000..=0xBFFF => {
+                if let Some(i) = self.ram_index(ram, addr) {
+                    ram[i] = value;
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    pub(super) fn save_state(&self, w: &mut StateWriter) {
+        for v in [
+            self.rom_bank_low, self.rom_bank_mid, self.rom_bank_high, self.rom_bank_mask,
+            self.ram_bank_low, self.ram_bank_high, self.ram_bank_mask,
+        ] {
+            w.u8(v);
+        }
+        for v in [self.mapped, self.ram_enabled, self.mode, self.mode_locked] {
+            w.bool(v);
+        }
+    }
+
+    pub(super) fn load_state(&mut self, r: &mut StateReader) {
+        for v in [
+            &mut self.rom_bank_low,
+            &mut self.rom_bank_mid,
+            &mut self.rom_bank_high,
+            &mut self.rom_bank_mask,
+            &mut self.ram_bank_low,
+            &mut self.ram_bank_high,
+            &mut self.ram_bank_mask,
+        ] {
+            *v = r.u8();
+        }
+        for v in [&mut self.mapped, &mut self.ram_enabled, &mut self.mode, &mut self.mode_locked] {
+            *v = r.bool();
+        }
+    }
+}
+
+impl Default for Mmm01 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::tests::banked_rom;
+
+    #[test]
+    fn boots_menu_then_locks_game_mapping() {
+        let rom = banked_rom(64, 0x0C);
+        let mut ram = [0u8; 0x2000];
+        let mut mbc = Mmm01::new();
+        // 未マップ: 最後の 32KB
+        assert_eq!(mbc.read(&rom, &ram, 0x0000), 62);
+        assert_eq!(mbc.read(&rom, &ram, 0x4000), 63);
+        // ゲームの開始バンク 32 (mid=1) を設定し、bit1–4 をマスクせずにマップ
+        mbc.write(&mut ram, 0x2000, 0x20);
+        mbc.write(&mut ram, 0x0000, 0x40 | 0x0A);
+        assert_eq!(mbc.read(&rom, &ram, 0x0000), 32);
+        assert_eq!(mbc.read(&rom, &ram, 0x4000), 33);
+        mbc.write(&mut ram, 0x2000, 0x05);
+        assert_eq!(mbc.read(&rom, &ram, 0x4000), 37);
+        // マップ後は上位ビットを変えられない
+        mbc.write(&mut ram, 0x2000, 0x60);
+        assert_eq!(mbc.read(&rom, &ram, 0x4000), 33);
+        mbc.write(&mut ram, 0xA000, 0x5A);
+        assert_eq!(mbc.read(&rom, &ram, 0xA000), 0x5A);
+    }
+}
@@ -0,0 +1,174 @@
+//! MBC3 のリアルタイムクロック。
+
+use crate::savestate::{StateReader, StateWriter};
+
+/// RTC レジスタ (0x08–0x0C) の添字
+const RTC_S: usize = 0;
+const RTC_M: usize = 1;
+const RTC_H: usize = 2;
+const RTC_DL: usize = 3;
+const RTC_DH: usize = 4;
+/// DH: bit0=日カウンタ bit8, bit6=停止, bit7=日カウンタ桁あふれ
+pub(super) const DH_DAY_HI: u8 = 0x01;
+pub(super) const DH_HALT: u8 = 0x40;
+pub(super) const DH_CARRY: u8 = 0x80;
+/// 各レジスタの有効ビット
+const RTC_MASK: [u8; 5] = [0x3F, 0x3F, 0x1F, 0xFF, 0xC1];
+/// .sav 末尾の RTC フッタ長
+pub const RTC_FOOTER_LEN: usize = 48;
+
+/// MBC3 リアルタイムクロック。
+///
+/// カウンタは時刻源の経過秒で遅延更新する（ラッチ・書き込み・保存の直前に [`Rtc::sync`]）。
+pub struct Rtc {
+    /// 現在のカウンタ [S, M, H, DL, DH]
+    regs: [u8; 5],
+    /// ラッチ済みの値（CPU から読めるのはこちら）
+    latched: [u8; 5],
+    /// 直前のラッチレジスタ書き込み値（0x00→0x01 でラッチ）
+    latch_prev: u8,
+    /// `regs` が表す時点の時刻源の値
+    last: u64,
+}
+
+impl Rtc {
+    pub fn new(now: u64) -> Self {
+        Self { regs: [0; 5], latched: [0; 5], latch_prev: 0xFF, last: now }
+    }
+
+    /// 時刻源の現在値までカウンタを進める（停止中は経過時間を捨てる）
+    pub fn sync(&mut self, now: u64) {
+        if self.regs[RTC_DH] & DH_HALT == 0 {
+            self.advance(now.saturating_sub(self.last));
+        }
+        self.last = now;
+    }
+
+    fn advance(&mut self, mut secs: u64) {
+        // 範囲外の値（書き込みで設定できる）は 1 秒ずつ進めて正規化する
+        while secs > 0
+            && (self.regs[RTC_S] >= 60 || self.regs[RTC_M] >= 60 || self.regs[RTC_H] >= 24)
+        {
+            self.tick();
+            secs -= 1;
+        }
+        if secs == 0 {
+            return;
+        }
+        let total = self.regs[RTC_S] as u64 + secs;
+        self.regs[RTC_S] = (total % 60) as u8;
+        let total = self.regs[RTC_M] as u64 + total / 60;
+        self.regs[RTC_M] = (total % 60) as u8;
+        let total = self.regs[RTC_H] as u64 + total / 60;
+        self.regs[RTC_H] = (total % 24) as u8;
+        let days = self.days() as u64 + total / 24;
+        if days >= 512 {
+            self.regs[RTC_DH] |= DH_CARRY;
+        }
+        self.set_days((days % 512) as u16);
+    }
+
+    /// 1 秒進める。範囲外の値は各レジスタのビット幅で折り返し、繰り上がらない。
+    fn tick(&mut self) {
+        let s = &mut self.regs;
+        s[RTC_S] = (s[RTC_S] + 1) & RTC_MASK[RTC_S];
+        if s[RTC_S] != 60 {
+            return;
+        }
+        s[RTC_S] = 0;
+        s[RTC_M] = (s[RTC_M] + 1) & RTC_MASK[RTC_M];
+        if s[RTC_M] != 60 {
+            return;
+        }
+        s[RTC_M] = 0;
+        s[RTC_H] = (s[RTC_H] + 1) & RTC_MASK[RTC_H];
+        if s[RTC_H] != 24 {
+            return;
+        }
+        s[RTC_H] = 0;
+        let days = self.days() + 1;
+        if days >= 512 {
+            self.regs[RTC_DH] |= DH_CARRY;
+        }
+        self.set_days(days % 512);
+    }
+
+    fn days(&self) -> u16 {
+        self.regs[RTC_DL] as u16 | ((self.regs[RTC_DH] & DH_DAY_HI) as u16) << 8
+    }
+
+    fn set_days(&mut self, days: u16) {
+        self.regs[RTC_DL] = days as u8;
+        self.regs[RTC_DH] = (self.regs[RTC_DH] & !DH_DAY_HI) | (days >> 8) as u8 & DH_DAY_HI;
+    }
+
+    /// 0x6000–0x7FFF への書き込み。0x00 → 0x01 の順で現在値をラッチする。
+    pub fn write_latch(&mut self, value: u8, now: u64) {
+        if self.latch_prev == 0x00 && value == 0x01 {
+            self.sync(now);
+            self.latched = self.regs;
+        }
+        self.latch_prev = value;
+    }
+
+    /// ラッチ済みレジスタの読み出し（reg = 0x08–0x0C）
+    pub fn read(&self, reg: u8) -> u8 {
+        self.latched[(reg - 0x08) as usize]
+    }
+
+    /// レジスタへの書き込み。時刻源が秒単位なので 1 秒未満の端数は持たない
+    /// （実機の秒書き込みによる端数リセットは再現しない）。
+    pub fn write(&mut self, reg: u8, value: u8, now: u64) {
+        self.sync(now);
+        let i = (reg - 0x08) as usize;
+        self.regs[i] = value & RTC_MASK[i];
+        // 読み出しはラッチ値を返すため、書き込んだ値はラッチ側にも反映する
+        // （書き込み直後に読み戻して検証するソフトがある）
+        self.latched[i] = self.regs[i];
+    }
+
+    /// BGB/VBA-M 互換の 48 バイト RTC フッタ（.sav の末尾）を生成する。
+    /// 現在値 5 × u32、ラッチ値 5 × u32、UNIX 時刻 u64（すべてリトルエンディアン）。
+    pub fn to_footer(&mut self, now: u64) -> [u8; RTC_FOOTER_LEN] {
+        self.sync(now);
+        let mut out = [0u8; RTC_FOOTER_LEN];
+        for (i, &v) in self.regs.iter().chain(self.latched.iter()).enumerate() {
+            out[i * 4..i * 4 + 4].copy_from_slice(&(v as u32).to_le_bytes());
+        }
+        out[40..48].copy_from_slice(&self.last.to_le_bytes());
+        out
+    }
+
+    /// RTC フッタを読み込む。タイムスタンプが 32 bit の 44 バイト形式も受け付ける。
+    /// 保存時刻からの経過時間は次の同期で反映される。
+    pub fn load_footer(&mut self, data: &[u8]) -> bool {
+        if data.len() != RTC_FOOTER_LEN && data.len() != RTC_FOOTER_LEN - 4 {
+            return false;
+        }
+        let word = |i: usize| u32::from_le_bytes(data[i * 4..i * 4 + 4].try_into().unwrap());
+        for (i, mask) in RTC_MASK.iter().enumerate() {
+            self.regs[i] = word(i) as u8 & mask;
+            self.latched[i] = word(i + 5) as u8 & mask;
+        }
+        self.last = if data.len() == RTC_FOOTER_LEN {
+            u64::from_le_bytes(data[40..48].try_into().unwrap())
+        } else {
+            word(10) as u64
+        };
+        true
+    }
+
+    pub(super) fn save_state(&self, w: &mut StateWriter) {
+        w.bytes(&self.regs);
+        w.bytes(&self.latched);
+        w.u8(self.latch_prev);
+        w.u64(self.last);
+    }
+
+    pub(super) fn load_state(&mut self, r: &mut StateReader) {
+        r.bytes(&mut self.regs);
+        r.bytes(&mut self.latched);
+        self.latch_prev = r.u8();
+        self.last = r.u64();
+    }
+}
@@ -1,11 +1,15 @@
 mod decode;
+pub mod disasm;
 mod exec;
 mod instr;
 mod operand;
 mod registers;
 
+use crate::debugger::CpuRegisters;
 use crate::mmu::MemoryBus;
-use instr::Instr;
+use crate::savestate::{Snapshot, StateReader, StateWriter};
+use core::fmt;
+use instr::{Instr, InstrSource};
 use registers::Registers;
 
 pub struct Cpu {
@@ -20,12 +24,18 @@ pub struct Cpu {
     halt_bug: bool,
     /// 実行中の命令ユニット（step を内包）
     instr: Instr,
+    /// `instr` の生成元（セーブステートからの復元用）
+    instr_src: InstrSource,
     /// 内部一時レジスタ（即値・アドレス・中間値。実機WZ相当）
     wz: u16,
     /// fetch 済みの次オペコード
     opcode: u8,
+    /// `opcode` を読んだアドレス（次に実行する命令の PC）
+    instr_pc: u16,
     /// 現命令が完了し、次の命令境界にいるか
     done: bool,
+    /// 一度でも fetch したか。起動直後の（fetch 前の）NOP をトレースに出さないため
+    fetched: bool,
 }
 
 impl Cpu {
@@ -37,9 +47,12 @@ impl Cpu {
             halted: false,
             halt_bug: false,
             instr: Instr::nop(),
+            instr_src: InstrSource::Opcode(0x00),
             wz: 0,
             opcode: 0,
+            instr_pc: 0,
             done: true,
+            fetched: false,
         }
     }
 
@@ -81,9 +94,63 @@ impl Cpu {
         (self.regs.a, self.regs.hl(), self.regs.sp)
     }
 
+    /// デバッガ用: 全レジスタ。PC は次に実行する命令のアドレス
+    /// （fetch 済みのオペコードの位置。HALT バグ直後は内部の PC と同じになる）。
+    pub fn registers(&self) -> CpuRegisters {
+        let r = &self.regs;
+        CpuRegisters {
+            a: r.a,
+            f: r.f,
+            b: r.b,
+            c: r.c,
+            d: r.d,
+            e: r.e,
+            h: r.h,
+            l: r.l,
+            sp: r.sp,
+            pc: self.instr_pc,
+            ime: self.ime,
+            halted: self.halted,
+        }
+    }
+
+    /// 命令の境界にいるか（現命令を終え、次のオペコードを fetch 済み）
+    pub fn at_boundary(&self) -> bool {
+        self.done
+    }
+
+    /// 次に実行する命令の直前の状態を gameboy-doctor 形式の 1 行で書く:
+    /// `A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02`
+    pub fn write_trace(&self, bus: &dyn MemoryBus, out: &mut dyn fmt::Write) {
+        let r = self.registers();
+        let mem = |i: u16| bus.read(r.pc.wrapping_add(i));
+        // 書き込み先のエラーはトレースが欠けるだけなので無視する
+        let _ = writeln!(
+            out,
+            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} \
+             SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            r.a,
+            r.f,
+            r.b,
+            r.c,
+            r.d,
+            r.e,
+            r.h,
+            r.l,
+            r.sp,
+            r.pc,
+            mem(0),
+            mem(1),
+            mem(2),
+            mem(3)
+        );
+    }
+
     /// 次のオペコードを先読みする（実機同様のオーバーラップ fetch）
     pub fn fetch(&mut self, bus: &dyn MemoryBus) {
         self.opcode = bus.read(self.regs.pc);
+        self.instr_pc = self.regs.pc;
+        self.fetched = true;
         if self.halt_bug {
             // HALT バグ: PC をインクリメントしない（次命令の第1オペランドが opcode と同じアドレスになる）
             self.halt_bug = false;
@@ -93,7 +160,13 @@ impl Cpu {
     }
 
     /// 1 M-cycle 進める。完了なら次命令を decode、未完了なら現命令を継続する。
-    pub fn emulate_cycle(&mut self, bus: &mut dyn MemoryBus) {
+    /// このサイクルで命令（割り込みディスパッチを含む）が完了したら true。
+    /// `trace` を渡すと、オペコードを decode する命令境界で [`Cpu::write_trace`] の 1 行を書く。
+    pub fn emulate_cycle(
+        &mut self,
+        bus: &mut dyn MemoryBus,
+        trace: Option<&mut dyn fmt::Write>,
+    ) -> bool {
         if self.done {
             // === 命令境界 ===
             // EI の遅延処理: 前の命令が EI だったら今ここで IME を有効化。
@@ -112,15 +185,26 @@ impl Cpu {
                     self.halted = false;
                 } else {
                     // まだ HALT 中: 命令境界を維持して待機
-                    return;
+                    return false;
                 }
             }
 
             // 割り込みディスパッチ（EI の直後サイクルはスキップ）か、通常 decode
-            self.instr = if self.ime && pending != 0 && !was_ei_delayed {
-                Instr::interrupt()
+            self.instr_src = if self.ime && pending != 0 && !was_ei_delayed {
+                InstrSource::Interrupt
             } else {
-                decode::decode(self.opcode)
+                InstrSource::Opcode(self.opcode)
+            };
+            self.instr = match self.instr_src {
+                InstrSource::Interrupt => Instr::interrupt(),
+                _ => {
+                    if let Some(out) = trace
+                        && self.fetched
+                    {
+                        self.write_trace(bus, out);
+                    }
+                    decode::decode(self.opcode)
+                }
             };
             self.done = false;
         }
@@ -130,6 +214,85 @@ impl Cpu {
             self.fetch(bus);
             self.done = true;
         }
+        self.done
+    }
+}
+
+impl Snapshot for Cpu {
+    fn save(&self, w: &mut StateWriter) {
+        let r = &self.regs;
+        w.u16(r.pc);
+        w.u16(r.sp);
+        for v in [r.a, r.f, r.b, r.c, r.d, r.e, r.h, r.l] {
+            w.u8(v);
+        }
+        w.bool(self.ime);
+        w.bool(self.ei_delay);
+        w.bool(self.halted);
+        w.bool(self.halt_bug);
+        // 命令途中の状態: 生成元 + マイクロステップ
+        match self.instr_src {
+            InstrSource::Opcode(op) => {
+                w.u8(0);
+                w.u8(op);
+            }
+            InstrSource::Cb(cb) => {
+                w.u8(1);
+                w.u8(cb);
+            }
+            InstrSource::Interrupt => {
+                w.u8(2);
+                w.u8(0);
+            }
+        }
+        w.u8(self.instr.step);
+        w.u16(self.wz);
+        w.u8(self.opcode);
+        w.u16(self.instr_pc);
+        w.bool(self.done);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        let regs = &mut self.regs;
+        regs.pc = r.u16();
+        regs.sp = r.u16();
+        for v in [
+            &mut regs.a,
+            &mut regs.f,
+            &mut regs.b,
+            &mut regs.c,
+            &mut regs.d,
+            &mut regs.e,
+            &mut regs.h,
+            &mut regs.l,
+        ] {
+            *v = r.u8();
+        }
+        self.ime = r.bool();
+        self.ei_delay = r.bool();
+        self.halted = r.bool();
+        self.halt_bug = r.bool();
+        let (tag, code) = (r.u8(), r.u8());
+        self.instr_src = match tag {
+            0 => InstrSource::Opcode(code),
+            1 => InstrSource::Cb(code),
+            2 => InstrSource::Interrupt,
+            _ => {
+                r.invalid();
+                InstrSource::Opcode(0x00)
+            }
+        };
+        self.instr = match self.instr_src {
+            InstrSource::Opcode(op) => decode::decode(op),
+            InstrSource::Cb(cb) => decode::decode_cb(cb),
+            InstrSource::Interrupt => Instr::interrupt(),
+        };
+        self.instr.step = r.u8();
+        self.wz = r.u16();
+        self.opcode = r.u8();
+        self.instr_pc = r.u16();
+        self.done = r.bool();
+        self.fetched = true;
     }
 }
 
@@ -162,7 +325,7 @@ mod tests {
     /// 指定 M-cycle 数だけ実行
     fn run(cpu: &mut Cpu, mmu: &mut dyn MemoryBus, cycles: usize) {
         for _ in 0..cycles {
-            cpu.emulate_cycle(mmu);
+            cpu.emulate_cycle(mmu, None);
         }
     }
 
@@ -373,4 +536,72 @@ mod tests {
         run(&mut c, &mut m, 1); // NOP 完了。境界で IME 有効化されるが直後割り込みは次境界
         assert!(c.ime);
     }
+
+    // ── トレース ────────────────────────────
+    #[test]
+    fn trace_writes_doctor_line_per_instruction() {
+        let (mut c, mut m) = setup(&[0x00, 0x3E, 0x42, 0x00]); // NOP; LD A,d8; NOP
+        c.regs.a = 0x01;
+        c.regs.f = 0xB0;
+        c.regs.c = 0x13;
+        let mut log = String::new();
+        for _ in 0..4 {
+            c.emulate_cycle(&mut m, Some(&mut log));
+        }
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "A:01 F:B0 B:00 C:13 D:00 E:00 H:00 L:00 SP:D000 PC:C000 PCMEM:00,3E,42,00",
+                "A:01 F:B0 B:00 C:13 D:00 E:00 H:00 L:00 SP:D000 PC:C001 PCMEM:3E,42,00,00",
+                "A:42 F:B0 B:00 C:13 D:00 E:00 H:00 L:00 SP:D000 PC:C003 PCMEM:00,00,00,00",
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_pc_after_halt_bug() {
+        // DI; HALT を IF&IE 保留中に実行すると HALT に入らず、次の命令は HALT の直後から始まる。
+        // その命令 (INC A) は PC を進めずに fetch されるので 2 回実行される。
+        let (mut c, mut m) = setup(&[0xF3, 0x76, 0x3C, 0x00]); // DI; HALT; INC A; NOP
+        m.ie = 0x01;
+        m.if_ = 0x01;
+        let mut log = String::new();
+        for _ in 0..4 {
+            c.emulate_cycle(&mut m, Some(&mut log));
+        }
+        let pcs: Vec<&str> = log.lines().map(|l| &l[l.find("PC:").unwrap()..]).collect();
+        assert_eq!(
+            pcs,
+            [
+                "PC:C000 PCMEM:F3,76,3C,00",
+                "PC:C001 PCMEM:76,3C,00,00",
+                "PC:C002 PCMEM:3C,00,00,00",
+                "PC:C002 PCMEM:3C,00,00,00",
+            ]
+        );
+        assert_eq!(c.regs.a, 2);
+    }
+
+    // ── セーブステート ──────────────────────
+    #[test]
+    fn snapshot_mid_cb_instruction() {
+        // CB プレフィックス命令の途中で保存し、別の Cpu に復元しても同じ結果になる
+        let (mut c, mut m) = setup(&[0xCB, 0x36]); // SWAP (HL)
+        c.regs.write_hl(0xC050);
+        m.write(0xC050, 0x12);
+        run(&mut c, &mut m, 2); // プレフィックス + (HL) 読み出しまで
+        let mut buf = [0u8; 64];
+        let mut w = StateWriter::new(&mut buf);
+        c.save(&mut w);
+        let len = w.finish().unwrap();
+
+        let mut restored = Cpu::new();
+        let mut r = StateReader::new(&buf[..len]);
+        restored.load(&mut r);
+        r.finish().unwrap();
+        run(&mut restored, &mut m, 2);
+        assert_eq!(m.read(0xC050), 0x21);
+        assert_eq!(restored.regs.pc, 0xC003);
+    }
 }
@@ -1,9 +1,154 @@
 //! オペコードを命令ユニット `Instr` に変換する純粋関数(実行はしない)。
+//! 表は [`Decoded`] を返し、CPU と逆アセンブラ (`disasm`) が共有する。
 
 use super::exec::*;
 use super::instr::{ExecFn, Instr};
 use super::operand::{Cond, Indirect, Operand, Reg8, Reg16};
 
+/// 命令の種類。実行関数 ([`Op::exec`]) と逆アセンブラのニーモニックはどちらもここから引く
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum Op {
+    Nop,
+    Ld8,
+    Ld16,
+    Inc16,
+    Inc8,
+    Dec8,
+    Rlca,
+    LdNnSp,
+    AddHl,
+    Dec16,
+    Rrca,
+    Stop,
+    Rla,
+    Jr,
+    Rra,
+    Daa,
+    Cpl,
+    Scf,
+    Ccf,
+    Halt,
+    Add,
+    Adc,
+    Sub,
+    Sbc,
+    And,
+    Xor,
+    Or,
+    Cp,
+    RetCc,
+    Pop,
+    Jp,
+    Call,
+    Push,
+    Rst,
+    Ret,
+    Prefix,
+    Reti,
+    LdhNA,
+    AddSp,
+    JpHl,
+    LdAbsA,
+    LdhAN,
+    Di,
+    Ldhl,
+    LdSpHl,
+    LdAAbs,
+    Ei,
+    Rlc,
+    Rrc,
+    Rl,
+    Rr,
+    Sla,
+    Sra,
+    Swap,
+    Srl,
+    Bit,
+    Res,
+    Set,
+    /// 未定義オペコード（実機はロックアップするが NOP として実行する）
+    Illegal,
+}
+
+impl Op {
+    pub(super) fn exec(self) -> ExecFn {
+        match self {
+            Op::Nop | Op::Illegal => exec_nop,
+            Op::Ld8 => exec_ld8,
+            Op::Ld16 => exec_ld16,
+            Op::Inc16 => exec_inc16,
+            Op::Inc8 => exec_inc8,
+            Op::Dec8 => exec_dec8,
+            Op::Rlca => exec_rlca,
+            Op::LdNnSp => exec_ld_nn_sp,
+            Op::AddHl => exec_add_hl,
+            Op::Dec16 => exec_dec16,
+            Op::Rrca => exec_rrca,
+            Op::Stop => exec_stop,
+            Op::Rla => exec_rla,
+            Op::Jr => exec_jr,
+            Op::Rra => exec_rra,
+            Op::Daa => exec_daa,
+            Op::Cpl => exec_cpl,
+            Op::Scf => exec_scf,
+            Op::Ccf => exec_ccf,
+            Op::Halt => exec_halt,
+            Op::Add => exec_add,
+            Op::Adc => exec_adc,
+            Op::Sub => exec_sub,
+            Op::Sbc => exec_sbc,
+            Op::And => exec_and,
+            Op::Xor => exec_xor,
+            Op::Or => exec_or,
+            Op::Cp => exec_cp,
+            Op::RetCc => exec_ret_cc,
+            Op::Pop => exec_pop,
+            Op::Jp => exec_jp,
+            Op::Call => exec_call,
+            Op::Push => exec_push,
+            Op::Rst => exec_rst,
+            Op::Ret => exec_ret,
+            Op::Prefix => exec_cb_prefix,
+            Op::Reti => exec_reti,
+            Op::LdhNA => exec_ldh_n_a,
+            Op::AddSp => exec_add_sp,
+            Op::JpHl => exec_jp_hl,
+            Op::LdAbsA => exec_ld_abs_a,
+            Op::LdhAN => exec_ldh_a_n,
+            Op::Di => exec_di,
+            Op::Ldhl => exec_ldhl,
+            Op::LdSpHl => exec_ld_sp_hl,
+            Op::LdAAbs => exec_ld_a_abs,
+            Op::Ei => exec_ei,
+            Op::Rlc => exec_cb_rlc,
+            Op::Rrc => exec_cb_rrc,
+            Op::Rl => exec_cb_rl,
+            Op::Rr => exec_cb_rr,
+            Op::Sla => exec_cb_sla,
+            Op::Sra => exec_cb_sra,
+            Op::Swap => exec_cb_swap,
+            Op::Srl => exec_cb_srl,
+            Op::Bit => exec_cb_bit,
+            Op::Res => exec_cb_res,
+            Op::Set => exec_cb_set,
+        }
+    }
+}
+
+/// デコード結果。`Instr` から実行状態を除いたもの
+#[derive(Clone, Copy)]
+pub(super) struct Decoded {
+    pub op: Op,
+    pub dst: Operand,
+    pub src: Operand,
+}
+
+impl Decoded {
+    pub(super) fn instr(self) -> Instr {
+        Instr::with(self.dst, self.src, self.op.exec())
+    }
+}
+
 // オペランド構築ショートハンド
 const N: Operand = Operand::None;
 const IMM: Operand = Operand::Imm;
@@ -20,82 +165,98 @@ fn cc(c: Cond) -> Operand {
     Operand::Cond(c)
 }
 
-fn ld(dst: Operand, src: Operand) -> Instr {
-    Instr::with(dst, src, exec_ld8)
+fn simple(op: Op) -> Decoded {
+    Decoded { op, dst: N, src: N }
+}
+fn with(dst: Operand, src: Operand, op: Op) -> Decoded {
+    Decoded { op, dst, src }
+}
+fn ld(dst: Operand, src: Operand) -> Decoded {
+    with(dst, src, Op::Ld8)
 }
-fn alu(exec: ExecFn, src: Operand) -> Instr {
-    Instr::with(N, src, exec)
+fn alu(op: Op, src: Operand) -> Decoded {
+    with(N, src, op)
 }
 
 /// オペコード → 命令ユニット
 pub(super) fn decode(opcode: u8) -> Instr {
+    decode_op(opcode).instr()
+}
+
+/// CB プリフィックス命令 → 命令ユニット
+pub(super) fn decode_cb(cb: u8) -> Instr {
+    decode_cb_op(cb).instr()
+}
+
+/// オペコード → 命令の種類とオペランド
+pub(super) fn decode_op(opcode: u8) -> Decoded {
     use Reg8::*;
     use Reg16::*;
     match opcode {
-        0x00 => Instr::simple(exec_nop),
-        0x01 => Instr::with(rr(BC), N, exec_ld16),
+        0x00 => simple(Op::Nop),
+        0x01 => with(rr(BC), N, Op::Ld16),
         0x02 => ld(ind(Indirect::BC), r(A)),
-        0x03 => Instr::with(rr(BC), N, exec_inc16),
-        0x04 => Instr::with(r(B), N, exec_inc8),
-        0x05 => Instr::with(r(B), N, exec_dec8),
+        0x03 => with(rr(BC), N, Op::Inc16),
+        0x04 => with(r(B), N, Op::Inc8),
+        0x05 => with(r(B), N, Op::Dec8),
         0x06 => ld(r(B), IMM),
-        0x07 => Instr::simple(exec_rlca),
-        0x08 => Instr::simple(exec_ld_nn_sp),
-        0x09 => alu(exec_add_hl, rr(BC)),
+        0x07 => simple(Op::Rlca),
+        0x08 => simple(Op::LdNnSp),
+        0x09 => alu(Op::AddHl, rr(BC)),
         0x0A => ld(r(A), ind(Indirect::BC)),
-        0x0B => Instr::with(rr(BC), N, exec_dec16),
-        0x0C => Instr::with(r(C), N, exec_inc8),
-        0x0D => Instr::with(r(C), N, exec_dec8),
+        0x0B => with(rr(BC), N, Op::Dec16),
+        0x0C => with(r(C), N, Op::Inc8),
+        0x0D => with(r(C), N, Op::Dec8),
         0x0E => ld(r(C), IMM),
-        0x0F => Instr::simple(exec_rrca),
-        0x10 => Instr::simple(exec_stop),
-        0x11 => Instr::with(rr(DE), N, exec_ld16),
+        0x0F => simple(Op::Rrca),
+        0x10 => simple(Op::Stop),
+        0x11 => with(rr(DE), N, Op::Ld16),
         0x12 => ld(ind(Indirect::DE), r(A)),
-        0x13 => Instr::with(rr(DE), N, exec_inc16),
-        0x14 => Instr::with(r(D), N, exec_inc8),
-        0x15 => Instr::with(r(D), N, exec_dec8),
+        0x13 => with(rr(DE), N, Op::Inc16),
+        0x14 => with(r(D), N, Op::Inc8),
+        0x15 => with(r(D), N, Op::Dec8),
         0x16 => ld(r(D), IMM),
-        0x17 => Instr::simple(exec_rla),
-        0x18 => Instr::with(N, N, exec_jr),
-        0x19 => alu(exec_add_hl, rr(DE)),
+        0x17 => simple(Op::Rla),
+        0x18 => with(N, N, Op::Jr),
+        0x19 => alu(Op::AddHl, rr(DE)),
         0x1A => ld(r(A), ind(Indirect::DE)),
-        0x1B => Instr::with(rr(DE), N, exec_dec16),
-        0x1C => Instr::with(r(E), N, exec_inc8),
-        0x1D => Instr::with(r(E), N, exec_dec8),
+        0x1B => with(rr(DE), N, Op::Dec16),
+        0x1C => with(r(E), N, Op::Inc8),
+        0x1D => with(r(E), N, Op::Dec8),
         0x1E => ld(r(E), IMM),
-        0x1F => Instr::simple(exec_rra),
-        0x20 => Instr::with(N, cc(Cond::NZ), exec_jr),
-        0x21 => Instr::with(rr(HL), N, exec_ld16),
+        0x1F => simple(Op::Rra),
+        0x20 => with(N, cc(Cond::NZ), Op::Jr),
+        0x21 => with(rr(HL), N, Op::Ld16),
         0x22 => ld(ind(Indirect::HLI), r(A)),
-        0x23 => Instr::with(rr(HL), N, exec_inc16),
-        0x24 => Instr::with(r(H), N, exec_inc8),
-        0x25 => Instr::with(r(H), N, exec_dec8),
+        0x23 => with(rr(HL), N, Op::Inc16),
+        0x24 => with(r(H), N, Op::Inc8),
+        0x25 => with(r(H), N, Op::Dec8),
         0x26 => ld(r(H), IMM),
-        0x27 => Instr::simple(exec_daa),
-        0x28 => Instr::with(N, cc(Cond::Z), exec_jr),
-        0x29 => alu(exec_add_hl, rr(HL)),
+        0x27 => simple(Op::Daa),
+        0x28 => with(N, cc(Cond::Z), Op::Jr),
+        0x29 => alu(Op::AddHl, rr(HL)),
         0x2A => ld(r(A), ind(Indirect::HLI)),
-        0x2B => Instr::with(rr(HL), N, exec_dec16),
-        0x2C => Instr::with(r(L), N, exec_inc8),
-        0x2D => Instr::with(r(L), N, exec_dec8),
+        0x2B => with(rr(HL), N, Op::Dec16),
+        0x2C => with(r(L), N, Op::Inc8),
+        0x2D => with(r(L), N, Op::Dec8),
         0x2E => ld(r(L), IMM),
-        0x2F => Instr::simple(exec_cpl),
-        0x30 => Instr::with(N, cc(Cond::NC), exec_jr),
-        0x31 => Instr::with(rr(SP), N, exec_ld16),
+        0x2F => simple(Op::Cpl),
+        0x30 => with(N, cc(Cond::NC), Op::Jr),
+        0x31 => with(rr(SP), N, Op::Ld16),
         0x32 => ld(ind(Indirect::HLD), r(A)),
-        0x33 => Instr::with(rr(SP), N, exec_inc16),
-        0x34 => Instr::with(ind(Indirect::HL), N, exec_inc8),
-        0x35 => Instr::with(ind(Indirect::HL), N, exec_dec8),
+        0x33 => with(rr(SP), N, Op::Inc16),
+        0x34 => with(ind(Indirect::HL), N, Op::Inc8),
+        0x35 => with(ind(Indirect::HL), N, Op::Dec8),
         0x36 => ld(ind(Indirect::HL), IMM),
-        0x37 => Instr::simple(exec_scf),
-        0x38 => Instr::with(N, cc(Cond::C), exec_jr),
-        0x39 => alu(exec_add_hl, rr(SP)),
+        0x37 => simple(Op::Scf),
+        0x38 => with(N, cc(Cond::C), Op::Jr),
+        0x39 => alu(Op::AddHl, rr(SP)),
         0x3A => ld(r(A), ind(Indirect::HLD)),
-        0x3B => Instr::with(rr(SP), N, exec_dec16),
-        0x3C => Instr::with(r(A), N, exec_inc8),
-        0x3D => Instr::with(r(A), N, exec_dec8),
+        0x3B => with(rr(SP), N, Op::Dec16),
+        0x3C => with(r(A), N, Op::Inc8),
+        0x3D => with(r(A), N, Op::Dec8),
         0x3E => ld(r(A), IMM),
-        0x3F => Instr::simple(exec_ccf),
+        0x3F => simple(Op::Ccf),
 
         // LD r,r' (0x40-0x7F, 0x76 は HALT)
         0x40 => ld(r(B), r(B)),
@@ -152,7 +313,7 @@ pub(super) fn decode(opcode: u8) -> Instr {
         0x73 => ld(ind(Indirect::HL), r(E)),
         0x74 => ld(ind(Indirect::HL), r(H)),
         0x75 => ld(ind(Indirect::HL), r(L)),
-        0x76 => Instr::simple(exec_halt),
+        0x76 => simple(Op::Halt),
         0x77 => ld(ind(Indirect::HL), r(A)),
         0x78 => ld(r(A), r(B)),
         0x79 => ld(r(A), r(C)),
@@ -164,132 +325,132 @@ pub(super) fn decode(opcode: u8) -> Instr {
         0x7F => ld(r(A), r(A)),
 
         // 8bit ALU A,r
-        0x80 => alu(exec_add, r(B)),
-        0x81 => alu(exec_add, r(C)),
-        0x82 => alu(exec_add, r(D)),
-        0x83 => alu(exec_add, r(E)),
-        0x84 => alu(exec_add, r(H)),
-        0x85 => alu(exec_add, r(L)),
-        0x86 => alu(exec_add, ind(Indirect::HL)),
-        0x87 => alu(exec_add, r(A)),
-        0x88 => alu(exec_adc, r(B)),
-        0x89 => alu(exec_adc, r(C)),
-        0x8A => alu(exec_adc, r(D)),
-        0x8B => alu(exec_adc, r(E)),
-        0x8C => alu(exec_adc, r(H)),
-        0x8D => alu(exec_adc, r(L)),
-        0x8E => alu(exec_adc, ind(Indirect::HL)),
-        0x8F => alu(exec_adc, r(A)),
-        0x90 => alu(exec_sub, r(B)),
-        0x91 => alu(exec_sub, r(C)),
-        0x92 => alu(exec_sub, r(D)),
-        0x93 => alu(exec_sub, r(E)),
-        0x94 => alu(exec_sub, r(H)),
-        0x95 => alu(exec_sub, r(L)),
-        0x96 => alu(exec_sub, ind(Indirect::HL)),
-        0x97 => alu(exec_sub, r(A)),
-        0x98 => alu(exec_sbc, r(B)),
-        0x99 => alu(exec_sbc, r(C)),
-        0x9A => alu(exec_sbc, r(D)),
-        0x9B => alu(exec_sbc, r(E)),
-        0x9C => alu(exec_sbc, r(H)),
-        0x9D => alu(exec_sbc, r(L)),
-        0x9E => alu(exec_sbc, ind(Indirect::HL)),
-        0x9F => alu(exec_sbc, r(A)),
-        0xA0 => alu(exec_and, r(B)),
-        0xA1 => alu(exec_and, r(C)),
-        0xA2 => alu(exec_and, r(D)),
-        0xA3 => alu(exec_and, r(E)),
-        0xA4 => alu(exec_and, r(H)),
-        0xA5 => alu(exec_and, r(L)),
-        0xA6 => alu(exec_and, ind(Indirect::HL)),
-        0xA7 => alu(exec_and, r(A)),
-        0xA8 => alu(exec_xor, r(B)),
-        0xA9 => alu(exec_xor, r(C)),
-        0xAA => alu(exec_xor, r(D)),
-        0xAB => alu(exec_xor, r(E)),
-        0xAC => alu(exec_xor, r(H)),
-        0xAD => alu(exec_xor, r(L)),
-        0xAE => alu(exec_xor, ind(Indirect::HL)),
-        0xAF => alu(exec_xor, r(A)),
-        0xB0 => alu(exec_or, r(B)),
-        0xB1 => alu(exec_or, r(C)),
-        0xB2 => alu(exec_or, r(D)),
-        0xB3 => alu(exec_or, r(E)),
-        0xB4 => alu(exec_or, r(H)),
-        0xB5 => alu(exec_or, r(L)),
-        0xB6 => alu(exec_or, ind(Indirect::HL)),
-        0xB7 => alu(exec_or, r(A)),
-        0xB8 => alu(exec_cp, r(B)),
-        0xB9 => alu(exec_cp, r(C)),
-        0xBA => alu(exec_cp, r(D)),
-        0xBB => alu(exec_cp, r(E)),
-        0xBC => alu(exec_cp, r(H)),
-        0xBD => alu(exec_cp, r(L)),
-        0xBE => alu(exec_cp, ind(Indirect::HL)),
-        0xBF => alu(exec_cp, r(A)),
+        0x80 => alu(Op::Add, r(B)),
+        0x81 => alu(Op::Add, r(C)),
+        0x82 => alu(Op::Add, r(D)),
+        0x83 => alu(Op::Add, r(E)),
+        0x84 => alu(Op::Add, r(H)),
+        0x85 => alu(Op::Add, r(L)),
+        0x86 => alu(Op::Add, ind(Indirect::HL)),
+        0x87 => alu(Op::Add, r(A)),
+        0x88 => alu(Op::Adc, r(B)),
+        0x89 => alu(Op::Adc, r(C)),
+        0x8A => alu(Op::Adc, r(D)),
+        0x8B => alu(Op::Adc, r(E)),
+        0x8C => alu(Op::Adc, r(H)),
+        0x8D => alu(Op::Adc, r(L)),
+        0x8E => alu(Op::Adc, ind(Indirect::HL)),
+        0x8F => alu(Op::Adc, r(A)),
+        0x90 => alu(Op::Sub, r(B)),
+        0x91 => alu(Op::Sub, r(C)),
+        0x92 => alu(Op::Sub, r(D)),
+        0x93 => alu(Op::Sub, r(E)),
+        0x94 => alu(Op::Sub, r(H)),
+        0x95 => alu(Op::Sub, r(L)),
+        0x96 => alu(Op::Sub, ind(Indirect::HL)),
+        0x97 => alu(Op::Sub, r(A)),
+        0x98 => alu(Op::Sbc, r(B)),
+        0x99 => alu(Op::Sbc, r(C)),
+        0x9A => alu(Op::Sbc, r(D)),
+        0x9B => alu(Op::Sbc, r(E)),
+        0x9C => alu(Op::Sbc, r(H)),
+        0x9D => alu(Op::Sbc, r(L)),
+        0x9E => alu(Op::Sbc, ind(Indirect::HL)),
+        0x9F => alu(Op::Sbc, r(A)),
+        0xA0 => alu(Op::And, r(B)),
+        0xA1 => alu(Op::And, r(C)),
+        0xA2 => alu(Op::And, r(D)),
+        0xA3 => alu(Op::And, r(E)),
+        0xA4 => alu(Op::And, r(H)),
+        0xA5 => alu(Op::And, r(L)),
+        0xA6 => alu(Op::And, ind(Indirect::HL)),
+        0xA7 => alu(Op::And, r(A)),
+        0xA8 => alu(Op::Xor, r(B)),
+        0xA9 => alu(Op::Xor, r(C)),
+        0xAA => alu(Op::Xor, r(D)),
+        0xAB => alu(Op::Xor, r(E)),
+        0xAC => alu(Op::Xor, r(H)),
+        0xAD => alu(Op::Xor, r(L)),
+        0xAE => alu(Op::Xor, ind(Indirect::HL)),
+        0xAF => alu(Op::Xor, r(A)),
+        0xB0 => alu(Op::Or, r(B)),
+        0xB1 => alu(Op::Or, r(C)),
+        0xB2 => alu(Op::Or, r(D)),
+        0xB3 => alu(Op::Or, r(E)),
+        0xB4 => alu(Op::Or, r(H)),
+        0xB5 => alu(Op::Or, r(L)),
+        0xB6 => alu(Op::Or, ind(Indirect::HL)),
+        0xB7 => alu(Op::Or, r(A)),
+        0xB8 => alu(Op::Cp, r(B)),
+        0xB9 => alu(Op::Cp, r(C)),
+        0xBA => alu(Op::Cp, r(D)),
+        0xBB => alu(Op::Cp, r(E)),
+        0xBC => alu(Op::Cp, r(H)),
+        0xBD => alu(Op::Cp, r(L)),
+        0xBE => alu(Op::Cp, ind(Indirect::HL)),
+        0xBF => alu(Op::Cp, r(A)),
 
-        0xC0 => Instr::with(N, cc(Cond::NZ), exec_ret_cc),
-        0xC1 => Instr::with(rr(BC), N, exec_pop),
-        0xC2 => Instr::with(N, cc(Cond::NZ), exec_jp),
-        0xC3 => Instr::with(N, N, exec_jp),
-        0xC4 => Instr::with(N, cc(Cond::NZ), exec_call),
-        0xC5 => Instr::with(N, rr(BC), exec_push),
-        0xC6 => alu(exec_add, IMM),
-        0xC7 => Instr::with(Operand::Rst(0x00), N, exec_rst),
-        0xC8 => Instr::with(N, cc(Cond::Z), exec_ret_cc),
-        0xC9 => Instr::simple(exec_ret),
-        0xCA => Instr::with(N, cc(Cond::Z), exec_jp),
-        0xCB => Instr::simple(exec_cb_prefix),
-        0xCC => Instr::with(N, cc(Cond::Z), exec_call),
-        0xCD => Instr::with(N, N, exec_call),
-        0xCE => alu(exec_adc, IMM),
-        0xCF => Instr::with(Operand::Rst(0x08), N, exec_rst),
-        0xD0 => Instr::with(N, cc(Cond::NC), exec_ret_cc),
-        0xD1 => Instr::with(rr(DE), N, exec_pop),
-        0xD2 => Instr::with(N, cc(Cond::NC), exec_jp),
-        0xD4 => Instr::with(N, cc(Cond::NC), exec_call),
-        0xD5 => Instr::with(N, rr(DE), exec_push),
-        0xD6 => alu(exec_sub, IMM),
-        0xD7 => Instr::with(Operand::Rst(0x10), N, exec_rst),
-        0xD8 => Instr::with(N, cc(Cond::C), exec_ret_cc),
-        0xD9 => Instr::simple(exec_reti),
-        0xDA => Instr::with(N, cc(Cond::C), exec_jp),
-        0xDC => Instr::with(N, cc(Cond::C), exec_call),
-        0xDE => alu(exec_sbc, IMM),
-        0xDF => Instr::with(Operand::Rst(0x18), N, exec_rst),
-        0xE0 => Instr::simple(exec_ldh_n_a),
-        0xE1 => Instr::with(rr(HL), N, exec_pop),
+        0xC0 => with(N, cc(Cond::NZ), Op::RetCc),
+        0xC1 => with(rr(BC), N, Op::Pop),
+        0xC2 => with(N, cc(Cond::NZ), Op::Jp),
+        0xC3 => with(N, N, Op::Jp),
+        0xC4 => with(N, cc(Cond::NZ), Op::Call),
+        0xC5 => with(N, rr(BC), Op::Push),
+        0xC6 => alu(Op::Add, IMM),
+        0xC7 => with(Operand::Rst(0x00), N, Op::Rst),
+        0xC8 => with(N, cc(Cond::Z), Op::RetCc),
+        0xC9 => simple(Op::Ret),
+        0xCA => with(N, cc(Cond::Z), Op::Jp),
+        0xCB => simple(Op::Prefix),
+        0xCC => with(N, cc(Cond::Z), Op::Call),
+        0xCD => with(N, N, Op::Call),
+        0xCE => alu(Op::Adc, IMM),
+        0xCF => with(Operand::Rst(0x08), N, Op::Rst),
+        0xD0 => with(N, cc(Cond::NC), Op::RetCc),
+        0xD1 => with(rr(DE), N, Op::Pop),
+        0xD2 => with(N, cc(Cond::NC), Op::Jp),
+        0xD4 => with(N, cc(Cond::NC), Op::Call),
+        0xD5 => with(N, rr(DE), Op::Push),
+        0xD6 => alu(Op::Sub, IMM),
+        0xD7 => with(Operand::Rst(0x10), N, Op::Rst),
+        0xD8 => with(N, cc(Cond::C), Op::RetCc),
+        0xD9 => simple(Op::Reti),
+        0xDA => with(N, cc(Cond::C), Op::Jp),
+        0xDC => with(N, cc(Cond::C), Op::Call),
+        0xDE => alu(Op::Sbc, IMM),
+        0xDF => with(Operand::Rst(0x18), N, Op::Rst),
+        0xE0 => simple(Op::LdhNA),
+        0xE1 => with(rr(HL), N, Op::Pop),
         0xE2 => ld(ind(Indirect::CFF), r(A)),
-        0xE5 => Instr::with(N, rr(HL), exec_push),
-        0xE6 => alu(exec_and, IMM),
-        0xE7 => Instr::with(Operand::Rst(0x20), N, exec_rst),
-        0xE8 => Instr::simple(exec_add_sp),
-        0xE9 => Instr::simple(exec_jp_hl),
-        0xEA => Instr::simple(exec_ld_abs_a),
-        0xEE => alu(exec_xor, IMM),
-        0xEF => Instr::with(Operand::Rst(0x28), N, exec_rst),
-        0xF0 => Instr::simple(exec_ldh_a_n),
-        0xF1 => Instr::with(rr(AF), N, exec_pop),
+        0xE5 => with(N, rr(HL), Op::Push),
+        0xE6 => alu(Op::And, IMM),
+        0xE7 => with(Operand::Rst(0x20), N, Op::Rst),
+        0xE8 => simple(Op::AddSp),
+        0xE9 => simple(Op::JpHl),
+        0xEA => simple(Op::LdAbsA),
+        0xEE => alu(Op::Xor, IMM),
+        0xEF => with(Operand::Rst(0x28), N, Op::Rst),
+        0xF0 => simple(Op::LdhAN),
+        0xF1 => with(rr(AF), N, Op::Pop),
         0xF2 => ld(r(A), ind(Indirect::CFF)),
-        0xF3 => Instr::simple(exec_di),
-        0xF5 => Instr::with(N, rr(AF), exec_push),
-        0xF6 => alu(exec_or, IMM),
-        0xF7 => Instr::with(Operand::Rst(0x30), N, exec_rst),
-        0xF8 => Instr::simple(exec_ldhl),
-        0xF9 => Instr::simple(exec_ld_sp_hl),
-        0xFA => Instr::simple(exec_ld_a_abs),
-        0xFB => Instr::simple(exec_ei),
-        0xFE => alu(exec_cp, IMM),
-        0xFF => Instr::with(Operand::Rst(0x38), N, exec_rst),
+        0xF3 => simple(Op::Di),
+        0xF5 => with(N, rr(AF), Op::Push),
+        0xF6 => alu(Op::Or, IMM),
+        0xF7 => with(Operand::Rst(0x30), N, Op::Rst),
+        0xF8 => simple(Op::Ldhl),
+        0xF9 => simple(Op::LdSpHl),
+        0xFA => simple(Op::LdAAbs),
+        0xFB => simple(Op::Ei),
+        0xFE => alu(Op::Cp, IMM),
+        0xFF => with(Operand::Rst(0x38), N, Op::Rst),
 
         // 未定義オペコード: NOP として扱う
-        _ => Instr::simple(exec_nop),
+        _ => simple(Op::Illegal),
     }
 }
 
 /// CB プリフィックス命令のデコード
-pub(super) fn decode_cb(cb: u8) -> Instr {
+pub(super) fn decode_cb_op(cb: u8) -> Decoded {
     use Reg8::*;
     let target = match cb & 0x07 {
         0 => r(B),
@@ -305,22 +466,22 @@ pub(super) fn decode_cb(cb: u8) -> Instr {
     let n = (cb >> 3) & 0x07;
     match cb >> 6 {
         0 => {
-            let e: ExecFn = match (cb >> 3) & 0x07 {
-                0 => exec_cb_rlc,
-                1 => exec_cb_rrc,
-                2 => exec_cb_rl,
-                3 => exec_cb_rr,
-                4 => exec_cb_sla,
-                5 => exec_cb_sra,
-                6 => exec_cb_swap,
-                7 => exec_cb_srl,
+            let op = match (cb >> 3) & 0x07 {
+                0 => Op::Rlc,
+                1 => Op::Rrc,
+                2 => Op::Rl,
+                3 => Op::Rr,
+                4 => Op::Sla,
+                5 => Op::Sra,
+                6 => Op::Swap,
+                7 => Op::Srl,
                 _ => unreachable!(),
             };
-            Instr::with(target, N, e)
+            with(target, N, op)
         }
-        1 => Instr::with(target, Operand::Bit(n), exec_cb_bit),
-        2 => Instr::with(target, Operand::Bit(n), exec_cb_res),
-        3 => Instr::with(target, Operand::Bit(n), exec_cb_set),
+        1 => with(target, Operand::Bit(n), Op::Bit),
+        2 => with(target, Operand::Bit(n), Op::Res),
+        3 => with(target, Operand::Bit(n), Op::Set),
         _ => unreachable!(),
     }
 }
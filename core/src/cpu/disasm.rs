@@ -0,0 +1,464 @@
+//! SM83 逆アセンブラ。
+//!
+//! CPU と同じデコード表 ([`decode_op`] / [`decode_cb_op`]) から命令を組み立てるので、
+//! 実行される命令と表示が食い違わない。表記は RGBDS 風（`LD A, [HL+]`）で、
+//! 分岐先は絶対アドレスに解決し、I/O レジスタは `rLCDC` などの名前で表示する。
+
+use core::fmt;
+
+use super::decode::{Decoded, Op, decode_cb_op, decode_op};
+use super::operand::{Cond, Indirect, Operand, Reg8, Reg16};
+
+/// 逆アセンブルした 1 命令
+#[derive(Clone, Copy)]
+pub struct Instruction {
+    addr: u16,
+    bytes: [u8; 3],
+    size: u8,
+    decoded: Decoded,
+}
+
+/// 命令のサイクル数（T-cycle）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycles {
+    /// 条件付き命令は条件成立時
+    pub taken: u8,
+    /// 条件不成立時（条件付きの JR / JP / CALL / RET のみ）
+    pub not_taken: Option<u8>,
+}
+
+/// `addr` の命令を逆アセンブルする。`bytes` は `addr` からのメモリ内容で、
+/// 足りないオペランドは 0 とみなす。
+pub fn disassemble(addr: u16, bytes: &[u8]) -> Instruction {
+    let byte = |i: usize| bytes.get(i).copied().unwrap_or(0);
+    let opcode = byte(0);
+    let decoded = if opcode == 0xCB { decode_cb_op(byte(1)) } else { decode_op(opcode) };
+    let size = if opcode == 0xCB { 2 } else { size(&decoded) };
+    Instruction { addr, bytes: [opcode, byte(1), byte(2)], size, decoded }
+}
+
+/// CB 以外の命令長
+fn size(d: &Decoded) -> u8 {
+    match d.op {
+        Op::Ld16 | Op::LdNnSp | Op::Jp | Op::Call | Op::LdAbsA | Op::LdAAbs => 3,
+        Op::Jr | Op::LdhNA | Op::LdhAN | Op::AddSp | Op::Ldhl => 2,
+        _ if matches!(d.src, Operand::Imm) => 2,
+        _ => 1,
+    }
+}
+
+impl Instruction {
+    pub fn addr(&self) -> u16 {
+        self.addr
+    }
+
+    /// 命令長（1-3 バイト）
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    /// 命令を構成するバイト列
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.size as usize]
+    }
+
+    /// 次の命令のアドレス
+    pub fn next_addr(&self) -> u16 {
+        self.addr.wrapping_add(self.size as u16)
+    }
+
+    fn imm8(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    fn imm16(&self) -> u16 {
+        u16::from_le_bytes([self.bytes[1], self.bytes[2]])
+    }
+
+    /// JR / JP / CALL / RST の分岐先（JP HL と RET は実行時まで決まらないので None）
+    pub fn target(&self) -> Option<u16> {
+        match self.decoded.op {
+            Op::Jr => Some(self.next_addr().wrapping_add(self.imm8() as i8 as u16)),
+            Op::Jp | Op::Call => Some(self.imm16()),
+            Op::Rst => Some(self.decoded.dst.rst() as u16),
+            _ => None,
+        }
+    }
+
+    /// 実行にかかるサイクル数
+    pub fn cycles(&self) -> Cycles {
+        let d = &self.decoded;
+        let mem = |o: Operand| matches!(o, Operand::Ind(_) | Operand::Imm) as u8;
+        // M-cycle 数（条件成立時, 不成立時）
+        let (taken, not_taken) = match d.op {
+            Op::Ld8 => (1 + mem(d.dst) + mem(d.src), None),
+            Op::Add | Op::Adc | Op::Sub | Op::Sbc | Op::And | Op::Xor | Op::Or | Op::Cp => {
+                (1 + mem(d.src), None)
+            }
+            Op::Inc8 | Op::Dec8 => (1 + 2 * mem(d.dst), None),
+            Op::Inc16 | Op::Dec16 | Op::AddHl | Op::LdSpHl => (2, None),
+            Op::Ld16 | Op::Pop | Op::LdhNA | Op::LdhAN | Op::Ldhl => (3, None),
+            Op::Ret | Op::Reti | Op::Rst | Op::Push | Op::LdAbsA | Op::LdAAbs | Op::AddSp => {
+                (4, None)
+            }
+            Op::LdNnSp => (5, None),
+            Op::Jr => (3, Some(2)),
+            Op::Jp => (4, Some(3)),
+            Op::Call => (6, Some(3)),
+            Op::RetCc => (5, Some(2)),
+            // CB: プリフィックス + 本体。(HL) は BIT が読むだけ、他は読み書きする
+            Op::Bit => (2 + mem(d.dst), None),
+            Op::Rlc | Op::Rrc | Op::Rl | Op::Rr | Op::Sla | Op::Sra | Op::Swap | Op::Srl
+            | Op::Res | Op::Set => (2 + 2 * mem(d.dst), None),
+            _ => (1, None),
+        };
+        // 条件なしの JR / JP / CALL は常に成立側
+        let not_taken = not_taken.filter(|_| matches!(d.src, Operand::Cond(_)));
+        Cycles { taken: taken * 4, not_taken: not_taken.map(|m| m * 4) }
+    }
+
+    fn mnemonic(&self) -> &'static str {
+        match self.decoded.op {
+            Op::Nop => "NOP",
+            Op::Ld8 if matches!(self.decoded.dst, Operand::Ind(Indirect::CFF)) => "LDH",
+            Op::Ld8 if matches!(self.decoded.src, Operand::Ind(Indirect::CFF)) => "LDH",
+            Op::Ld8 | Op::Ld16 | Op::LdNnSp | Op::LdAbsA | Op::LdAAbs | Op::Ldhl | Op::LdSpHl => {
+                "LD"
+            }
+            Op::LdhNA | Op::LdhAN => "LDH",
+            Op::Inc8 | Op::Inc16 => "INC",
+            Op::Dec8 | Op::Dec16 => "DEC",
+            Op::Rlca => "RLCA",
+            Op::Rrca => "RRCA",
+            Op::Rla => "RLA",
+            Op::Rra => "RRA",
+            Op::Stop => "STOP",
+            Op::Halt => "HALT",
+            Op::Jr => "JR",
+            Op::Daa => "DAA",
+            Op::Cpl => "CPL",
+            Op::Scf => "SCF",
+            Op::Ccf => "CCF",
+            Op::Add | Op::AddHl | Op::AddSp => "ADD",
+            Op::Adc => "ADC",
+            Op::Sub => "SUB",
+            Op::Sbc => "SBC",
+            Op::And => "AND",
+            Op::Xor => "XOR",
+            Op::Or => "OR",
+            Op::Cp => "CP",
+            Op::Ret | Op::RetCc => "RET",
+            Op::Reti => "RETI",
+            Op::Pop => "POP",
+            Op::Push => "PUSH",
+            Op::Jp | Op::JpHl => "JP",
+            Op::Call => "CALL",
+            Op::Rst => "RST",
+            Op::Di => "DI",
+            Op::Ei => "EI",
+            Op::Rlc => "RLC",
+            Op::Rrc => "RRC",
+            Op::Rl => "RL",
+            Op::Rr => "RR",
+            Op::Sla => "SLA",
+            Op::Sra => "SRA",
+            Op::Swap => "SWAP",
+            Op::Srl => "SRL",
+            Op::Bit => "BIT",
+            Op::Res => "RES",
+            Op::Set => "SET",
+            // CB は disassemble() が次のバイトと合わせてデコードするので現れない
+            Op::Prefix => "PREFIX",
+            Op::Illegal => "DB",
+        }
+    }
+
+    fn write_operand(&self, f: &mut fmt::Formatter<'_>, o: Operand) -> fmt::Result {
+        match o {
+            Operand::None => Ok(()),
+            Operand::Reg8(r) => f.write_str(reg8_name(r)),
+            Operand::Reg16(r) => f.write_str(reg16_name(r)),
+            Operand::Imm => write!(f, "${:02X}", self.imm8()),
+            Operand::Ind(i) => write!(f, "[{}]", indirect_name(i)),
+            Operand::Cond(c) => f.write_str(cond_name(c)),
+            Operand::Bit(n) => write!(f, "{}", n),
+            Operand::Rst(v) => write!(f, "${:02X}", v),
+        }
+    }
+
+    /// 分岐命令の `[条件, ]分岐先`
+    fn write_branch(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Operand::Cond(c) = self.decoded.src {
+            write!(f, " {},", cond_name(c))?;
+        }
+        write!(f, " ${:04X}", self.target().unwrap_or(0))
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = &self.decoded;
+        f.write_str(self.mnemonic())?;
+        let offset = Offset(self.imm8() as i8);
+        match d.op {
+            Op::Jr | Op::Jp | Op::Call => self.write_branch(f),
+            Op::RetCc => write!(f, " {}", d.src.cond().map_or("", cond_name)),
+            Op::Rst => write!(f, " ${:02X}", d.dst.rst()),
+            Op::Ld16 => write!(f, " {}, ${:04X}", reg16_name(d.dst.reg16()), self.imm16()),
+            Op::LdNnSp => write!(f, " [${:04X}], SP", self.imm16()),
+            Op::LdAbsA => write!(f, " [{}], A", Address(self.imm16())),
+            Op::LdAAbs => write!(f, " A, [{}]", Address(self.imm16())),
+            Op::LdhNA => write!(f, " [{}], A", Address(0xFF00 | self.imm8() as u16)),
+            Op::LdhAN => write!(f, " A, [{}]", Address(0xFF00 | self.imm8() as u16)),
+            Op::AddHl => write!(f, " HL, {}", reg16_name(d.src.reg16())),
+            Op::AddSp => write!(f, " SP, {}", offset),
+            Op::Ldhl if offset.0 < 0 => write!(f, " HL, SP{}", offset),
+            Op::Ldhl => write!(f, " HL, SP+{}", offset),
+            Op::LdSpHl => f.write_str(" SP, HL"),
+            Op::JpHl => f.write_str(" HL"),
+            Op::Push => write!(f, " {}", reg16_name(d.src.reg16())),
+            Op::Bit | Op::Res | Op::Set => {
+                write!(f, " {}, ", d.src.bit())?;
+                self.write_operand(f, d.dst)
+            }
+            Op::Add | Op::Adc | Op::Sub | Op::Sbc | Op::And | Op::Xor | Op::Or | Op::Cp => {
+                f.write_str(" A, ")?;
+                self.write_operand(f, d.src)
+            }
+            Op::Illegal => write!(f, " ${:02X}", self.bytes[0]),
+            _ => match (d.dst, d.src) {
+                (Operand::None, _) => Ok(()),
+                (dst, Operand::None) => {
+                    f.write_str(" ")?;
+                    self.write_operand(f, dst)
+                }
+                (dst, src) => {
+                    f.write_str(" ")?;
+                    self.write_operand(f, dst)?;
+                    f.write_str(", ")?;
+                    self.write_operand(f, src)
+                }
+            },
+        }
+    }
+}
+
+/// 符号付きオフセット（`$05` / `-$03`）
+struct Offset(i8);
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        write!(f, "{}${:02X}", sign, self.0.unsigned_abs())
+    }
+}
+
+/// メモリアドレス。I/O レジスタなら名前で表示する
+struct Address(u16);
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match io_register_name(self.0) {
+            Some(name) => f.write_str(name),
+            None => write!(f, "${:04X}", self.0),
+        }
+    }
+}
+
+/// I/O レジスタ名（hardware.inc の表記）
+pub fn io_register_name(addr: u16) -> Option<&'static str> {
+    Some(match addr {
+        0xFF00 => "rP1",
+        0xFF01 => "rSB",
+        0xFF02 => "rSC",
+        0xFF04 => "rDIV",
+        0xFF05 => "rTIMA",
+        0xFF06 => "rTMA",
+        0xFF07 => "rTAC",
+        0xFF0F => "rIF",
+        0xFF10 => "rNR10",
+        0xFF11 => "rNR11",
+        0xFF12 => "rNR12",
+        0xFF13 => "rNR13",
+        0xFF14 => "rNR14",
+        0xFF16 => "rNR21",
+        0xFF17 => "rNR22",
+        0xFF18 => "rNR23",
+        0xFF19 => "rNR24",
+        0xFF1A => "rNR30",
+        0xFF1B => "rNR31",
+        0xFF1C => "rNR32",
+        0xFF1D => "rNR33",
+        0xFF1E => "rNR34",
+        0xFF20 => "rNR41",
+        0xFF21 => "rNR42",
+        0xFF22 => "rNR43",
+        0xFF23 => "rNR44",
+        0xFF24 => "rNR50",
+        0xFF25 => "rNR51",
+        0xFF26 => "rNR52",
+        0xFF40 => "rLCDC",
+        0xFF41 => "rSTAT",
+        0xFF42 => "rSCY",
+        0xFF43 => "rSCX",
+        0xFF44 => "rLY",
+        0xFF45 => "rLYC",
+        0xFF46 => "rDMA",
+        0xFF47 => "rBGP",
+        0xFF48 => "rOBP0",
+        0xFF49 => "rOBP1",
+        0xFF4A => "rWY",
+        0xFF4B => "rWX",
+        0xFF4C => "rKEY0",
+        0xFF4D => "rKEY1",
+        0xFF4F => "rVBK",
+        0xFF50 => "rBANK",
+        0xFF51 => "rHDMA1",
+        0xFF52 => "rHDMA2",
+        0xFF53 => "rHDMA3",
+        0xFF54 => "rHDMA4",
+        0xFF55 => "rHDMA5",
+        0xFF56 => "rRP",
+        0xFF68 => "rBCPS",
+        0xFF69 => "rBCPD",
+        0xFF6A => "rOCPS",
+        0xFF6B => "rOCPD",
+        0xFF6C => "rOPRI",
+        0xFF70 => "rSVBK",
+        0xFF76 => "rPCM12",
+        0xFF77 => "rPCM34",
+        0xFFFF => "rIE",
+        _ => return None,
+    })
+}
+
+fn reg8_name(r: Reg8) -> &'static str {
+    match r {
+        Reg8::A => "A",
+        Reg8::B => "B",
+        Reg8::C => "C",
+        Reg8::D => "D",
+        Reg8::E => "E",
+        Reg8::H => "H",
+        Reg8::L => "L",
+    }
+}
+
+fn reg16_name(r: Reg16) -> &'static str {
+    match r {
+        Reg16::AF => "AF",
+        Reg16::BC => "BC",
+        Reg16::DE => "DE",
+        Reg16::HL => "HL",
+        Reg16::SP => "SP",
+    }
+}
+
+fn indirect_name(i: Indirect) -> &'static str {
+    match i {
+        Indirect::BC => "BC",
+        Indirect::DE => "DE",
+        Indirect::HL => "HL",
+        Indirect::HLI => "HL+",
+        Indirect::HLD => "HL-",
+        Indirect::CFF => "C",
+    }
+}
+
+fn cond_name(c: Cond) -> &'static str {
+    match c {
+        Cond::NZ => "NZ",
+        Cond::Z => "Z",
+        Cond::NC => "NC",
+        Cond::C => "C",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// JSON のオペランド記述を、即値がすべて 0 のときの表示に直す
+    fn expected_text(op: &Value) -> String {
+        let mut text = op["mnemonic"].as_str().unwrap().to_string();
+        let operands: Vec<String> = op["operands"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| {
+                let flag = |key: &str| o[key].as_bool().unwrap_or(false);
+                let name = match o["name"].as_str().unwrap() {
+                    "n8" => "$00".to_string(),
+                    "n16" | "a16" => "$0000".to_string(),
+                    "a8" => "rP1".to_string(),
+                    // JR の分岐先は次の命令、ADD SP は符号なしの 0
+                    "e8" if text == "JR" => "$0002".to_string(),
+                    "e8" => "$00".to_string(),
+                    n if flag("increment") => format!("{}+", n),
+                    n if flag("decrement") => format!("{}-", n),
+                    n => n.to_string(),
+                };
+                if flag("immediate") { name } else { format!("[{}]", name) }
+            })
+            .collect();
+        // LD HL, SP+e8 は SP と e8 で 1 つのオペランド
+        let operands = operands.join(", ").replace("SP+, ", "SP+");
+        if !operands.is_empty() {
+            text += " ";
+            text += &operands;
+        }
+        text
+    }
+
+    fn check(bytes: &[u8], op: &Value) {
+        let ins = disassemble(0, bytes);
+        let mnemonic = op["mnemonic"].as_str().unwrap();
+        if mnemonic.starts_with("ILLEGAL") {
+            assert_eq!(ins.decoded.op, Op::Illegal, "{:02X?}", bytes);
+            assert_eq!(ins.to_string(), format!("DB ${:02X}", bytes[0]));
+            return;
+        }
+        let cycles = op["cycles"].as_array().unwrap();
+        let cycle = |c: &Value| c.as_u64().unwrap() as u8;
+        let expected = Cycles { taken: cycle(&cycles[0]), not_taken: cycles.get(1).map(cycle) };
+        assert_eq!(ins.cycles(), expected, "{:02X?} {}", bytes, ins);
+        // STOP は CPU が 1 バイト命令として実行する（次のバイトを読み飛ばさない）
+        if mnemonic == "STOP" {
+            assert_eq!((ins.size(), ins.to_string().as_str()), (1, "STOP"));
+            return;
+        }
+        assert_eq!(Some(ins.size() as u64), op["bytes"].as_u64(), "{:02X?}", bytes);
+        assert_eq!(ins.to_string(), expected_text(op), "{:02X?}", bytes);
+    }
+
+    #[test]
+    fn matches_opcodes_json() {
+        let json: Value =
+            serde_json::from_slice(include_bytes!("../../../Opcodes.json")).unwrap();
+        for (key, op) in json["unprefixed"].as_object().unwrap() {
+            let opcode = u8::from_str_radix(&key[2..], 16).unwrap();
+            if opcode != 0xCB {
+                check(&[opcode, 0, 0], op);
+            }
+        }
+        for cb in 0..=255u8 {
+            check(&[0xCB, cb], &json["cbprefixed"][format!("0x{:02X}", cb)]);
+        }
+    }
+
+    #[test]
+    fn resolves_targets_and_io_names() {
+        let jr = disassemble(0x0150, &[0x20, 0xFE]);
+        assert_eq!(jr.to_string(), "JR NZ, $0150");
+        assert_eq!(jr.target(), Some(0x0150));
+        assert_eq!(jr.cycles(), Cycles { taken: 12, not_taken: Some(8) });
+        assert_eq!(disassemble(0, &[0xE0, 0x40]).to_string(), "LDH [rLCDC], A");
+        assert_eq!(disassemble(0, &[0xF0, 0x80]).to_string(), "LDH A, [$FF80]");
+        assert_eq!(disassemble(0, &[0xEA, 0xFF, 0xFF]).to_string(), "LD [rIE], A");
+        assert_eq!(disassemble(0, &[0xF8, 0xFD]).to_string(), "LD HL, SP-$03");
+        let call = disassemble(0x4000, &[0xCD, 0x34, 0x12]);
+        assert_eq!((call.to_string().as_str(), call.size(), call.next_addr()), ("CALL $1234", 3, 0x4003));
+        assert_eq!(disassemble(0, &[0xCB, 0x7E]).bytes(), [0xCB, 0x7E]);
+    }
+}
@@ -4,6 +4,7 @@
 
 use super::Cpu;
 use super::decode;
+use super::instr::InstrSource;
 use super::operand::{Cond, Indirect, Operand, Reg8, Reg16};
 use crate::mmu::MemoryBus;
 
@@ -741,6 +742,7 @@ pub(crate) fn exec_rra(cpu: &mut Cpu, _bus: &mut dyn MemoryBus) -> bool {
 pub(crate) fn exec_cb_prefix(cpu: &mut Cpu, bus: &mut dyn MemoryBus) -> bool {
     let cb = cpu.imm8(bus);
     cpu.instr = decode::decode_cb(cb);
+    cpu.instr_src = InstrSource::Cb(cb);
     false
 }
 
@@ -6,6 +6,18 @@ use crate::mmu::MemoryBus;
 /// 1 M-cycle分の制御を行い、命令完了で `true` を返す実行関数。
 pub type ExecFn = fn(&mut Cpu, &mut dyn MemoryBus) -> bool;
 
+/// `Instr` の生成元。`exec` は関数ポインタでシリアライズできないため、
+/// セーブステートではこれを保存して decode し直す。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InstrSource {
+    /// 通常オペコード
+    Opcode(u8),
+    /// CB プレフィックス命令（CB の次のバイト）
+    Cb(u8),
+    /// 割り込みディスパッチ
+    Interrupt,
+}
+
 /// 自己完結した命令実行ユニット。
 #[derive(Clone, Copy)]
 pub struct Instr {
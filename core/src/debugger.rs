@@ -0,0 +1,705 @@
+//! デバッガ: ブレークポイント・ウォッチポイント・条件ブレークとステップ実行。
+//!
+//! [`Debugger`] が停止条件を持ち、[`Debugger::run`] が [`GameBoy`] を M-cycle 単位で進めて
+//! 命令の境界で止まり、[`StopReason`] を返す。ウォッチポイントは CPU のメモリアクセスだけを
+//! 監視する（命令フェッチも読み出しに数える。OAM DMA / HDMA の転送は対象外）。
+
+use core::cell::Cell;
+use core::fmt;
+
+use crate::gameboy::GameBoy;
+use crate::input::InputSource;
+use crate::mmu::MemoryBus;
+use crate::platform::{AudioSink, CartridgeBus, Display, SerialPort};
+
+pub const MAX_BREAKPOINTS: usize = 32;
+pub const MAX_WATCHPOINTS: usize = 32;
+pub const MAX_CONDITIONS: usize = 16;
+
+/// F レジスタのフラグ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Flags {
+    pub z: bool,
+    pub n: bool,
+    pub h: bool,
+    pub c: bool,
+}
+
+/// CPU レジスタの写し。`pc` は次に実行する命令のアドレス。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuRegisters {
+    pub a: u8,
+    pub f: u8,
+    pub b: u8,
+    pub c: u8,
+    pub d: u8,
+    pub e: u8,
+    pub h: u8,
+    pub l: u8,
+    pub sp: u16,
+    pub pc: u16,
+    pub ime: bool,
+    pub halted: bool,
+}
+
+impl CpuRegisters {
+    pub fn af(&self) -> u16 {
+        u16::from_be_bytes([self.a, self.f])
+    }
+
+    pub fn bc(&self) -> u16 {
+        u16::from_be_bytes([self.b, self.c])
+    }
+
+    pub fn de(&self) -> u16 {
+        u16::from_be_bytes([self.d, self.e])
+    }
+
+    pub fn hl(&self) -> u16 {
+        u16::from_be_bytes([self.h, self.l])
+    }
+
+    pub fn flags(&self) -> Flags {
+        Flags {
+            z: self.f & 0x80 != 0,
+            n: self.f & 0x40 != 0,
+            h: self.f & 0x20 != 0,
+            c: self.f & 0x10 != 0,
+        }
+    }
+
+    pub fn get(&self, reg: Reg) -> u16 {
+        match reg {
+            Reg::A => self.a as u16,
+            Reg::F => self.f as u16,
+            Reg::B => self.b as u16,
+            Reg::C => self.c as u16,
+            Reg::D => self.d as u16,
+            Reg::E => self.e as u16,
+            Reg::H => self.h as u16,
+            Reg::L => self.l as u16,
+            Reg::AF => self.af(),
+            Reg::BC => self.bc(),
+            Reg::DE => self.de(),
+            Reg::HL => self.hl(),
+            Reg::SP => self.sp,
+            Reg::PC => self.pc,
+        }
+    }
+}
+
+/// 条件ブレークで比較するレジスタ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reg {
+    A,
+    F,
+    B,
+    C,
+    D,
+    E,
+    H,
+    L,
+    AF,
+    BC,
+    DE,
+    HL,
+    SP,
+    PC,
+}
+
+impl Reg {
+    const ALL: [Reg; 14] = [
+        Reg::A,
+        Reg::F,
+        Reg::B,
+        Reg::C,
+        Reg::D,
+        Reg::E,
+        Reg::H,
+        Reg::L,
+        Reg::AF,
+        Reg::BC,
+        Reg::DE,
+        Reg::HL,
+        Reg::SP,
+        Reg::PC,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Reg::A => "a",
+            Reg::F => "f",
+            Reg::B => "b",
+            Reg::C => "c",
+            Reg::D => "d",
+            Reg::E => "e",
+            Reg::H => "h",
+            Reg::L => "l",
+            Reg::AF => "af",
+            Reg::BC => "bc",
+            Reg::DE => "de",
+            Reg::HL => "hl",
+            Reg::SP => "sp",
+            Reg::PC => "pc",
+        }
+    }
+
+    /// レジスタ名（大文字小文字は問わない）
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// 比較演算子
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "==",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+        }
+    }
+
+    pub fn parse(symbol: &str) -> Option<Self> {
+        [CmpOp::Eq, CmpOp::Ne, CmpOp::Lt, CmpOp::Le, CmpOp::Gt, CmpOp::Ge]
+            .into_iter()
+            .find(|op| op.symbol() == symbol)
+    }
+}
+
+/// レジスタの値についての条件（例: `a == 0x10`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub reg: Reg,
+    pub op: CmpOp,
+    pub value: u16,
+}
+
+impl Condition {
+    pub fn holds(&self, regs: &CpuRegisters) -> bool {
+        let v = regs.get(self.reg);
+        match self.op {
+            CmpOp::Eq => v == self.value,
+            CmpOp::Ne => v != self.value,
+            CmpOp::Lt => v < self.value,
+            CmpOp::Le => v <= self.value,
+            CmpOp::Gt => v > self.value,
+            CmpOp::Ge => v >= self.value,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} 0x{:X}", self.reg.name(), self.op.symbol(), self.value)
+    }
+}
+
+/// PC ブレークポイント。`bank` を指定するとそのバンクが割り当てられているときだけ止まる
+/// （[`GameBoy::bank_at`] と比較）。`condition` を指定すると成り立つときだけ止まる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    pub bank: Option<usize>,
+    pub condition: Option<Condition>,
+}
+
+impl Breakpoint {
+    pub fn new(addr: u16) -> Self {
+        Self { addr, bank: None, condition: None }
+    }
+}
+
+/// メモリアクセスの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+/// アドレス範囲 `start..=end` へのアクセスで止まるウォッチポイント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub start: u16,
+    pub end: u16,
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl Watchpoint {
+    pub fn matches(&self, addr: u16, access: Access) -> bool {
+        let enabled = match access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+            Access::Execute => self.execute,
+        };
+        enabled && (self.start..=self.end).contains(&addr)
+    }
+}
+
+/// ウォッチポイントに一致したアクセス（`value` は読み書きした値、実行ならオペコード）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchHit {
+    pub index: usize,
+    pub addr: u16,
+    pub access: Access,
+    pub value: u8,
+}
+
+/// [`Debugger::run`] の実行単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunCommand {
+    /// 1 命令
+    StepInstruction,
+    /// 1 命令。CALL / RST は戻ってくるまで実行する
+    StepOver,
+    /// 現在のサブルーチンから RET で戻るまで
+    StepOut,
+    /// 次のフレーム完成まで
+    RunToFrame,
+    /// 停止条件に当たるまで
+    Continue,
+}
+
+/// [`Debugger::run`] が止まった理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// ステップ実行が完了した
+    Step,
+    /// フレームが完成した（[`RunCommand::RunToFrame`]）
+    Frame,
+    Breakpoint { index: usize },
+    Watchpoint(WatchHit),
+    /// 条件ブレークが成り立った
+    Condition { index: usize },
+    /// 入力の quit が立った
+    Quit,
+    /// 上限の M-cycle 数を実行した（ホストはここでイベント処理等をして再開できる）
+    Limit,
+}
+
+/// 停止条件の集合
+#[derive(Default)]
+pub struct Debugger {
+    breakpoints: heapless::Vec<Breakpoint, MAX_BREAKPOINTS>,
+    watchpoints: heapless::Vec<Watchpoint, MAX_WATCHPOINTS>,
+    conditions: heapless::Vec<Condition, MAX_CONDITIONS>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加した番号を返す（上限に達していれば None）
+    pub fn add_breakpoint(&mut self, bp: Breakpoint) -> Option<usize> {
+        self.breakpoints.push(bp).ok().map(|_| self.breakpoints.len() - 1)
+    }
+
+    pub fn remove_breakpoint(&mut self, index: usize) -> Option<Breakpoint> {
+        (index < self.breakpoints.len()).then(|| self.breakpoints.remove(index))
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn add_watchpoint(&mut self, wp: Watchpoint) -> Option<usize> {
+        self.watchpoints.push(wp).ok().map(|_| self.watchpoints.len() - 1)
+    }
+
+    pub fn remove_watchpoint(&mut self, index: usize) -> Option<Watchpoint> {
+        (index < self.watchpoints.len()).then(|| self.watchpoints.remove(index))
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// どこでも、成り立っていなかった条件が成り立った時点で止まる
+    pub fn add_condition(&mut self, cond: Condition) -> Option<usize> {
+        self.conditions.push(cond).ok().map(|_| self.conditions.len() - 1)
+    }
+
+    pub fn remove_condition(&mut self, index: usize) -> Option<Condition> {
+        (index < self.conditions.len()).then(|| self.conditions.remove(index))
+    }
+
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// `cmd` を最大 `max_cycles` M-cycle 実行する。上限に達しても命令の途中では止まらず、
+    /// 次の命令境界まで進めてから [`StopReason::Limit`] を返す。
+    ///
+    /// ブレークポイント・条件は 1 命令以上実行した後の命令境界で調べるので、
+    /// ブレークポイント上から再開してもすぐには止まらない。
+    pub fn run<C, D, A, I, S>(
+        &self,
+        gb: &mut GameBoy<C, D, A, I, S>,
+        cmd: RunCommand,
+        max_cycles: u64,
+    ) -> StopReason
+    where
+        C: CartridgeBus,
+        D: Display,
+        A: AudioSink,
+        I: InputSource,
+        S: SerialPort,
+    {
+        let start = gb.registers();
+        let mut opcode = gb.mmu().read(start.pc);
+        // step over で CALL / RST を飛ばすときの戻り先
+        let return_to = match cmd {
+            RunCommand::StepOver => call_return_addr(opcode, start.pc),
+            _ => None,
+        };
+        // 条件ブレークは偽→真になったときだけ止まる
+        let mut held = [false; MAX_CONDITIONS];
+        for (h, c) in held.iter_mut().zip(&self.conditions) {
+            *h = c.holds(&start);
+        }
+        let mut frame = false;
+        let mut watch_hit = None;
+
+        let mut cycles = 0;
+        loop {
+            cycles += 1;
+            let (result, hit) = gb.step_watched(&self.watchpoints, None);
+            if result.quit {
+                return StopReason::Quit;
+            }
+            frame |= result.frame_ready;
+            watch_hit = watch_hit.or(hit);
+            if !result.instruction_done {
+                // HALT 中のフレーム完成はその場（命令境界）で止まる
+                if cmd == RunCommand::RunToFrame && frame && gb.at_instruction_boundary() {
+                    return StopReason::Frame;
+                }
+                if cycles >= max_cycles && gb.at_instruction_boundary() {
+                    return StopReason::Limit;
+                }
+                continue;
+            }
+
+            if let Some(hit) = watch_hit {
+                return StopReason::Watchpoint(hit);
+            }
+            let regs = gb.registers();
+            let finished = match cmd {
+                RunCommand::StepInstruction => true,
+                RunCommand::StepOver => {
+                    return_to.is_none_or(|addr| regs.pc == addr && regs.sp >= start.sp)
+                }
+                RunCommand::StepOut => is_return(opcode) && regs.sp > start.sp,
+                RunCommand::RunToFrame | RunCommand::Continue => false,
+            };
+            if finished {
+                return StopReason::Step;
+            }
+            if cmd == RunCommand::RunToFrame && frame {
+                return StopReason::Frame;
+            }
+            if let Some(reason) = self.check(gb, &regs, &mut held) {
+                return reason;
+            }
+            if cycles >= max_cycles {
+                return StopReason::Limit;
+            }
+            opcode = gb.mmu().read(regs.pc);
+        }
+    }
+
+    /// 命令境界での停止条件（PC ブレークポイント・実行ウォッチポイント・条件ブレーク）
+    fn check<C, D, A, I, S>(
+        &self,
+        gb: &GameBoy<C, D, A, I, S>,
+        regs: &CpuRegisters,
+        held: &mut [bool; MAX_CONDITIONS],
+    ) -> Option<StopReason>
+    where
+        C: CartridgeBus,
+        D: Display,
+        A: AudioSink,
+        I: InputSource,
+        S: SerialPort,
+    {
+        let pc = regs.pc;
+        let hit_bp = self.breakpoints.iter().position(|bp| {
+            bp.addr == pc
+                && bp.bank.is_none_or(|bank| bank == gb.bank_at(pc))
+                && bp.condition.is_none_or(|c| c.holds(regs))
+        });
+        if let Some(index) = hit_bp {
+            return Some(StopReason::Breakpoint { index });
+        }
+        if let Some(index) = self.watchpoints.iter().position(|w| w.matches(pc, Access::Execute)) {
+            let value = gb.mmu().read(pc);
+            return Some(StopReason::Watchpoint(WatchHit {
+                index,
+                addr: pc,
+                access: Access::Execute,
+                value,
+            }));
+        }
+        let mut reason = None;
+        for (index, (c, h)) in self.conditions.iter().zip(held.iter_mut()).enumerate() {
+            let now = c.holds(regs);
+            if now && !*h && reason.is_none() {
+                reason = Some(StopReason::Condition { index });
+            }
+            *h = now;
+        }
+        reason
+    }
+}
+
+/// CALL / RST ならその戻り先
+fn call_return_addr(opcode: u8, pc: u16) -> Option<u16> {
+    match opcode {
+        0xC4 | 0xCC | 0xCD | 0xD4 | 0xDC => Some(pc.wrapping_add(3)),
+        op if op & 0xC7 == 0xC7 => Some(pc.wrapping_add(1)),
+        _ => None,
+    }
+}
+
+/// RET / RETI / RET cc
+fn is_return(opcode: u8) -> bool {
+    matches!(opcode, 0xC0 | 0xC8 | 0xC9 | 0xD0 | 0xD8 | 0xD9)
+}
+
+/// CPU のメモリアクセスをウォッチポイントと照合するバス
+pub(crate) struct WatchBus<'a, B: MemoryBus> {
+    bus: &'a mut B,
+    points: &'a [Watchpoint],
+    hit: Cell<Option<WatchHit>>,
+}
+
+impl<'a, B: MemoryBus> WatchBus<'a, B> {
+    pub(crate) fn new(bus: &'a mut B, points: &'a [Watchpoint]) -> Self {
+        Self { bus, points, hit: Cell::new(None) }
+    }
+
+    /// 最初に一致したアクセス
+    pub(crate) fn hit(&self) -> Option<WatchHit> {
+        self.hit.get()
+    }
+
+    fn check(&self, addr: u16, access: Access, value: u8) {
+        if self.hit.get().is_some() {
+            return;
+        }
+        if let Some(index) = self.points.iter().position(|w| w.matches(addr, access)) {
+            self.hit.set(Some(WatchHit { index, addr, access, value }));
+        }
+    }
+}
+
+impl<B: MemoryBus> MemoryBus for WatchBus<'_, B> {
+    fn read(&self, addr: u16) -> u8 {
+        let value = self.bus.read(addr);
+        self.check(addr, Access::Read, value);
+        value
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        self.check(addr, Access::Write, val);
+        self.bus.write(addr, val);
+    }
+
+    fn if_(&self) -> u8 {
+        self.bus.if_()
+    }
+
+    fn set_if(&mut self, val: u8) {
+        self.bus.set_if(val);
+    }
+
+    fn ie(&self) -> u8 {
+        self.bus.ie()
+    }
+
+    fn perform_speed_switch(&mut self) {
+        self.bus.perform_speed_switch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bootrom::Bootrom;
+    use crate::cartridge::{MbcCart, NoClock};
+    use crate::cartridge::tests::banked_rom;
+    use crate::input::NullInput;
+    use crate::mmu::Mmu;
+    use crate::platform::{NullAudio, NullDisplay};
+
+    type TestGb = GameBoy<MbcCart<Vec<u8>, Vec<u8>>, NullDisplay, NullAudio, NullInput>;
+
+    /// 0x0100 から: SP 設定 → バンク 2 を選択 → CALL 0x4000（INC A ×2, RET）→
+    /// (0xC000) へ書いて読み戻し → 無限ループ
+    fn new_gb() -> TestGb {
+        let mut rom = banked_rom(4, 0x01);
+        let main = [
+            0x31, 0xFE, 0xDF, // 0100 LD SP,0xDFFE
+            0x3E, 0x02, // 0103 LD A,2
+            0xEA, 0x00, 0x20, // 0105 LD (0x2000),A
+            0xCD, 0x00, 0x40, // 0108 CALL 0x4000
+            0x21, 0x00, 0xC0, // 010B LD HL,0xC000
+            0x77, // 010E LD (HL),A
+            0x7E, // 010F LD A,(HL)
+            0x18, 0xFE, // 0110 JR 0x0110
+        ];
+        rom[0x100..0x100 + main.len()].copy_from_slice(&main);
+        rom[0x8000..0x8003].copy_from_slice(&[0x3C, 0x3C, 0xC9]);
+        let cart = MbcCart::new(rom, Vec::new(), NoClock).unwrap();
+        let mmu = Mmu::new(Bootrom::disabled(), cart);
+        let mut gb = GameBoy::new(mmu, NullDisplay, NullAudio, NullInput);
+        // 起動直後の CPU は 0x0100 を fetch する前の NOP を 1 つ実行する
+        Debugger::new().run(&mut gb, RunCommand::StepInstruction, 100);
+        assert_eq!(gb.registers().pc, 0x0100);
+        gb
+    }
+
+    /// 0x0100 から CALL 0x0200 → CALL 0x0300 と入れ子に呼ぶ
+    fn nested_gb() -> TestGb {
+        let mut rom = banked_rom(2, 0x00);
+        let code: [(usize, &[u8]); 3] = [
+            (0x0100, &[0x31, 0xFE, 0xDF, 0xCD, 0x00, 0x02, 0x18, 0xFE]), // LD SP / CALL / JR $
+            (0x0200, &[0xCD, 0x00, 0x03, 0x04, 0xC9]), // CALL 0x0300 / INC B / RET
+            (0x0300, &[0x0C, 0xC9]),                   // INC C / RET
+        ];
+        for (addr, bytes) in code {
+            rom[addr..addr + bytes.len()].copy_from_slice(bytes);
+        }
+        let cart = MbcCart::new(rom, Vec::new(), NoClock).unwrap();
+        let mmu = Mmu::new(Bootrom::disabled(), cart);
+        let mut gb = GameBoy::new(mmu, NullDisplay, NullAudio, NullInput);
+        Debugger::new().run(&mut gb, RunCommand::StepInstruction, 100);
+        gb
+    }
+
+    #[test]
+    fn step_instruction_and_step_over() {
+        let mut gb = new_gb();
+        let dbg = Debugger::new();
+        for pc in [0x0103, 0x0105, 0x0108] {
+            assert_eq!(dbg.run(&mut gb, RunCommand::StepInstruction, 100), StopReason::Step);
+            assert_eq!(gb.registers().pc, pc);
+        }
+        assert_eq!(dbg.run(&mut gb, RunCommand::StepOver, 100), StopReason::Step);
+        let regs = gb.registers();
+        assert_eq!((regs.pc, regs.a, regs.sp), (0x010B, 4, 0xDFFE));
+        assert!(!regs.flags().z && !regs.flags().n);
+    }
+
+    #[test]
+    fn bank_qualified_breakpoint_and_step_out() {
+        let mut gb = new_gb();
+        let mut dbg = Debugger::new();
+        dbg.add_breakpoint(Breakpoint { bank: Some(1), ..Breakpoint::new(0x4000) });
+        dbg.add_breakpoint(Breakpoint { bank: Some(2), ..Breakpoint::new(0x4000) });
+        let reason = dbg.run(&mut gb, RunCommand::Continue, 1000);
+        assert_eq!(reason, StopReason::Breakpoint { index: 1 });
+        assert_eq!(gb.registers().pc, 0x4000);
+        assert_eq!(gb.bank_at(0x4000), 2);
+        assert_eq!(dbg.run(&mut gb, RunCommand::StepOut, 1000), StopReason::Step);
+        assert_eq!(gb.registers().pc, 0x010B);
+    }
+
+    #[test]
+    fn watchpoints_and_conditions() {
+        let mut gb = new_gb();
+        let mut dbg = Debugger::new();
+        dbg.add_condition(Condition { reg: Reg::A, op: CmpOp::Eq, value: 3 });
+        let reason = dbg.run(&mut gb, RunCommand::Continue, 1000);
+        assert_eq!(reason, StopReason::Condition { index: 0 });
+        assert_eq!(gb.registers().pc, 0x4001);
+        dbg.remove_condition(0);
+
+        let none = Watchpoint { start: 0, end: 0, read: false, write: false, execute: false };
+        dbg.add_watchpoint(Watchpoint { start: 0xC000, end: 0xC0FF, read: true, write: true, ..none });
+        let hit = WatchHit { index: 0, addr: 0xC000, access: Access::Write, value: 4 };
+        assert_eq!(dbg.run(&mut gb, RunCommand::Continue, 1000), StopReason::Watchpoint(hit));
+        assert_eq!(gb.registers().pc, 0x010F);
+        let hit = WatchHit { access: Access::Read, ..hit };
+        assert_eq!(dbg.run(&mut gb, RunCommand::Continue, 1000), StopReason::Watchpoint(hit));
+
+        dbg.add_watchpoint(Watchpoint { start: 0x0110, end: 0x0110, execute: true, ..none });
+        assert!(matches!(
+            dbg.run(&mut gb, RunCommand::Continue, 1000),
+            StopReason::Watchpoint(WatchHit { index: 1, addr: 0x0110, access: Access::Execute, .. })
+        ));
+        dbg.remove_watchpoint(1);
+        assert_eq!(dbg.run(&mut gb, RunCommand::RunToFrame, 100_000), StopReason::Frame);
+        assert_eq!(dbg.run(&mut gb, RunCommand::StepOut, 10), StopReason::Limit);
+    }
+
+    #[test]
+    fn step_out_skips_nested_calls() {
+        let mut gb = nested_gb();
+        let mut dbg = Debugger::new();
+        dbg.add_breakpoint(Breakpoint::new(0x0200));
+        let reason = dbg.run(&mut gb, RunCommand::Continue, 1000);
+        assert_eq!(reason, StopReason::Breakpoint { index: 0 });
+        // 0x0200 からのステップアウトは内側の RET では止まらない
+        dbg.remove_breakpoint(0);
+        assert_eq!(dbg.run(&mut gb, RunCommand::StepOut, 1000), StopReason::Step);
+        let regs = gb.registers();
+        assert_eq!((regs.pc, regs.b, regs.sp), (0x0106, 1, 0xDFFE));
+
+        let mut gb = nested_gb();
+        dbg.add_breakpoint(Breakpoint::new(0x0300));
+        dbg.run(&mut gb, RunCommand::Continue, 1000);
+        assert_eq!(dbg.run(&mut gb, RunCommand::StepOut, 1000), StopReason::Step);
+        assert_eq!((gb.registers().pc, gb.registers().b), (0x0203, 0));
+    }
+
+    #[test]
+    fn watchpoint_hit_by_call_push() {
+        let mut gb = nested_gb();
+        let mut dbg = Debugger::new();
+        dbg.add_watchpoint(Watchpoint {
+            start: 0xDFFC,
+            end: 0xDFFD,
+            read: false,
+            write: true,
+            execute: false,
+        });
+        // CALL 0x0200 が戻り先 0x0106 を積んだ命令の後で止まる
+        let reason = dbg.run(&mut gb, RunCommand::Continue, 1000);
+        assert!(matches!(
+            reason,
+            StopReason::Watchpoint(WatchHit { index: 0, access: Access::Write, .. })
+        ));
+        assert_eq!(gb.registers().pc, 0x0200);
+        assert_eq!(gb.mmu().read(0xDFFC), 0x06);
+    }
+
+    #[test]
+    fn limit_stops_at_instruction_boundary() {
+        let mut gb = nested_gb();
+        let dbg = Debugger::new();
+        dbg.run(&mut gb, RunCommand::StepInstruction, 100);
+        assert_eq!(gb.registers().pc, 0x0103);
+        // CALL は 6 M-cycle かかるが、1 M-cycle の上限でも命令の途中では止まらない
+        assert_eq!(dbg.run(&mut gb, RunCommand::Continue, 1), StopReason::Limit);
+        assert!(gb.at_instruction_boundary());
+        assert_eq!(gb.registers().pc, 0x0200);
+    }
+}
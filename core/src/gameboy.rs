@@ -1,7 +1,10 @@
 use crate::cpu::Cpu;
+use crate::debugger::{CpuRegisters, WatchBus, WatchHit, Watchpoint};
 use crate::input::InputSource;
 use crate::mmu::Mmu;
-use crate::platform::{AudioSink, CartridgeBus, Display};
+use crate::platform::{AudioSink, CartridgeBus, Display, NullSerial, SerialPort};
+use crate::savestate::{self, Snapshot, StateError, StateReader};
+use core::fmt;
 
 pub const CPU_CLOCK_HZ: u32 = 4_194_304;
 pub const M_CYCLE_CLOCK: u32 = 4;
@@ -15,42 +18,62 @@ pub struct StepResult {
     pub quit: bool,
     /// CGB ダブルスピードモードで動作中（メインループのタイミング調整に使用）
     pub double_speed: bool,
+    /// このサイクルで CPU が命令（割り込みディスパッチを含む）を 1 つ終えた
+    pub instruction_done: bool,
 }
 
-/// CPU・MMU と各プラットフォーム実装（表示・音声・入力・カート）を束ねる。
+/// CPU・MMU と各プラットフォーム実装（表示・音声・入力・カート・シリアル）を束ねる。
 ///
 /// タイミング駆動（M-cycle 周期のスリープ等）は行わず、[`GameBoy::step`] を
 /// 各プラットフォームの main ループが必要なペースで呼ぶ。
-pub struct GameBoy<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource> {
+pub struct GameBoy<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource, S: SerialPort = NullSerial>
+{
     cpu: Cpu,
     mmu: Mmu<C>,
     display: D,
     audio: A,
     input: I,
+    serial: S,
     /// CGB ダブルスピード時に PPU/APU を 1 step おきに進めるための位相フラグ。
     av_phase: bool,
 }
 
 impl<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource> GameBoy<C, D, A, I> {
-    pub fn new(mut mmu: Mmu<C>, display: D, audio: A, input: I) -> Self {
+    /// シリアルポート未接続で生成する。
+    pub fn new(mmu: Mmu<C>, display: D, audio: A, input: I) -> Self {
+        Self::with_serial(mmu, display, audio, input, NullSerial)
+    }
+}
+
+impl<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource, S: SerialPort>
+    GameBoy<C, D, A, I, S>
+{
+    /// シリアルポート（リンクケーブル・周辺機器）を接続して生成する。
+    pub fn with_serial(mut mmu: Mmu<C>, display: D, audio: A, input: I, serial: S) -> Self {
         let mut cpu = Cpu::new();
         // BootROM の有無にかかわらず ROM ヘッダで CGB モードを決定する。
         // DMG BootROM は CGB レジスタを初期化しないため、BootROM あり CGB ROM でも
         // cgb_mode だけは先に確定させる必要がある。
+        // CGB BootROM 実行中は常に CGB モード（DMG カートかどうかは BootROM が KEY0 に書く）
         let cgb_flag = mmu.cart.read(0x0143);
-        let cgb_mode = cgb_flag == 0x80 || cgb_flag == 0xC0;
+        let cgb_cart = cgb_flag == 0x80 || cgb_flag == 0xC0;
+        let cgb_mode = cgb_cart || (mmu.bootrom.is_active() && mmu.bootrom.is_cgb());
         mmu.set_cgb_mode(cgb_mode);
         if !mmu.bootrom.is_active() {
             // BootROM なし: ソフトウェアで起動直後のハードウェア状態を再現する
             if cgb_mode {
                 cpu.apply_cgb_init();
                 mmu.apply_cgb_init();
+            } else if mmu.cgb_hardware() {
+                // CGB 本体で DMG ソフト: CPU は CGB の初期値（A=0x11）で DMG 互換モード
+                cpu.apply_cgb_init();
+                mmu.apply_dmg_compat_init();
             } else {
                 cpu.apply_dmg_init();
                 mmu.apply_dmg_init();
             }
         }
-        Self { cpu, mmu, display, audio, input, av_phase: false }
+        Self { cpu, mmu, display, audio, input, serial, av_phase: false }
     }
 
     /// MMU への不変参照（test-harness の出力監視等に使用）。
@@ -58,11 +81,31 @@ impl<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource> GameBoy<C, D, A,
         &self.mmu
     }
 
+    /// カートリッジへの可変参照（バッテリーバックアップ RAM の保存等に使用）。
+    pub fn cart_mut(&mut self) -> &mut C {
+        &mut self.mmu.cart
+    }
+
     /// display への可変参照（プラットフォーム側の統計表示・計測に使用）。
     pub fn display_mut(&mut self) -> &mut D {
         &mut self.display
     }
 
+    /// input への可変参照（プラットフォーム固有のホットキー取得等に使用）。
+    pub fn input_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// シリアルポートへの参照（test-harness のシリアル出力の読み出し等に使用）。
+    pub fn serial(&self) -> &S {
+        &self.serial
+    }
+
+    /// シリアルポートへの可変参照。
+    pub fn serial_mut(&mut self) -> &mut S {
+        &mut self.serial
+    }
+
     /// デバッグ用: CPU の (PC, HALT 中か, IME)。
     pub fn debug_cpu(&self) -> (u16, bool, bool) {
         self.cpu.debug_state()
@@ -73,24 +116,115 @@ impl<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource> GameBoy<C, D, A,
         self.cpu.debug_regs()
     }
 
+    /// 全レジスタ（PC は次に実行する命令のアドレス）。
+    pub fn registers(&self) -> CpuRegisters {
+        self.cpu.registers()
+    }
+
+    /// `addr` に割り当て中のバンク番号。ROM 領域は ROM バンク、0x8000–0x9FFF は VRAM バンク、
+    /// 0xA000–0xBFFF は外部 RAM バンク、0xD000–0xDFFF は WRAM バンク、
+    /// それ以外はバンク切替がないので 0。
+    pub fn bank_at(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x7FFF => self.mmu.cart.rom_bank_at(addr),
+            0x8000..=0x9FFF => self.mmu.ppu.vram_bank(),
+            0xA000..=0xBFFF => self.mmu.cart.ram_bank(),
+            0xD000..=0xDFFF => self.mmu.wram.bank() as usize,
+            _ => 0,
+        }
+    }
+
+    /// CPU が命令の境界にいるか（デバッガはここでだけ止まる）。
+    pub(crate) fn at_instruction_boundary(&self) -> bool {
+        self.cpu.at_boundary()
+    }
+
+    /// 現在の状態を `buf` へセーブステートとして書き出し、書き込んだバイト数を返す。
+    /// バッファが足りない場合は必要サイズを `BufferTooSmall` で返す。
+    pub fn save_state(&self, buf: &mut [u8]) -> Result<usize, StateError> {
+        savestate::write_with_header(buf, |w| {
+            w.bool(self.av_phase);
+            self.cpu.save(w);
+            self.mmu.save(w);
+        })
+    }
+
+    /// [`GameBoy::save_state`] の出力から状態を復元する。
+    /// ヘッダ・チェックサムは先に検証するため、それらのエラーでは状態を変更しない。
+    /// チェックサムが正しくても別のカート種別・RAM サイズで保存したデータは途中まで読み込んでから
+    /// エラーになり、状態は壊れる（同じ ROM で保存したデータであることは呼び出し側が保証する）。
+    pub fn load_state(&mut self, buf: &[u8]) -> Result<(), StateError> {
+        let payload = savestate::verify_header(buf)?;
+        let mut r = StateReader::new(payload);
+        self.av_phase = r.bool();
+        self.cpu.load(&mut r);
+        self.mmu.load(&mut r);
+        r.finish()
+    }
+
+    /// セーブステートに必要なバッファサイズ（外部 RAM サイズにより変わる）。
+    pub fn state_size(&self) -> usize {
+        match self.save_state(&mut []) {
+            Ok(n) | Err(StateError::BufferTooSmall(n)) => n,
+            Err(_) => 0,
+        }
+    }
+
     /// 1 M-cycle 進める。フレーム完成時に display へ draw し、入力をポーリングする。
     pub fn step(&mut self) -> StepResult {
+        self.step_watched(&[], None).0
+    }
+
+    /// [`GameBoy::step`] と同じだが、命令を decode するたびに gameboy-doctor 形式の
+    /// CPU トレースを `log` へ 1 行書く（[`Cpu::write_trace`]）。
+    pub fn step_traced(&mut self, log: &mut dyn fmt::Write) -> StepResult {
+        self.step_watched(&[], Some(log)).0
+    }
+
+    /// [`GameBoy::step`] と同じだが、CPU のメモリアクセスを `watchpoints` と照合し、
+    /// 最初に一致したアクセスを返す（デバッガ用）。`trace` は [`GameBoy::step_traced`] 用。
+    pub(crate) fn step_watched(
+        &mut self,
+        watchpoints: &[Watchpoint],
+        trace: Option<&mut dyn fmt::Write>,
+    ) -> (StepResult, Option<WatchHit>) {
         let mut result = StepResult::default();
+        let mut hit = None;
 
-        self.cpu.emulate_cycle(&mut self.mmu);
+        // HDMA/GDMA 転送中は CPU が止まる（他のコンポーネントは進む）
+        if !self.mmu.cpu_stalled() {
+            result.instruction_done = if watchpoints.is_empty() {
+                self.cpu.emulate_cycle(&mut self.mmu, trace)
+            } else {
+                let mut bus = WatchBus::new(&mut self.mmu, watchpoints);
+                let done = self.cpu.emulate_cycle(&mut bus, trace);
+                hit = bus.hit();
+                done
+            };
+        }
+        self.mmu.step_oam_dma();
 
         // タイマー割り込み (タイマーは CPU クロック同期なので毎 step 進める。
         // ダブルスピード時は実時間比 2 倍になり実機と一致する)
         if self.mmu.timer.emulate_cycle() {
             self.mmu.if_ |= 0x04;
         }
+        // APU の Frame Sequencer は DIV のビットで進む（ダブルスピードでも実時間 512 Hz）
+        if self.mmu.timer.apu_tick {
+            self.mmu.timer.apu_tick = false;
+            self.mmu.apu.clock_div_apu();
+        }
+
+        // シリアル転送（CPU クロック同期。CGB ダブルスピード時は転送速度も 2 倍）
+        self.mmu.step_serial(&mut self.serial);
+        self.mmu.cart.tick();
 
         // PPU/APU のクロックは実機ではダブルスピード切替の影響を受けない。
         // ダブルスピード時の 1 step は実時間で半 M-cycle 相当なので、1 step おきに進める。
         self.av_phase = !self.av_phase;
         if self.mmu.double_speed() && self.av_phase {
             result.double_speed = true;
-            return result;
+            return (result, hit);
         }
 
         // APU サンプル生成
@@ -108,6 +242,8 @@ impl<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource> GameBoy<C, D, A,
                 result.quit = true;
             }
             self.mmu.update_joypad(&btn);
+            let tilt = self.input.poll_analog();
+            self.mmu.cart.set_tilt(tilt.x, tilt.y);
             result.frame_ready = true;
         }
         if self.mmu.ppu.vblank_irq {
@@ -125,6 +261,6 @@ impl<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource> GameBoy<C, D, A,
         }
 
         result.double_speed = self.mmu.double_speed();
-        result
+        (result, hit)
     }
 }
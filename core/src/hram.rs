@@ -1,3 +1,5 @@
+use crate::savestate::{Snapshot, StateReader, StateWriter};
+
 // 128 bytes of high ram
 pub struct HRam {
     val: [u8; 0x80],
@@ -16,3 +18,13 @@ impl HRam {
         self.val[(addr as usize) & 0x7f] = val;
     }
 }
+
+impl Snapshot for HRam {
+    fn save(&self, w: &mut StateWriter) {
+        w.bytes(&self.val);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        r.bytes(&mut self.val);
+    }
+}
@@ -11,8 +11,21 @@ pub struct ButtonState {
     pub quit: bool,
 }
 
+/// アナログ入力（MBC7 カートの加速度センサ用の傾き）。
+/// 各軸 -1.0〜1.0 で、水平に置いた状態が (0, 0)。x は右、y は手前を下げる向きが正。
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct AnalogState {
+    pub x: f32,
+    pub y: f32,
+}
+
 pub trait InputSource {
     fn poll(&mut self) -> ButtonState;
+
+    /// 傾きを取得する。poll() の直後に 1 フレーム 1 回呼ばれる（既定は常に水平）。
+    fn poll_analog(&mut self) -> AnalogState {
+        AnalogState::default()
+    }
 }
 
 pub struct NullInput;
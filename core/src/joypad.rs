@@ -1,4 +1,5 @@
 use crate::input::ButtonState;
+use crate::savestate::{Snapshot, StateReader, StateWriter};
 
 pub struct Joypad {
     /// アクションボタン: [A, B, Select, Start]
@@ -62,3 +63,19 @@ impl Joypad {
         false
     }
 }
+
+impl Snapshot for Joypad {
+    fn save(&self, w: &mut StateWriter) {
+        for &b in self.action.iter().chain(self.direction.iter()) {
+            w.bool(b);
+        }
+        w.u8(self.select);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        for b in self.action.iter_mut().chain(self.direction.iter_mut()) {
+            *b = r.bool();
+        }
+        self.select = r.u8() & 0x30;
+    }
+}
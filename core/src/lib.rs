@@ -2,7 +2,9 @@
 
 pub mod apu;
 pub mod bootrom;
+pub mod cartridge;
 pub mod cpu;
+pub mod debugger;
 pub mod gameboy;
 pub mod hram;
 pub mod input;
@@ -10,5 +12,7 @@ pub mod joypad;
 pub mod mmu;
 pub mod platform;
 pub mod ppu;
+pub mod savestate;
+pub mod serial;
 pub mod timer;
 pub mod wram;
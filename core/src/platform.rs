@@ -1,6 +1,8 @@
 //! プラットフォーム抽象トレイト群（表示・音声・カートリッジバス）。
 //! 入力は [`crate::input::InputSource`] を使う。
 
+use crate::savestate::{StateReader, StateWriter};
+
 /// 160x144 の RGB555 ピクセルバッファを表示する。
 /// bits 0-4=R, bits 5-9=G, bits 10-14=B（GBC ネイティブ形式）
 pub trait Display {
@@ -20,6 +22,59 @@ pub trait AudioSink {
 pub trait CartridgeBus {
     fn read(&self, addr: u16) -> u8;
     fn write(&mut self, addr: u16, val: u8);
+
+    /// セーブステートへバンク状態・外部 RAM を書き出す（既定は何もしない）。
+    /// 実カートのように内部状態を読み出せない実装はデフォルトのままでよい。
+    fn save_state(&self, _w: &mut StateWriter) {}
+
+    /// [`CartridgeBus::save_state`] で書いた内容を同じ順序で読み戻す。
+    fn load_state(&mut self, _r: &mut StateReader) {}
+
+    /// 傾きセンサを持つカート（MBC7）へ現在の傾きを渡す（既定は何もしない）。
+    /// 値の範囲は [`crate::input::AnalogState`] と同じ。
+    fn set_tilt(&mut self, _x: f32, _y: f32) {}
+
+    /// 毎 M-cycle（CPU クロック）呼ばれる。時間で動くカート（ポケットカメラ）用。
+    fn tick(&mut self) {}
+
+    /// ROM 領域 (0x0000–0x7FFF) の `addr` に割り当て中の ROM バンク番号
+    /// （デバッガのバンク指定ブレークポイント用。既定はバンク切替なし）。
+    fn rom_bank_at(&self, addr: u16) -> usize {
+        if addr < 0x4000 { 0 } else { 1 }
+    }
+
+    /// 外部 RAM 領域 (0xA000–0xBFFF) に割り当て中の RAM バンク番号（既定はバンク切替なし）
+    fn ram_bank(&self) -> usize {
+        0
+    }
+}
+
+/// シリアルポートの相手側（リンクケーブル・プリンタ等の周辺機器）。
+///
+/// 転送タイミングは [`crate::serial::Serial`] が管理し、バイト単位で呼び出す。
+pub trait SerialPort {
+    /// 内部クロック（自分がマスター）で 1 バイトの転送が完了した。
+    /// 送信バイト `out` を相手へ渡し、相手から受け取ったバイトを返す（未接続なら 0xFF）。
+    fn transfer(&mut self, out: u8) -> u8;
+
+    /// 外部クロック（相手がマスター）で転送待ちの間、毎 M-cycle 呼ばれる。
+    /// `out` は自分の SB。相手のクロックで 1 バイト届いたら受信バイトを返す。
+    fn poll_external(&mut self, out: u8) -> Option<u8>;
+
+    /// 毎 M-cycle、転送処理の後に呼ばれる（プロセス間リンクのロックステップ同期などに使う）。
+    fn tick(&mut self) {}
+}
+
+/// ケーブル未接続。内部クロック転送は 0xFF を受信し、外部クロック転送は完了しない。
+pub struct NullSerial;
+
+impl SerialPort for NullSerial {
+    fn transfer(&mut self, _: u8) -> u8 {
+        0xFF
+    }
+    fn poll_external(&mut self, _: u8) -> Option<u8> {
+        None
+    }
 }
 
 /// 表示を破棄する no-op 実装（ヘッドレス/テスト用）。
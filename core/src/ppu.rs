@@ -1,3 +1,22 @@
+pub mod compat;
+mod fifo;
+mod inspect;
+
+use crate::savestate::{Snapshot, StateReader, StateWriter};
+use compat::CompatPalette;
+use fifo::Fifo;
+pub use inspect::{MapTile, OamEntry};
+
+/// 描画方式
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Renderer {
+    /// モード 3 の終わりに 1 ライン分まとめて描く（軽量。Teensy 向け）
+    #[default]
+    Scanline,
+    /// ピクセル FIFO をドット単位で進める（描画中のレジスタ変更・可変長モード 3 を再現）
+    Fifo,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum Mode {
     HBlank,
@@ -90,6 +109,17 @@ pub struct Ppu {
     ocps: u8,
     /// OPRI (0xFF6C): OBJ 優先度モード (0=OAM 順, 1=X 座標順/DMG 互換)
     opri: u8,
+    /// CGB 上の DMG 互換モード（DMG の色番号をパレット RAM の色で表示する）
+    dmg_compat: bool,
+    renderer: Renderer,
+    /// FIFO レンダラの描画中ライン状態
+    fifo: Fifo,
+    /// OAM DMA 転送中（PPU から見た OAM は 0xFF になる）
+    pub oam_dma: bool,
+    /// このフレームで LY == WY になったか（ウィンドウ開始条件、FIFO レンダラで使用）
+    wy_hit: bool,
+    /// CPU から読む LY を 0x90 に固定する（gameboy-doctor のログと比較する条件）
+    ly_stub: bool,
 }
 
 impl Ppu {
@@ -124,9 +154,28 @@ impl Ppu {
             obj_palette_ram: [0xFF; 64],
             ocps: 0,
             opri: 0,
+            dmg_compat: false,
+            renderer: Renderer::Scanline,
+            fifo: Fifo::default(),
+            oam_dma: false,
+            wy_hit: false,
+            ly_stub: false,
         }
     }
 
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
+    }
+
+    pub fn renderer(&self) -> Renderer {
+        self.renderer
+    }
+
+    /// LY の読み出しを 0x90 固定にする（PPU 自体のタイミングは変わらない）
+    pub fn set_ly_stub(&mut self, on: bool) {
+        self.ly_stub = on;
+    }
+
     pub fn read(&self, addr: u16) -> u8 {
         match addr {
             0x8000..=0x9FFF => {
@@ -147,6 +196,7 @@ impl Ppu {
             0xFF41 => 0x80 | self.stat | self.mode as u8,
             0xFF42 => self.scy,
             0xFF43 => self.scx,
+            0xFF44 if self.ly_stub => 0x90,
             0xFF44 => self.ly,
             0xFF45 => self.lyc,
             0xFF47 => self.bgp,
@@ -311,13 +361,17 @@ impl Ppu {
                 let pixel = self.get_pixel_from_tile(tile_idx, pixel_row, pixel_col, 0);
                 self.bg_pixel_buffer[buf_idx] = pixel;
                 let color_idx = (self.bgp >> (pixel << 1)) & 0b11;
-                self.buffer[buf_idx] = DMG_PALETTE[color_idx as usize];
+                self.buffer[buf_idx] = self.dmg_color(None, color_idx);
             }
         }
     }
 
     fn collect_sprites(&mut self) {
         self.sprite_buffer.clear();
+        // DMA 中の OAM は全バイト 0xFF に見える: Y=0xFF のスプライトはどのラインにも掛からない
+        if self.oam_dma {
+            return;
+        }
         let sprite_height: u8 = if self.lcdc & SPRITE_SIZE != 0 { 16 } else { 8 };
         for i in 0..40usize {
             if self.sprite_buffer.len() >= 10 {
@@ -427,9 +481,10 @@ impl Ppu {
                     let hi = self.obj_palette_ram[palette_base + 1];
                     lo as u16 | ((hi as u16) << 8)
                 } else {
-                    let palette = if s.flags & 0x10 != 0 { self.obp1 } else { self.obp0 };
+                    let obj_palette = ((s.flags >> 4) & 0x01) as usize;
+                    let palette = if obj_palette != 0 { self.obp1 } else { self.obp0 };
                     let color_idx = (palette >> (pixel << 1)) & 0b11;
-                    DMG_PALETTE[color_idx as usize]
+                    self.dmg_color(Some(obj_palette), color_idx)
                 };
             }
         }
@@ -494,12 +549,49 @@ impl Ppu {
                 let pixel = self.get_pixel_from_tile(tile_idx, pixel_row, pixel_col, 0);
                 self.bg_pixel_buffer[buf_idx] = pixel;
                 let color_idx = (self.bgp >> (pixel << 1)) & 0b11;
-                self.buffer[buf_idx] = DMG_PALETTE[color_idx as usize];
+                self.buffer[buf_idx] = self.dmg_color(None, color_idx);
             }
         }
         self.window_line_counter += 1;
     }
 
+    /// DMG パレット (BGP/OBP) 適用後の色番号を RGB555 にする。`obj_palette` は OBP0/OBP1 の番号。
+    /// DMG 互換モードでは BG はパレット RAM の BG パレット 0、OBJ は OBJ パレット 0/1 の色になる。
+    fn dmg_color(&self, obj_palette: Option<usize>, color: u8) -> u16 {
+        if !self.dmg_compat {
+            return DMG_PALETTE[color as usize];
+        }
+        let (ram, num) = match obj_palette {
+            Some(n) => (&self.obj_palette_ram, n),
+            None => (&self.bg_palette_ram, 0),
+        };
+        let base = num * 8 + color as usize * 2;
+        ram[base] as u16 | (ram[base + 1] as u16) << 8
+    }
+
+    /// DMG 互換モードに入る（CGB BootROM 終了時に KEY0 で DMG ソフトと判定された場合）。
+    /// OBJ の優先度は DMG と同じ X 座標順になる。
+    pub fn enter_dmg_compat(&mut self) {
+        self.dmg_compat = true;
+        self.opri = 0x01;
+    }
+
+    pub fn dmg_compat(&self) -> bool {
+        self.dmg_compat
+    }
+
+    /// 互換パレットをパレット RAM に書き込む（BootROM なし起動・手動選択用）
+    pub fn set_compat_palette(&mut self, palette: &CompatPalette) {
+        let write = |ram: &mut [u8; 64], num: usize, colors: &[u16; 4]| {
+            for (i, c) in colors.iter().enumerate() {
+                ram[num * 8 + i * 2..][..2].copy_from_slice(&c.to_le_bytes());
+            }
+        };
+        write(&mut self.bg_palette_ram, 0, &palette.bg);
+        write(&mut self.obj_palette_ram, 0, &palette.obj0);
+        write(&mut self.obj_palette_ram, 1, &palette.obj1);
+    }
+
     fn check_lyc_eq_ly(&mut self) {
         if self.ly == self.lyc {
             self.stat |= LYC_EQ_LY;
@@ -513,6 +605,15 @@ impl Ppu {
             return false;
         }
 
+        if self.mode == Mode::Drawing && self.renderer == Renderer::Fifo {
+            if self.fifo_cycle() {
+                // ライン全体 114 M-cycle のうち残りが HBlank
+                self.cycle = 114 - 20 - self.fifo.cycles;
+                self.enter_hblank();
+            }
+            return false;
+        }
+
         self.cycle -= 1;
         if self.cycle > 0 {
             return false;
@@ -533,6 +634,7 @@ impl Ppu {
                     self.mode = Mode::VBlank;
                     self.cycle = 114;
                     self.window_line_counter = 0;
+                    self.wy_hit = false;
                     self.vblank_irq = true;
                     if self.stat & VBLANK_INT != 0 {
                         self.stat_irq = true;
@@ -563,37 +665,154 @@ impl Ppu {
             }
             Mode::OAMScan => {
                 self.collect_sprites();
+                if self.ly == self.wy {
+                    self.wy_hit = true;
+                }
                 self.mode = Mode::Drawing;
                 self.cycle = 43;
+                if self.renderer == Renderer::Fifo {
+                    self.fifo_start_line();
+                }
             }
             Mode::Drawing => {
                 self.render_bg();
                 self.render_window();
                 self.render_sprites();
-                self.mode = Mode::HBlank;
                 self.cycle = 51;
-                self.hblank_trigger = true;
-                if self.stat & HBLANK_INT != 0 {
-                    self.stat_irq = true;
-                }
+                self.enter_hblank();
             }
         }
 
         is_vsync
     }
 
+    fn enter_hblank(&mut self) {
+        self.mode = Mode::HBlank;
+        self.hblank_trigger = true;
+        if self.stat & HBLANK_INT != 0 {
+            self.stat_irq = true;
+        }
+    }
+
+    /// OAM DMA による書き込み（モードによるアクセス制限を受けない）
+    pub fn write_oam_dma(&mut self, index: usize, val: u8) {
+        self.oam[index] = val;
+    }
+
     pub fn pixel_buffer(&self) -> &[u16] {
         &self.buffer
     }
 
+    /// デバッグ用: OAM の内容。
+    pub fn oam(&self) -> &[u8] {
+        &self.oam
+    }
+
     /// デバッグ用: 現在の LCDC 値。
     pub fn lcdc(&self) -> u8 {
         self.lcdc
     }
 
+    /// デバッグ用: CPU から見えている VRAM バンク (VBK)。
+    pub fn vram_bank(&self) -> usize {
+        self.vbk as usize
+    }
+
     /// デバッグ用: BG カラーパレット `idx` (0-7) の色 0 (RGB555 raw)。
     pub fn bg_palette_color0(&self, idx: usize) -> u16 {
         let base = idx * 8;
         self.bg_palette_ram[base] as u16 | ((self.bg_palette_ram[base + 1] as u16) << 8)
     }
 }
+
+/// 表示中のピクセルバッファは次フレームで描き直されるため保存しない。
+/// 描画方式は設定なので含めない（FIFO の途中状態は常に保存する）。
+impl Snapshot for Ppu {
+    fn save(&self, w: &mut StateWriter) {
+        w.u8(self.mode as u8);
+        for v in [
+            self.lcdc, self.stat, self.scy, self.scx, self.ly, self.lyc, self.bgp, self.obp0,
+            self.obp1, self.wy, self.wx, self.vbk,
+        ] {
+            w.u8(v);
+        }
+        for bank in &self.vram {
+            w.bytes(bank);
+        }
+        w.bytes(&self.oam);
+        w.u8(self.sprite_buffer.len() as u8);
+        for s in &self.sprite_buffer {
+            for v in [s.x, s.y, s.tile_num, s.flags, s.order] {
+                w.u8(v);
+            }
+        }
+        w.u8(self.window_line_counter);
+        w.u8(self.cycle);
+        w.bool(self.vblank_irq);
+        w.bool(self.stat_irq);
+        w.bool(self.hblank_trigger);
+        w.bytes(&self.bg_palette_ram);
+        w.u8(self.bcps);
+        w.bytes(&self.obj_palette_ram);
+        w.u8(self.ocps);
+        w.u8(self.opri);
+        w.bool(self.dmg_compat);
+        w.bool(self.wy_hit);
+        self.fifo.save(w);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        self.mode = match r.u8() {
+            0 => Mode::HBlank,
+            1 => Mode::VBlank,
+            2 => Mode::OAMScan,
+            3 => Mode::Drawing,
+            _ => {
+                r.invalid();
+                Mode::OAMScan
+            }
+        };
+        for v in [
+            &mut self.lcdc,
+            &mut self.stat,
+            &mut self.scy,
+            &mut self.scx,
+            &mut self.ly,
+            &mut self.lyc,
+            &mut self.bgp,
+            &mut self.obp0,
+            &mut self.obp1,
+            &mut self.wy,
+            &mut self.wx,
+            &mut self.vbk,
+        ] {
+            *v = r.u8();
+        }
+        for bank in &mut self.vram {
+            r.bytes(bank);
+        }
+        r.bytes(&mut self.oam);
+        self.sprite_buffer.clear();
+        for _ in 0..r.u8() {
+            let mut v = [0u8; 5];
+            r.bytes(&mut v);
+            let sprite = SpriteData { x: v[0], y: v[1], tile_num: v[2], flags: v[3], order: v[4] };
+            if self.sprite_buffer.push(sprite).is_err() {
+                r.invalid();
+            }
+        }
+        self.window_line_counter = r.u8();
+        self.cycle = r.u8();
+        self.vblank_irq = r.bool();
+        self.stat_irq = r.bool();
+        self.hblank_trigger = r.bool();
+        r.bytes(&mut self.bg_palette_ram);
+        self.bcps = r.u8();
+        r.bytes(&mut self.obj_palette_ram);
+        self.ocps = r.u8();
+        self.opri = r.u8();
+        self.dmg_compat = r.bool();
+        self.wy_hit = r.bool();
+        self.fifo.load(r);
+    }
+}
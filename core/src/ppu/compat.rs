@@ -0,0 +1,373 @@
+//! CGB 上で DMG ソフトを動かすとき（DMG 互換モード）の自動パレット。
+//!
+//! CGB BootROM は任天堂ライセンスのソフトならタイトルのチェックサム（衝突するものは
+//! タイトル 4 文字目も）で内蔵テーブルからパレットを選び、それ以外は既定パレットにする。
+//! ロゴ表示中に方向キー（+ A/B）を押していれば 12 種類のパレットから手動で選べる。
+//! BootROM なしで起動するときはここで同じ選択を再現する。
+
+use super::rgb555;
+use crate::input::ButtonState;
+use crate::platform::CartridgeBus;
+
+/// 互換モードのパレット（BG・OBJ0・OBJ1 の 4 色ずつ、RGB555）。
+/// BG はパレット RAM の BG パレット 0、OBJ0/OBJ1 は OBJ パレット 0/1 に入る。
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CompatPalette {
+    pub bg: [u16; 4],
+    pub obj0: [u16; 4],
+    pub obj1: [u16; 4],
+}
+
+const fn colors(c: [u32; 4]) -> [u16; 4] {
+    let mut out = [0; 4];
+    let mut i = 0;
+    while i < 4 {
+        out[i] = rgb555((c[i] >> 16) as u8, (c[i] >> 8) as u8, c[i] as u8);
+        i += 1;
+    }
+    out
+}
+
+const fn palette(bg: [u16; 4], obj0: [u16; 4], obj1: [u16; 4]) -> CompatPalette {
+    CompatPalette { bg, obj0, obj1 }
+}
+
+const BROWN: [u16; 4] = colors([0xFFFFFF, 0xFFAD63, 0x843100, 0x000000]);
+const RED: [u16; 4] = colors([0xFFFFFF, 0xFF8484, 0x943A3A, 0x000000]);
+const GREEN: [u16; 4] = colors([0xFFFFFF, 0x7BFF31, 0x008400, 0x000000]);
+const BLUE: [u16; 4] = colors([0xFFFFFF, 0x63A5FF, 0x0000FF, 0x000000]);
+const DARK_BROWN: [u16; 4] = colors([0xFFE6C5, 0xCE9C84, 0x846B29, 0x5A3108]);
+const DARK_BLUE: [u16; 4] = colors([0xFFFFFF, 0x8C8CDE, 0x52528C, 0x000000]);
+const GRAY: [u16; 4] = colors([0xFFFFFF, 0xA5A5A5, 0x525252, 0x000000]);
+const PASTEL: [u16; 4] = colors([0xFFFFA5, 0xFF9494, 0x9494FF, 0x000000]);
+const ORANGE: [u16; 4] = colors([0xFFFFFF, 0xFFFF00, 0xFF0000, 0x000000]);
+const YELLOW: [u16; 4] = colors([0xFFFFFF, 0xFFFF00, 0x7B4A00, 0x000000]);
+const LIME: [u16; 4] = colors([0xFFFFFF, 0x52FF00, 0xFF4200, 0x000000]);
+const DARK_GREEN: [u16; 4] = colors([0xFFFFFF, 0x7BFF31, 0x0063C5, 0x000000]);
+const INVERTED: [u16; 4] = colors([0x000000, 0x008484, 0xFFDE00, 0xFFFFFF]);
+
+/// 上
+pub const UP: CompatPalette = palette(BROWN, BROWN, BROWN);
+/// 上 + A
+pub const UP_A: CompatPalette = palette(RED, GREEN, BLUE);
+/// 上 + B
+pub const UP_B: CompatPalette = palette(DARK_BROWN, DARK_BROWN, DARK_BROWN);
+/// 左
+pub const LEFT: CompatPalette = palette(BLUE, RED, GREEN);
+/// 左 + A
+pub const LEFT_A: CompatPalette = palette(DARK_BLUE, RED, BROWN);
+/// 左 + B
+pub const LEFT_B: CompatPalette = palette(GRAY, GRAY, GRAY);
+/// 下
+pub const DOWN: CompatPalette = palette(PASTEL, PASTEL, PASTEL);
+/// 下 + A
+pub const DOWN_A: CompatPalette = palette(ORANGE, ORANGE, ORANGE);
+/// 下 + B
+pub const DOWN_B: CompatPalette = palette(YELLOW, BLUE, GREEN);
+/// 右
+pub const RIGHT: CompatPalette = palette(LIME, LIME, LIME);
+/// 右 + A（テーブルに無いソフトの既定パレット）
+pub const RIGHT_A: CompatPalette = palette(DARK_GREEN, RED, RED);
+/// 右 + B
+pub const RIGHT_B: CompatPalette = palette(INVERTED, INVERTED, INVERTED);
+
+/// タイトルテーブルに無いソフトの既定パレット
+pub const DEFAULT: CompatPalette = RIGHT_A;
+
+/// BootROM 内蔵のパレット 30 種 × 4 色（RGB555）。組み合わせはここへの色単位のオフセット
+#[rustfmt::skip]
+const PALETTE_COLORS: [u16; 120] = [
+    0x7FFF, 0x32BF, 0x00D0, 0x0000, // 0
+    0x639F, 0x4279, 0x15B0, 0x04CB, // 1
+    0x7FFF, 0x6E31, 0x454A, 0x0000, // 2
+    0x7FFF, 0x1BEF, 0x0200, 0x0000, // 3
+    0x7FFF, 0x421F, 0x1CF2, 0x0000, // 4
+    0x7FFF, 0x5294, 0x294A, 0x0000, // 5
+    0x7FFF, 0x03FF, 0x012F, 0x0000, // 6
+    0x7FFF, 0x03EF, 0x01D6, 0x0000, // 7
+    0x7FFF, 0x42B5, 0x3DC8, 0x0000, // 8
+    0x7E74, 0x03FF, 0x0180, 0x0000, // 9
+    0x67FF, 0x77AC, 0x1A13, 0x2D6B, // 10
+    0x7ED6, 0x4BFF, 0x2175, 0x0000, // 11
+    0x53FF, 0x4A5F, 0x7E52, 0x0000, // 12
+    0x4FFF, 0x7ED2, 0x3A4C, 0x1CE0, // 13
+    0x03ED, 0x7FFF, 0x255F, 0x0000, // 14
+    0x036A, 0x021F, 0x03FF, 0x7FFF, // 15
+    0x7FFF, 0x01DF, 0x0112, 0x0000, // 16
+    0x231F, 0x035F, 0x00F2, 0x0009, // 17
+    0x7FFF, 0x03EA, 0x011F, 0x0000, // 18
+    0x299F, 0x001A, 0x000C, 0x0000, // 19
+    0x7FFF, 0x027F, 0x001F, 0x0000, // 20
+    0x7FFF, 0x03E0, 0x0206, 0x0120, // 21
+    0x7FFF, 0x7EEB, 0x001F, 0x7C00, // 22
+    0x7FFF, 0x3FFF, 0x7E00, 0x001F, // 23
+    0x7FFF, 0x03FF, 0x001F, 0x0000, // 24
+    0x03FF, 0x001F, 0x000C, 0x0000, // 25
+    0x7FFF, 0x033F, 0x0193, 0x0000, // 26
+    0x0000, 0x4200, 0x037F, 0x7FFF, // 27
+    0x7FFF, 0x7E8C, 0x7C00, 0x0000, // 28
+    0x7FFF, 0x1BEF, 0x6180, 0x0000, // 29
+];
+
+/// パレット番号 → 色オフセット
+const fn pal(n: u8) -> u8 {
+    n * 4
+}
+
+/// パレットの組み合わせ [OBJ0, OBJ1, BG]（色オフセット）。BootROM はパレットを詰めて
+/// 格納しているため、パレットの境界をまたぐ組み合わせ（22・34・35）がある
+#[rustfmt::skip]
+const COMBINATIONS: [[u8; 3]; 51] = [
+    [pal(4), pal(4), pal(29)],   [pal(18), pal(18), pal(18)], [pal(20), pal(20), pal(20)],
+    [pal(24), pal(24), pal(24)], [pal(9), pal(9), pal(9)],    [pal(0), pal(0), pal(0)],
+    [pal(27), pal(27), pal(27)], [pal(5), pal(5), pal(5)],    [pal(12), pal(12), pal(12)],
+    [pal(26), pal(26), pal(26)], [pal(16), pal(8), pal(8)],   [pal(4), pal(28), pal(28)],
+    [pal(4), pal(2), pal(2)],    [pal(3), pal(4), pal(4)],    [pal(4), pal(29), pal(29)],
+    [pal(28), pal(4), pal(28)],  [pal(2), pal(17), pal(2)],   [pal(16), pal(16), pal(8)],
+    [pal(4), pal(4), pal(7)],    [pal(4), pal(4), pal(18)],   [pal(4), pal(4), pal(20)],
+    [pal(19), pal(19), pal(9)],  [pal(4) - 1, pal(4) - 1, pal(11)],
+    [pal(17), pal(17), pal(2)],  [pal(4), pal(4), pal(2)],    [pal(4), pal(4), pal(3)],
+    [pal(28), pal(28), pal(0)],  [pal(3), pal(3), pal(0)],    [pal(0), pal(0), pal(1)],
+    [pal(18), pal(22), pal(18)], [pal(20), pal(22), pal(20)], [pal(24), pal(22), pal(24)],
+    [pal(16), pal(22), pal(8)],  [pal(17), pal(4), pal(13)],  [pal(28) - 1, pal(0), pal(14)],
+    [pal(28) - 1, pal(4), pal(15)], [pal(19), pal(22), pal(9)], [pal(16), pal(28), pal(10)],
+    [pal(4), pal(23), pal(28)],  [pal(17), pal(22), pal(2)],  [pal(4), pal(0), pal(2)],
+    [pal(4), pal(28), pal(3)],   [pal(28), pal(3), pal(0)],   [pal(3), pal(28), pal(4)],
+    [pal(21), pal(28), pal(4)],  [pal(3), pal(28), pal(0)],   [pal(25), pal(3), pal(28)],
+    [pal(0), pal(28), pal(8)],   [pal(4), pal(3), pal(28)],   [pal(28), pal(3), pal(6)],
+    [pal(4), pal(28), pal(29)],
+];
+
+/// タイトルチェックサム（0x0134–0x0143 の和）と組み合わせ番号。
+/// [`FIRST_DUPLICATE`] 以降はチェックサムが衝突するため [`FOURTH_LETTERS`] でも区別する
+#[rustfmt::skip]
+const TITLE_CHECKSUMS: [(u8, u8); 94] = [
+    (0x00, 0),  // 既定
+    (0x88, 4),  // ALLEY WAY
+    (0x16, 5),  // YAKUMAN
+    (0x36, 35), // BASEBALL, (Game and Watch 2)
+    (0xD1, 34), // TENNIS
+    (0xDB, 3),  // TETRIS
+    (0xF2, 31), // QIX
+    (0x3C, 15), // DR.MARIO
+    (0x8C, 10), // RADARMISSION
+    (0x92, 5),  // F1RACE
+    (0x3D, 19), // YOSSY NO TAMAGO
+    (0x5C, 36),
+    (0x58, 7),  // X
+    (0xC9, 37), // MARIOLAND2
+    (0x3E, 30), // YOSSY NO COOKIE
+    (0x70, 44), // ZELDA
+    (0x1D, 21),
+    (0x59, 32),
+    (0x69, 31), // TETRIS FLASH
+    (0x19, 20), // DONKEY KONG
+    (0x35, 5),  // MARIO'S PICROSS
+    (0xA8, 33),
+    (0x14, 13), // POKEMON RED, (GAMEBOYCAMERA G)
+    (0xAA, 14), // POKEMON GREEN
+    (0x75, 5),  // PICROSS 2
+    (0x95, 29), // YOSSY NO PANEPON
+    (0x99, 5),  // KIRAKIRA KIDS
+    (0x34, 18), // GAMEBOY GALLERY
+    (0x6F, 9),  // POCKETCAMERA
+    (0x15, 3),
+    (0xFF, 2),  // BALLOON KID
+    (0x97, 26), // KINGOFTHEZOO
+    (0x4B, 25), // DMG FOOTBALL
+    (0x90, 25), // WORLD CUP
+    (0x17, 41), // OTHELLO
+    (0x10, 42), // SUPER RC PRO-AM
+    (0x39, 26), // DYNABLASTER
+    (0xF7, 45), // BOY AND BLOB GB2
+    (0xF6, 42), // MEGAMAN
+    (0xA2, 45), // STAR WARS-NOA
+    (0x49, 36),
+    (0x4E, 38), // WAVERACE
+    (0x43, 26),
+    (0x68, 42), // LOLO2
+    (0xE0, 30), // YOSHI'S COOKIE
+    (0x8B, 41), // MYSTIC QUEST
+    (0xF0, 34),
+    (0xCE, 34), // TOPRANKINGTENNIS
+    (0x0C, 5),  // MANSELL
+    (0x29, 42), // MEGAMAN3
+    (0xE8, 6),  // SPACE INVADERS
+    (0xB7, 5),  // GAME&WATCH
+    (0x86, 33), // DONKEYKONGLAND95
+    (0x9A, 25), // ASTEROIDS/MISCMD
+    (0x52, 42), // STREET FIGHTER 2
+    (0x01, 42), // DEFENDER/JOUST
+    (0x9D, 40), // KILLERINSTINCT95
+    (0x71, 14), // TETRIS BLAST
+    (0x9C, 16), // PINOCCHIO
+    (0xBD, 25),
+    (0x5D, 42), // BA.TOSHINDEN
+    (0x6D, 42), // NETTOU KOF 95
+    (0x67, 5),
+    (0x3F, 0),  // TETRIS PLUS
+    (0x6B, 39), // DONKEYKONGLAND 3
+    // ここから 4 文字目でも区別する
+    (0xB3, 36),
+    (0x46, 22), // SUPER MARIOLAND
+    (0x28, 25), // GOLF
+    (0xA5, 6),  // SOLARSTRIKER
+    (0xC6, 32), // GBWARS
+    (0xD3, 12), // KAERUNOTAMENI
+    (0x27, 36),
+    (0x61, 11), // POKEMON BLUE
+    (0x18, 39), // DONKEYKONGLAND
+    (0x66, 18), // GAMEBOY GALLERY2
+    (0x6A, 39), // DONKEYKONGLAND 2
+    (0xBF, 24), // KID ICARUS
+    (0x0D, 31), // TETRIS2
+    (0xF4, 50),
+    (0xB3, 17), // MOGURANYA
+    (0x46, 46),
+    (0x28, 6),  // GALAGA&GALAXIAN
+    (0xA5, 27), // BT2RAGNAROKWORLD
+    (0xC6, 0),  // KEN GRIFFEY JR
+    (0xD3, 47),
+    (0x27, 41), // MAGNETIC SOCCER
+    (0x61, 41), // VEGAS STAKES
+    (0x18, 0),
+    (0x66, 0),  // MILLI/CENTI/PEDE
+    (0x6A, 19), // MARIO & YOSHI
+    (0xBF, 34), // SOCCER
+    (0x0D, 23), // POKEBOM
+    (0xF4, 18), // G&W GALLERY
+    (0xB3, 29), // TETRIS ATTACK
+];
+
+/// 4 文字目で区別するエントリの先頭
+const FIRST_DUPLICATE: usize = 65;
+/// `TITLE_CHECKSUMS[FIRST_DUPLICATE..]` に対応するタイトル 4 文字目
+const FOURTH_LETTERS: &[u8; TITLE_CHECKSUMS.len() - FIRST_DUPLICATE] =
+    b"BEFAARBEKEK R-URAR INAILICE R";
+
+/// 組み合わせ番号 `n` のパレット
+fn combination(n: u8) -> CompatPalette {
+    let colors = |offset: u8| -> [u16; 4] {
+        core::array::from_fn(|i| PALETTE_COLORS[offset as usize + i])
+    };
+    let [obj0, obj1, bg] = COMBINATIONS[n as usize];
+    CompatPalette { bg: colors(bg), obj0: colors(obj0), obj1: colors(obj1) }
+}
+
+/// ヘッダ（ライセンシーとタイトル）からパレットを選ぶ。
+pub fn for_cartridge(cart: &impl CartridgeBus) -> CompatPalette {
+    let licensee = cart.read(0x014B);
+    let nintendo =
+        licensee == 0x01 || (licensee == 0x33 && cart.read(0x0144) == b'0' && cart.read(0x0145) == b'1');
+    if !nintendo {
+        return DEFAULT;
+    }
+    let checksum = (0x0134..=0x0143).fold(0u8, |sum, addr| sum.wrapping_add(cart.read(addr)));
+    let fourth = cart.read(0x0137);
+    TITLE_CHECKSUMS
+        .iter()
+        .enumerate()
+        .find(|&(i, &(sum, _))| {
+            sum == checksum
+                && (i < FIRST_DUPLICATE || FOURTH_LETTERS[i - FIRST_DUPLICATE] == fourth)
+        })
+        .map_or(DEFAULT, |(_, &(_, n))| combination(n))
+}
+
+/// 起動時のボタン（方向キー + A/B）による手動選択。方向キーが押されていなければ `None`。
+pub fn for_buttons(btn: &ButtonState) -> Option<CompatPalette> {
+    let [plain, with_a, with_b] = if btn.up {
+        [UP, UP_A, UP_B]
+    } else if btn.down {
+        [DOWN, DOWN_A, DOWN_B]
+    } else if btn.left {
+        [LEFT, LEFT_A, LEFT_B]
+    } else if btn.right {
+        [RIGHT, RIGHT_A, RIGHT_B]
+    } else {
+        return None;
+    };
+    Some(if btn.a {
+        with_a
+    } else if btn.b {
+        with_b
+    } else {
+        plain
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct HeaderCart([u8; 0x150]);
+
+    impl HeaderCart {
+        fn new(name: &[u8], licensee: u8) -> Self {
+            let mut rom = [0; 0x150];
+            rom[0x0134..0x0134 + name.len()].copy_from_slice(name);
+            rom[0x014B] = licensee;
+            Self(rom)
+        }
+    }
+
+    impl CartridgeBus for HeaderCart {
+        fn read(&self, addr: u16) -> u8 {
+            self.0.get(addr as usize).copied().unwrap_or(0xFF)
+        }
+        fn write(&mut self, _: u16, _: u8) {}
+    }
+
+    #[test]
+    fn title_checksum_lookup_requires_nintendo_licensee() {
+        let red = palette(RED, GREEN, RED);
+        let blue = palette(BLUE, RED, BLUE);
+        assert_eq!(for_cartridge(&HeaderCart::new(b"POKEMON RED", 0x01)), red);
+        assert_eq!(for_cartridge(&HeaderCart::new(b"POKEMON BLUE", 0x01)), blue);
+        // 新ライセンシーコード "01" も任天堂
+        let mut cart = HeaderCart::new(b"POKEMON BLUE", 0x33);
+        cart.0[0x0144..0x0146].copy_from_slice(b"01");
+        assert_eq!(for_cartridge(&cart), blue);
+        // 任天堂以外・テーブルに無いタイトルは既定パレット
+        assert_eq!(for_cartridge(&HeaderCart::new(b"POKEMON RED", 0x08)), DEFAULT);
+        assert_eq!(for_cartridge(&HeaderCart::new(b"HELLO WORLD", 0x01)), DEFAULT);
+    }
+
+    #[test]
+    fn fourth_letter_only_for_colliding_checksums() {
+        let lookup = |name: &[u8]| for_cartridge(&HeaderCart::new(name, 0x01));
+        // 衝突しないチェックサムは 4 文字目を見ない
+        assert_eq!(lookup(b"POKMEON RED"), lookup(b"POKEMON RED"));
+        // 0xC6: GBWARS と KEN GRIFFEY JR は 4 文字目で分かれる
+        assert_eq!(lookup(b"GBWARS"), combination(32));
+        assert_eq!(lookup(b"KEN GRIFFEY JR"), DEFAULT);
+        // 衝突するチェックサムで 4 文字目がどれとも合わなければ既定パレット
+        assert_eq!(lookup(b"GBWBQS"), DEFAULT);
+    }
+
+    #[test]
+    fn combinations_include_manual_palettes() {
+        assert_eq!(combination(0), DEFAULT);
+        assert_eq!(combination(5), UP);
+        assert_eq!(combination(43), UP_A);
+        assert_eq!(combination(48), LEFT);
+        assert_eq!(combination(40), LEFT_A);
+        assert_eq!(combination(49), DOWN_B);
+        assert_eq!(combination(1), RIGHT);
+    }
+
+    #[test]
+    fn button_combos() {
+        let btn = |f: fn(&mut ButtonState)| {
+            let mut b = ButtonState::default();
+            f(&mut b);
+            for_buttons(&b)
+        };
+        assert_eq!(btn(|_| {}), None);
+        assert_eq!(btn(|b| b.a = true), None);
+        assert_eq!(btn(|b| b.up = true), Some(UP));
+        assert_eq!(btn(|b| (b.left, b.b) = (true, true)), Some(LEFT_B));
+        assert_eq!(btn(|b| (b.right, b.a) = (true, true)), Some(RIGHT_A));
+        assert_eq!(btn(|b| (b.down, b.a) = (true, true)), Some(DOWN_A));
+    }
+}
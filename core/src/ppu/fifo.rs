@@ -0,0 +1,448 @@
+//! ピクセル FIFO 方式のレンダラ（ドット単位）。
+//!
+//! BG/ウィンドウのフェッチャーと BG・OBJ の 2 本の FIFO を 1 ドットずつ進め、
+//! 1 ピクセルずつ LCD に出力する。描画中の SCX・パレット・LCDC の書き換えが
+//! 以降のピクセルに反映され、モード 3 の長さは SCX の端数・ウィンドウ開始・
+//! スプライトのフェッチで伸びる。
+//!
+//! タイミングのモデル:
+//! - ライン開始時の空フェッチ 6 ドットの後、BG フェッチャーは
+//!   タイル番号 / 下位 / 上位をそれぞれ 2 ドットで取得し、FIFO が空なら次のドットで積む
+//! - SCX & 7 ピクセルはライン先頭で捨てる
+//! - ウィンドウ開始時は BG FIFO を破棄してフェッチャーを最初からやり直す (6 ドット)
+//! - スプライトは BG フェッチャーが取得途中のタイルを取り終えるのを待ってから 6 ドットでフェッチする
+
+use super::*;
+
+/// ライン開始時の空フェッチ（ドット）
+const STARTUP_DOTS: u8 = 6;
+/// スプライト 1 個のフェッチ（ドット）
+const SPRITE_FETCH_DOTS: u8 = 6;
+/// フェッチャーのステップ: 0-1 タイル番号, 2-3 下位, 4-5 上位, 6 以降は積める状態
+const STEP_READY: u8 = 6;
+
+#[derive(Clone, Copy, Default)]
+struct BgPixel {
+    color: u8,
+    /// CGB パレット番号
+    palette: u8,
+    /// CGB BG 属性 bit7
+    priority: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+struct ObjPixel {
+    color: u8,
+    /// DMG: OBP0/OBP1, CGB: パレット番号
+    palette: u8,
+    /// OAM 属性 bit7（BG 優先）
+    priority: bool,
+    /// OAM インデックス（CGB の OAM 順優先度）
+    order: u8,
+}
+
+/// 1 ライン分の FIFO 描画状態
+#[derive(Default)]
+pub(super) struct Fifo {
+    bg: [BgPixel; 8],
+    /// BG FIFO の残りピクセル数（`bg[8 - bg_len..]` が有効）
+    bg_len: u8,
+    /// OBJ FIFO（先頭が次に出力するピクセル）
+    obj: [ObjPixel; 8],
+    /// 出力済みピクセル数
+    lx: u8,
+    /// ライン先頭で捨てる残りピクセル数
+    discard: u8,
+    /// 空フェッチの残りドット
+    startup: u8,
+    step: u8,
+    /// フェッチ中のタイル列（ライン先頭 / ウィンドウ開始からの数）
+    fetch_x: u8,
+    tile: u8,
+    attrs: u8,
+    lo: u8,
+    hi: u8,
+    /// ウィンドウを描画中
+    window: bool,
+    /// フェッチ中のスプライト（sprite_buffer のインデックス）と残りドット
+    sprite: u8,
+    sprite_dots: u8,
+    /// フェッチ済みスプライトのビットマスク
+    fetched: u16,
+    /// このラインのモード 3 で経過した M-cycle
+    pub(super) cycles: u8,
+}
+
+impl Fifo {
+    fn pop_bg(&mut self) -> BgPixel {
+        let px = self.bg[8 - self.bg_len as usize];
+        self.bg_len -= 1;
+        px
+    }
+
+    fn pop_obj(&mut self) -> ObjPixel {
+        let px = self.obj[0];
+        self.obj.copy_within(1.., 0);
+        self.obj[7] = ObjPixel::default();
+        px
+    }
+}
+
+impl Ppu {
+    /// モード 3 の開始（OAMScan の終了時）
+    pub(super) fn fifo_start_line(&mut self) {
+        self.fifo = Fifo {
+            discard: self.scx & 7,
+            startup: STARTUP_DOTS,
+            ..Fifo::default()
+        };
+    }
+
+    /// モード 3 を 1 M-cycle (4 ドット) 進める。160 ピクセル出力し終えたら true。
+    pub(super) fn fifo_cycle(&mut self) -> bool {
+        self.fifo.cycles += 1;
+        for _ in 0..4 {
+            self.fifo_dot();
+            if self.fifo.lx as usize == LCD_WIDTH {
+                if self.fifo.window {
+                    self.window_line_counter += 1;
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn fifo_dot(&mut self) {
+        if self.fifo.startup > 0 {
+            self.fifo.startup -= 1;
+            return;
+        }
+        if self.fifo.sprite_dots > 0 {
+            self.fifo.sprite_dots -= 1;
+            if self.fifo.sprite_dots == 0 {
+                self.fetch_sprite(self.fifo.sprite as usize);
+            }
+            return;
+        }
+        if let Some(i) = self.pending_sprite() {
+            // BG フェッチャーが取得途中のタイルを取り終えるまで出力を止めて待つ
+            if self.fifo.bg_len > 0 && self.fifo.step >= 5 {
+                self.fifo.sprite = i as u8;
+                self.fifo.sprite_dots = SPRITE_FETCH_DOTS - 1;
+            } else {
+                self.bg_fetch_step();
+            }
+            return;
+        }
+        if self.window_triggered() {
+            self.fifo.window = true;
+            self.fifo.bg_len = 0;
+            self.fifo.step = 0;
+            self.fifo.fetch_x = 0;
+            // WX < 7 ではウィンドウ左端が画面外にはみ出す
+            self.fifo.discard = 7u8.saturating_sub(self.wx);
+        }
+        self.bg_fetch_step();
+        if self.fifo.bg_len == 0 {
+            return;
+        }
+        let bg = self.fifo.pop_bg();
+        if self.fifo.discard > 0 {
+            self.fifo.discard -= 1;
+            return;
+        }
+        let obj = self.fifo.pop_obj();
+        let buf_idx = LCD_WIDTH * self.ly as usize + self.fifo.lx as usize;
+        self.buffer[buf_idx] = self.mix_pixel(bg, obj);
+        self.fifo.lx += 1;
+    }
+
+    /// 現在位置でフェッチすべきスプライト
+    fn pending_sprite(&self) -> Option<usize> {
+        if self.lcdc & SPRITE_ENABLE == 0 {
+            return None;
+        }
+        let x = self.fifo.lx as u16 + 8;
+        self.sprite_buffer
+            .iter()
+            .enumerate()
+            .find(|(i, s)| self.fifo.fetched & (1 << i) == 0 && s.x as u16 <= x)
+            .map(|(i, _)| i)
+    }
+
+    fn window_triggered(&self) -> bool {
+        !self.fifo.window
+            && self.fifo.discard == 0
+            && self.lcdc & WINDOW_ENABLE != 0
+            && self.wy_hit
+            && self.fifo.lx as u16 + 7 == self.wx.max(7) as u16
+    }
+
+    /// BG / ウィンドウのフェッチャーを 1 ドット進める
+    fn bg_fetch_step(&mut self) {
+        match self.fifo.step {
+            1 => {
+                let addr = self.fetch_map_addr();
+                self.fifo.tile = self.vram[0][addr];
+                self.fifo.attrs = if self.cgb_mode { self.vram[1][addr] } else { 0 };
+            }
+            3 => self.fifo.lo = self.fetch_tile_byte(0),
+            5 => self.fifo.hi = self.fetch_tile_byte(1),
+            STEP_READY => {
+                if self.fifo.bg_len > 0 {
+                    return;
+                }
+                self.push_bg();
+                // 積んだドットで次のタイルのフェッチを始める
+                self.fifo.step = 0;
+            }
+            _ => {}
+        }
+        self.fifo.step += 1;
+    }
+
+    fn fetch_map_addr(&self) -> usize {
+        if self.fifo.window {
+            let base = if self.lcdc & WINDOW_TILE_MAP != 0 { 0x1C00 } else { 0x1800 };
+            let row = (self.window_line_counter / 8) as usize;
+            base + (row << 5) + (self.fifo.fetch_x & 31) as usize
+        } else {
+            let base = if self.lcdc & BG_TILE_MAP != 0 { 0x1C00 } else { 0x1800 };
+            let row = (self.ly.wrapping_add(self.scy) / 8) as usize;
+            let col = ((self.scx / 8).wrapping_add(self.fifo.fetch_x) & 31) as usize;
+            base + (row << 5) + col
+        }
+    }
+
+    /// フェッチ中のタイルの現在行の下位 (`half`=0) / 上位 (`half`=1) バイト
+    fn fetch_tile_byte(&self, half: usize) -> u8 {
+        let line = if self.fifo.window {
+            self.window_line_counter
+        } else {
+            self.ly.wrapping_add(self.scy)
+        };
+        let row = if self.fifo.attrs & 0x40 != 0 { 7 - (line & 7) } else { line & 7 };
+        let tile = self.fifo.tile;
+        let tile_idx = if self.lcdc & TILE_DATA_ADDRESSING_MODE != 0 {
+            tile as usize
+        } else {
+            (0x100i16 + (tile as i8) as i16) as usize
+        };
+        let bank = ((self.fifo.attrs >> 3) & 0x01) as usize;
+        self.vram[bank][((tile_idx << 4) | (row as usize * 2 + half)) & 0x1FFF]
+    }
+
+    fn push_bg(&mut self) {
+        let attrs = self.fifo.attrs;
+        let h_flip = attrs & 0x20 != 0;
+        for (x, px) in self.fifo.bg.iter_mut().enumerate() {
+            let bit = if h_flip { x } else { 7 - x };
+            *px = BgPixel {
+                color: ((self.fifo.hi >> bit) & 1) << 1 | ((self.fifo.lo >> bit) & 1),
+                palette: attrs & 0x07,
+                priority: attrs & 0x80 != 0,
+            };
+        }
+        self.fifo.bg_len = 8;
+        self.fifo.fetch_x = self.fifo.fetch_x.wrapping_add(1);
+    }
+
+    /// スプライトの現在行を取得して OBJ FIFO に重ねる
+    fn fetch_sprite(&mut self, i: usize) {
+        self.fifo.fetched |= 1 << i;
+        let s = &self.sprite_buffer[i];
+        let height: u8 = if self.lcdc & SPRITE_SIZE != 0 { 16 } else { 8 };
+        let row = self.ly.wrapping_sub(s.y.wrapping_sub(16));
+        let row = if s.flags & 0x40 != 0 { height.wrapping_sub(1).wrapping_sub(row) } else { row };
+        let tile = if height == 16 {
+            if row & 0x08 == 0 { s.tile_num & 0xFE } else { s.tile_num | 0x01 }
+        } else {
+            s.tile_num
+        };
+        let bank = if self.cgb_mode { ((s.flags >> 3) & 0x01) as usize } else { 0 };
+        let addr = ((tile as usize) << 4) | ((row & 7) as usize * 2);
+        let lo = self.vram[bank][addr & 0x1FFF];
+        let hi = self.vram[bank][(addr + 1) & 0x1FFF];
+        let palette = if self.cgb_mode { s.flags & 0x07 } else { (s.flags >> 4) & 0x01 };
+        let oam_order = self.cgb_mode && self.opri == 0;
+        // スプライト左端と次の出力位置のずれ（X < 8 のスプライトは左側が画面外）
+        let offset = s.x as i16 - 8 - self.fifo.lx as i16;
+        for col in 0..8i16 {
+            let slot = col + offset;
+            if !(0..8).contains(&slot) {
+                continue;
+            }
+            let bit = if s.flags & 0x20 != 0 { col } else { 7 - col };
+            let color = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+            let dst = &mut self.fifo.obj[slot as usize];
+            // 先にフェッチした（X が小さい）スプライトが優先。CGB の OAM 順では番号の小さい方
+            if color != 0 && (dst.color == 0 || (oam_order && s.order < dst.order)) {
+                *dst = ObjPixel { color, palette, priority: s.flags & 0x80 != 0, order: s.order };
+            }
+        }
+    }
+
+    /// BG と OBJ のピクセルを合成して RGB555 を返す
+    fn mix_pixel(&self, bg: BgPixel, obj: ObjPixel) -> u16 {
+        let bg_enable = self.lcdc & BG_WINDOW_ENABLE != 0;
+        let obj_visible = obj.color != 0 && self.lcdc & SPRITE_ENABLE != 0;
+        if self.cgb_mode {
+            // LCDC bit0=0 は BG master priority 無効: OBJ が常に前面
+            let hidden = bg_enable && (obj.priority || bg.priority) && bg.color != 0;
+            if obj_visible && !hidden {
+                let base = obj.palette as usize * 8 + obj.color as usize * 2;
+                self.obj_palette_ram[base] as u16 | (self.obj_palette_ram[base + 1] as u16) << 8
+            } else {
+                let base = bg.palette as usize * 8 + bg.color as usize * 2;
+                self.bg_palette_ram[base] as u16 | (self.bg_palette_ram[base + 1] as u16) << 8
+            }
+        } else {
+            // DMG: LCDC bit0=0 で BG/ウィンドウは白
+            let bg_color = if bg_enable { bg.color } else { 0 };
+            if obj_visible && !(obj.priority && bg_color != 0) {
+                let palette = if obj.palette != 0 { self.obp1 } else { self.obp0 };
+                self.dmg_color(Some(obj.palette as usize), (palette >> (obj.color << 1)) & 0b11)
+            } else if bg_enable {
+                self.dmg_color(None, (self.bgp >> (bg_color << 1)) & 0b11)
+            } else {
+                self.dmg_color(None, 0)
+            }
+        }
+    }
+}
+
+impl Snapshot for Fifo {
+    fn save(&self, w: &mut StateWriter) {
+        for px in &self.bg {
+            w.u8(px.color);
+            w.u8(px.palette);
+            w.bool(px.priority);
+        }
+        w.u8(self.bg_len);
+        for px in &self.obj {
+            w.u8(px.color);
+            w.u8(px.palette);
+            w.bool(px.priority);
+            w.u8(px.order);
+        }
+        for v in [
+            self.lx, self.discard, self.startup, self.step, self.fetch_x, self.tile, self.attrs,
+            self.lo, self.hi, self.sprite, self.sprite_dots, self.cycles,
+        ] {
+            w.u8(v);
+        }
+        w.bool(self.window);
+        w.u16(self.fetched);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        for px in &mut self.bg {
+            *px = BgPixel { color: r.u8(), palette: r.u8(), priority: r.bool() };
+        }
+        self.bg_len = r.u8();
+        for px in &mut self.obj {
+            *px = ObjPixel { color: r.u8(), palette: r.u8(), priority: r.bool(), order: r.u8() };
+        }
+        for v in [
+            &mut self.lx,
+            &mut self.discard,
+            &mut self.startup,
+            &mut self.step,
+            &mut self.fetch_x,
+            &mut self.tile,
+            &mut self.attrs,
+            &mut self.lo,
+            &mut self.hi,
+            &mut self.sprite,
+            &mut self.sprite_dots,
+            &mut self.cycles,
+        ] {
+            *v = r.u8();
+        }
+        self.window = r.bool();
+        self.fetched = r.u16();
+        if self.bg_len > 8 || self.lx as usize > LCD_WIDTH {
+            r.invalid();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BG 有効・タイルデータ 0x8000 方式で LCD を点け、FIFO レンダラにしたもの
+    fn fifo_ppu() -> Ppu {
+        let mut ppu = Ppu::new();
+        ppu.set_renderer(Renderer::Fifo);
+        ppu.write(0xFF47, 0xE4);
+        ppu.write(0xFF40, PPU_ENABLE | TILE_DATA_ADDRESSING_MODE | BG_WINDOW_ENABLE);
+        ppu
+    }
+
+    /// ライン 0 のモード 3 の長さ（M-cycle）を測る
+    fn mode3_cycles(ppu: &mut Ppu) -> u32 {
+        while ppu.mode != Mode::Drawing {
+            ppu.emulate_cycle();
+        }
+        let mut n = 0;
+        while ppu.mode == Mode::Drawing {
+            ppu.emulate_cycle();
+            n += 1;
+        }
+        n
+    }
+
+    #[test]
+    fn mode3_length_varies_with_scx_window_and_sprites() {
+        // 最短 172 ドット = 43 M-cycle（スキャンライン方式と同じ）
+        assert_eq!(mode3_cycles(&mut fifo_ppu()), 43);
+
+        // SCX の端数 7 ピクセル分伸びる: 179 ドット
+        let mut ppu = fifo_ppu();
+        ppu.write(0xFF43, 7);
+        assert_eq!(mode3_cycles(&mut ppu), 45);
+        assert_eq!(ppu.fifo.cycles, 45);
+
+        // ウィンドウ開始で 6 ドット: 178 ドット
+        let mut ppu = fifo_ppu();
+        ppu.write(0xFF4A, 0);
+        ppu.write(0xFF4B, 87);
+        ppu.write(0xFF40, ppu.lcdc | WINDOW_ENABLE);
+        assert_eq!(mode3_cycles(&mut ppu), 45);
+        assert_eq!(ppu.window_line_counter, 1);
+
+        // X=8 のスプライトは 11 ドット、X=13 は 6 ドット: 172+17 = 189 ドット
+        let mut ppu = fifo_ppu();
+        ppu.write(0xFF40, ppu.lcdc | SPRITE_ENABLE);
+        for (i, x) in [8u8, 13].into_iter().enumerate() {
+            ppu.oam[i * 4] = 16;
+            ppu.oam[i * 4 + 1] = x;
+        }
+        assert_eq!(mode3_cycles(&mut ppu), 48);
+    }
+
+    #[test]
+    fn mid_scanline_palette_write_is_visible() {
+        let mut ppu = fifo_ppu();
+        // タイル 0 を全ピクセル色 3 に
+        ppu.vram[0][..16].fill(0xFF);
+        while ppu.mode != Mode::Drawing {
+            ppu.emulate_cycle();
+        }
+        // 空フェッチ + 最初のフェッチ後、数 M-cycle 描画したところで BGP を変える
+        for _ in 0..10 {
+            ppu.emulate_cycle();
+        }
+        let written = ppu.fifo.lx as usize;
+        assert!(written > 0 && written < LCD_WIDTH);
+        ppu.write(0xFF47, 0x24); // 色 3 → 白
+        while ppu.mode == Mode::Drawing {
+            ppu.emulate_cycle();
+        }
+        let line = &ppu.pixel_buffer()[..LCD_WIDTH];
+        assert!(line[..written].iter().all(|&c| c == DMG_PALETTE[3]));
+        assert!(line[written..].iter().all(|&c| c == DMG_PALETTE[0]));
+    }
+}
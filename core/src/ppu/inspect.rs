@@ -0,0 +1,223 @@
+//! デバッグ・ビューア用の読み取り専用 API（VRAM・OAM・パレット・スクロール位置）。
+//!
+//! 描画の途中状態には触れないので、いつ呼んでもエミュレーションに影響しない。
+
+use super::{BG_TILE_MAP, Ppu, SPRITE_SIZE, TILE_DATA_ADDRESSING_MODE, WINDOW_TILE_MAP};
+
+/// OAM の 1 エントリ。座標は OAM の値そのもの（画面上の左上は `x - 8`, `y - 16`）
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OamEntry {
+    pub y: u8,
+    pub x: u8,
+    pub tile: u8,
+    pub attrs: u8,
+}
+
+impl OamEntry {
+    /// BG の色 1-3 の後ろに描く
+    pub fn behind_bg(&self) -> bool {
+        self.attrs & 0x80 != 0
+    }
+
+    pub fn y_flip(&self) -> bool {
+        self.attrs & 0x40 != 0
+    }
+
+    pub fn x_flip(&self) -> bool {
+        self.attrs & 0x20 != 0
+    }
+
+    /// DMG の OBP0 / OBP1
+    pub fn dmg_palette(&self) -> usize {
+        (self.attrs >> 4 & 1) as usize
+    }
+
+    /// CGB のタイルデータのバンク
+    pub fn vram_bank(&self) -> usize {
+        (self.attrs >> 3 & 1) as usize
+    }
+
+    /// CGB の OBJ パレット番号
+    pub fn cgb_palette(&self) -> usize {
+        (self.attrs & 0x07) as usize
+    }
+}
+
+/// BG / ウィンドウのタイルマップの 1 マス
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapTile {
+    /// LCDC bit4 のアドレッシングを適用したタイル位置（0-383、VRAM 先頭から 16 バイト単位）
+    pub tile: usize,
+    /// CGB の属性（バンク 1 の同じ位置。DMG では 0）
+    pub attrs: u8,
+}
+
+impl MapTile {
+    /// CGB の BG パレット番号
+    pub fn palette(&self) -> usize {
+        (self.attrs & 0x07) as usize
+    }
+
+    pub fn vram_bank(&self) -> usize {
+        (self.attrs >> 3 & 1) as usize
+    }
+
+    pub fn x_flip(&self) -> bool {
+        self.attrs & 0x20 != 0
+    }
+
+    pub fn y_flip(&self) -> bool {
+        self.attrs & 0x40 != 0
+    }
+
+    /// OBJ より前面に描く
+    pub fn priority(&self) -> bool {
+        self.attrs & 0x80 != 0
+    }
+}
+
+impl Ppu {
+    /// VRAM バンク `bank` (0 / 1) の 0x8000-0x9FFF
+    pub fn vram(&self, bank: usize) -> &[u8; 0x2000] {
+        &self.vram[bank]
+    }
+
+    /// OAM の `index` 番目 (0-39)
+    pub fn oam_entry(&self, index: usize) -> OamEntry {
+        let e = &self.oam[index * 4..index * 4 + 4];
+        OamEntry { y: e[0], x: e[1], tile: e[2], attrs: e[3] }
+    }
+
+    /// OBJ の高さ（LCDC bit2 で 8 / 16）
+    pub fn sprite_height(&self) -> u8 {
+        if self.lcdc & SPRITE_SIZE != 0 { 16 } else { 8 }
+    }
+
+    /// (SCX, SCY)
+    pub fn scroll(&self) -> (u8, u8) {
+        (self.scx, self.scy)
+    }
+
+    /// (WX, WY)
+    pub fn window_pos(&self) -> (u8, u8) {
+        (self.wx, self.wy)
+    }
+
+    /// BG が使うタイルマップ（0: 0x9800, 1: 0x9C00）
+    pub fn bg_map(&self) -> usize {
+        (self.lcdc & BG_TILE_MAP != 0) as usize
+    }
+
+    /// ウィンドウが使うタイルマップ（0: 0x9800, 1: 0x9C00）
+    pub fn window_map(&self) -> usize {
+        (self.lcdc & WINDOW_TILE_MAP != 0) as usize
+    }
+
+    /// タイルマップ `map` の (`col`, `row`) のマス（いずれも 0-31）
+    pub fn map_tile(&self, map: usize, col: usize, row: usize) -> MapTile {
+        let addr = 0x1800 + map * 0x400 + row * 32 + col;
+        let raw = self.vram[0][addr];
+        let tile = if self.lcdc & TILE_DATA_ADDRESSING_MODE != 0 {
+            raw as usize
+        } else {
+            (0x100 + raw as i8 as isize) as usize
+        };
+        let attrs = if self.cgb_mode { self.vram[1][addr] } else { 0 };
+        MapTile { tile, attrs }
+    }
+
+    /// タイル `tile` (0-383) の (`col`, `row`) の色番号 (0-3)
+    pub fn tile_pixel(&self, bank: usize, tile: usize, col: u8, row: u8) -> u8 {
+        self.get_pixel_from_tile(tile, row, col, bank)
+    }
+
+    /// CGB BG パレット RAM（8 パレット × 4 色、RGB555 リトルエンディアン）
+    pub fn bg_palette_ram(&self) -> &[u8; 64] {
+        &self.bg_palette_ram
+    }
+
+    /// CGB OBJ パレット RAM
+    pub fn obj_palette_ram(&self) -> &[u8; 64] {
+        &self.obj_palette_ram
+    }
+
+    /// BG の色番号 0-3 を描画に使う色 (RGB555)。CGB モードはパレット RAM の `palette` 番、
+    /// DMG（互換モードを含む）は BGP を通した色で `palette` は使わない。
+    pub fn bg_colors(&self, palette: usize) -> [u16; 4] {
+        if self.cgb_mode {
+            return core::array::from_fn(|c| ram_color(&self.bg_palette_ram, palette, c));
+        }
+        core::array::from_fn(|c| self.dmg_color(None, self.bgp >> (c * 2) & 0b11))
+    }
+
+    /// OBJ の色番号 0-3 を描画に使う色 (RGB555)。CGB モードはパレット RAM の `palette` 番、
+    /// DMG は OBP0 / OBP1（`palette` の bit0）を通した色。色 0 は透明として描かれない。
+    pub fn obj_colors(&self, palette: usize) -> [u16; 4] {
+        if self.cgb_mode {
+            return core::array::from_fn(|c| ram_color(&self.obj_palette_ram, palette, c));
+        }
+        let n = palette & 1;
+        let obp = if n == 0 { self.obp0 } else { self.obp1 };
+        core::array::from_fn(|c| self.dmg_color(Some(n), obp >> (c * 2) & 0b11))
+    }
+}
+
+fn ram_color(ram: &[u8; 64], palette: usize, color: usize) -> u16 {
+    let base = palette * 8 + color * 2;
+    u16::from_le_bytes([ram[base], ram[base + 1]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BG_WINDOW_ENABLE, DMG_PALETTE, PPU_ENABLE};
+    use super::*;
+
+    #[test]
+    fn reads_maps_oam_and_palettes() {
+        // LCD オフなので VRAM に自由に書ける
+        let mut ppu = Ppu::new();
+        ppu.write(0x9800, 0x80);
+        ppu.write(0x9C21, 0x05);
+        ppu.write(0x8010, 0b1000_0000);
+        ppu.write(0x8011, 0b1000_0001);
+        for (i, v) in [0x20, 0x18, 0x01, 0x30].into_iter().enumerate() {
+            ppu.write_oam_dma(4 + i, v);
+        }
+        ppu.write(0xFF47, 0b11_10_01_00);
+        ppu.write(0xFF49, 0b00_01_10_11);
+        ppu.write(0xFF42, 0x12);
+        ppu.write(0xFF43, 0x34);
+
+        // 0x8800 方式では 0x80 が位置 0x80、0x8000 方式と 0x9C00 のマップ
+        ppu.write(0xFF40, BG_WINDOW_ENABLE);
+        assert_eq!(ppu.map_tile(0, 0, 0).tile, 0x80);
+        assert_eq!(ppu.map_tile(1, 1, 1).tile, 0x105);
+        ppu.write(0xFF40, PPU_ENABLE | TILE_DATA_ADDRESSING_MODE | BG_TILE_MAP | SPRITE_SIZE);
+        assert_eq!(ppu.map_tile(1, 1, 1), MapTile { tile: 5, attrs: 0 });
+        assert_eq!((ppu.bg_map(), ppu.window_map(), ppu.sprite_height()), (1, 0, 16));
+        assert_eq!(ppu.scroll(), (0x34, 0x12));
+
+        assert_eq!((ppu.tile_pixel(0, 1, 0, 0), ppu.tile_pixel(0, 1, 7, 0)), (3, 2));
+        let e = ppu.oam_entry(1);
+        assert_eq!(e, OamEntry { y: 0x20, x: 0x18, tile: 0x01, attrs: 0x30 });
+        assert!(e.x_flip() && !e.y_flip() && !e.behind_bg());
+        assert_eq!(e.dmg_palette(), 1);
+
+        assert_eq!(ppu.bg_colors(0), DMG_PALETTE);
+        let mut reversed = DMG_PALETTE;
+        reversed.reverse();
+        assert_eq!(ppu.obj_colors(1), reversed);
+    }
+
+    #[test]
+    fn cgb_colors_come_from_palette_ram() {
+        let mut ppu = Ppu::new();
+        ppu.cgb_mode = true;
+        ppu.write(0xFF68, 0x80 | 0x08); // BG パレット 1 の色 0 から自動インクリメント
+        for b in [0x1F, 0x00, 0xE0, 0x03] {
+            ppu.write(0xFF69, b);
+        }
+        assert_eq!(ppu.bg_colors(1)[..2], [0x001F, 0x03E0]);
+        assert_eq!(ppu.bg_palette_ram()[8..12], [0x1F, 0x00, 0xE0, 0x03]);
+    }
+}
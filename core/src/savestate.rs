@@ -0,0 +1,271 @@
+//! セーブステート（スナップショット）のシリアライズ。
+//!
+//! no_std でヒープを使わないため、呼び出し側が用意したバイト列へ直接書き出す。
+//! 先頭 16 バイトはヘッダ（マジック・バージョン・ペイロード長・チェックサム）で、
+//! 読み込み時はヘッダとチェックサムを検証してから各コンポーネントへ反映する。
+//! 数値はすべてリトルエンディアン。
+
+/// ファイル先頭のマジック
+pub const MAGIC: [u8; 4] = *b"GBSS";
+/// フォーマットバージョン。フィールド構成を変えたら上げる
+pub const VERSION: u16 = 10;
+/// ヘッダ長 (magic 4 + version 2 + reserved 2 + payload_len 4 + checksum 4)
+pub const HEADER_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    /// 書き出し先バッファが足りない（必要なバイト数）
+    BufferTooSmall(usize),
+    /// マジックが一致しない（セーブステートではない）
+    BadMagic,
+    /// 未対応のバージョン
+    UnsupportedVersion(u16),
+    /// 長さ・チェックサム・値の不一致
+    Corrupt,
+}
+
+/// 各コンポーネントが実装するスナップショット入出力。
+/// `save` と `load` はフィールドを同じ順序で読み書きすること。
+pub trait Snapshot {
+    fn save(&self, w: &mut StateWriter);
+    fn load(&mut self, r: &mut StateReader);
+}
+
+/// バッファへの逐次書き出し。バッファを超えても位置だけは進め、
+/// 必要サイズを [`StateWriter::finish`] で報告する（サイズ見積もりにも使う）。
+pub struct StateWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> StateWriter<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    pub fn bytes(&mut self, data: &[u8]) {
+        let end = self.pos + data.len();
+        if end <= self.buf.len() {
+            self.buf[self.pos..end].copy_from_slice(data);
+        }
+        self.pos = end;
+    }
+
+    pub fn u8(&mut self, v: u8) {
+        self.bytes(&[v]);
+    }
+
+    pub fn bool(&mut self, v: bool) {
+        self.u8(v as u8);
+    }
+
+    pub fn u16(&mut self, v: u16) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, v: u32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    pub fn f32(&mut self, v: f32) {
+        self.u32(v.to_bits());
+    }
+
+    /// 書き込んだ（書き込もうとした）バイト数
+    pub fn len(&self) -> usize {
+        self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+
+    /// 全データが収まっていれば書き込みバイト数を返す
+    pub fn finish(self) -> Result<usize, StateError> {
+        if self.pos <= self.buf.len() {
+            Ok(self.pos)
+        } else {
+            Err(StateError::BufferTooSmall(self.pos))
+        }
+    }
+}
+
+/// バッファからの逐次読み出し。範囲外・不正値はエラーフラグを立てて 0 を返す
+/// （各 `load` 実装で `?` を連鎖させずに済むようにするため）。
+pub struct StateReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    error: bool,
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0, error: false }
+    }
+
+    pub fn bytes(&mut self, out: &mut [u8]) {
+        let end = self.pos + out.len();
+        if end <= self.buf.len() {
+            out.copy_from_slice(&self.buf[self.pos..end]);
+            self.pos = end;
+        } else {
+            out.fill(0);
+            self.error = true;
+        }
+    }
+
+    pub fn u8(&mut self) -> u8 {
+        let mut b = [0; 1];
+        self.bytes(&mut b);
+        b[0]
+    }
+
+    pub fn bool(&mut self) -> bool {
+        match self.u8() {
+            0 => false,
+            1 => true,
+            _ => {
+                self.error = true;
+                false
+            }
+        }
+    }
+
+    pub fn u16(&mut self) -> u16 {
+        let mut b = [0; 2];
+        self.bytes(&mut b);
+        u16::from_le_bytes(b)
+    }
+
+    pub fn u32(&mut self) -> u32 {
+        let mut b = [0; 4];
+        self.bytes(&mut b);
+        u32::from_le_bytes(b)
+    }
+
+    pub fn u64(&mut self) -> u64 {
+        let mut b = [0; 8];
+        self.bytes(&mut b);
+        u64::from_le_bytes(b)
+    }
+
+    pub fn f32(&mut self) -> f32 {
+        f32::from_bits(self.u32())
+    }
+
+    /// 値が不正だったことを通知する（enum の範囲外など）
+    pub fn invalid(&mut self) {
+        self.error = true;
+    }
+
+    /// 全データを過不足なく読めたか
+    pub fn finish(self) -> Result<(), StateError> {
+        if self.error || self.pos != self.buf.len() {
+            Err(StateError::Corrupt)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// ペイロードのチェックサム (FNV-1a 32bit)
+fn checksum(data: &[u8]) -> u32 {
+    data.iter().fold(0x811C_9DC5u32, |h, &b| (h ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+/// `payload` をヘッダ付きで `buf` に書き出す。`write_payload` は
+/// ヘッダ直後から書き始める writer を受け取る。
+pub(crate) fn write_with_header(
+    buf: &mut [u8],
+    write_payload: impl FnOnce(&mut StateWriter),
+) -> Result<usize, StateError> {
+    let (head, body) = if buf.len() >= HEADER_LEN {
+        buf.split_at_mut(HEADER_LEN)
+    } else {
+        // ヘッダすら入らない: サイズ見積もりだけ行う
+        let mut w = StateWriter::new(&mut []);
+        write_payload(&mut w);
+        return Err(StateError::BufferTooSmall(HEADER_LEN + w.len()));
+    };
+    let mut w = StateWriter::new(body);
+    write_payload(&mut w);
+    let len = w.finish().map_err(|e| match e {
+        StateError::BufferTooSmall(n) => StateError::BufferTooSmall(HEADER_LEN + n),
+        e => e,
+    })?;
+    let sum = checksum(&body[..len]);
+    head[0..4].copy_from_slice(&MAGIC);
+    head[4..6].copy_from_slice(&VERSION.to_le_bytes());
+    head[6..8].copy_from_slice(&[0, 0]);
+    head[8..12].copy_from_slice(&(len as u32).to_le_bytes());
+    head[12..16].copy_from_slice(&sum.to_le_bytes());
+    Ok(HEADER_LEN + len)
+}
+
+/// ヘッダを検証し、ペイロード部分を返す
+pub(crate) fn verify_header(buf: &[u8]) -> Result<&[u8], StateError> {
+    if buf.len() < HEADER_LEN {
+        return Err(StateError::Corrupt);
+    }
+    if buf[0..4] != MAGIC {
+        return Err(StateError::BadMagic);
+    }
+    let version = u16::from_le_bytes([buf[4], buf[5]]);
+    if version != VERSION {
+        return Err(StateError::UnsupportedVersion(version));
+    }
+    let len = u32::from_le_bytes([buf[8], buf[9], buf[10], buf[11]]) as usize;
+    let sum = u32::from_le_bytes([buf[12], buf[13], buf[14], buf[15]]);
+    let payload = buf.get(HEADER_LEN..HEADER_LEN + len).ok_or(StateError::Corrupt)?;
+    if checksum(payload) != sum {
+        return Err(StateError::Corrupt);
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bootrom::Bootrom;
+    use crate::gameboy::GameBoy;
+    use crate::input::NullInput;
+    use crate::mmu::Mmu;
+    use crate::platform::{NullAudio, NullCartridge, NullDisplay};
+
+    fn new_gb() -> GameBoy<NullCartridge, NullDisplay, NullAudio, NullInput> {
+        let mmu = Mmu::new(Bootrom::disabled(), NullCartridge);
+        GameBoy::new(mmu, NullDisplay, NullAudio, NullInput)
+    }
+
+    #[test]
+    fn round_trip_and_header_checks() {
+        let mut gb = new_gb();
+        for _ in 0..10_000 {
+            gb.step();
+        }
+        let mut buf = vec![0u8; gb.state_size()];
+        let len = gb.save_state(&mut buf).unwrap();
+        assert_eq!(len, buf.len());
+        assert_eq!(gb.save_state(&mut buf[..len - 1]), Err(StateError::BufferTooSmall(len)));
+
+        // 復元後は同じ状態から同じように進む
+        let mut other = new_gb();
+        other.load_state(&buf).unwrap();
+        for _ in 0..1000 {
+            gb.step();
+            other.step();
+        }
+        assert_eq!(gb.debug_cpu(), other.debug_cpu());
+        assert_eq!(gb.debug_regs(), other.debug_regs());
+
+        let mut broken = buf.clone();
+        broken[HEADER_LEN + 10] ^= 0xFF;
+        assert_eq!(other.load_state(&broken), Err(StateError::Corrupt));
+        broken[0] = b'X';
+        assert_eq!(other.load_state(&broken), Err(StateError::BadMagic));
+    }
+}
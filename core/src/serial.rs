@@ -0,0 +1,328 @@
+//! シリアル転送 (SB 0xFF01 / SC 0xFF02)。
+//!
+//! 転送タイミングとレジスタはここで管理し、相手側（リンクケーブル・周辺機器）は
+//! [`SerialPort`] として GameBoy に渡す。ビット単位のシフトは省略し、
+//! 8 ビット分の時間が経過した時点で 1 バイトを交換する。
+
+use core::cell::Cell;
+
+use crate::gameboy::{GameBoy, StepResult};
+use crate::input::InputSource;
+use crate::platform::{AudioSink, CartridgeBus, Display, SerialPort};
+use crate::savestate::{Snapshot, StateReader, StateWriter};
+
+/// 内部クロック 8192 Hz: 1 ビット = 128 M-cycle
+const BIT_CYCLES: u16 = 128;
+/// CGB 高速クロック (SC bit1) 262144 Hz: 1 ビット = 4 M-cycle
+const BIT_CYCLES_FAST: u16 = 4;
+
+const SC_START: u8 = 1 << 7;
+const SC_FAST: u8 = 1 << 1;
+const SC_INTERNAL: u8 = 1 << 0;
+
+pub struct Serial {
+    /// SB (0xFF01)
+    sb: u8,
+    /// SC (0xFF02): bit7=転送要求/転送中, bit1=高速クロック(CGB), bit0=内部クロック
+    sc: u8,
+    /// 内部クロック転送の残りビット数
+    bits_left: u8,
+    /// 次のビットまでの M-cycle
+    counter: u16,
+    /// CGB モード（SC bit1 が有効）
+    pub cgb_mode: bool,
+}
+
+impl Serial {
+    pub fn new() -> Self {
+        Self { sb: 0, sc: 0, bits_left: 0, counter: 0, cgb_mode: false }
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF01 => self.sb,
+            0xFF02 => self.sc | if self.cgb_mode { 0x7C } else { 0x7E },
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, val: u8) {
+        match addr {
+            0xFF01 => self.sb = val,
+            0xFF02 => {
+                let mask = if self.cgb_mode { 0x83 } else { 0x81 };
+                self.sc = val & mask;
+                if self.sc & (SC_START | SC_INTERNAL) == SC_START | SC_INTERNAL {
+                    self.bits_left = 8;
+                    self.counter = self.bit_cycles();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn bit_cycles(&self) -> u16 {
+        if self.sc & SC_FAST != 0 { BIT_CYCLES_FAST } else { BIT_CYCLES }
+    }
+
+    /// 1 M-cycle（CPU クロック）進める。転送完了でシリアル割り込みを要求するため true を返す。
+    pub fn emulate_cycle(&mut self, port: &mut impl SerialPort) -> bool {
+        if self.sc & SC_START == 0 {
+            return false;
+        }
+        if self.sc & SC_INTERNAL != 0 {
+            // セーブステートの値が転送中に 0 でも、桁あふれせずに転送を終える
+            self.counter = self.counter.saturating_sub(1);
+            if self.counter > 0 {
+                return false;
+            }
+            self.bits_left = self.bits_left.saturating_sub(1);
+            if self.bits_left > 0 {
+                self.counter = self.bit_cycles();
+                return false;
+            }
+            self.sb = port.transfer(self.sb);
+        } else {
+            match port.poll_external(self.sb) {
+                Some(byte) => self.sb = byte,
+                None => return false,
+            }
+        }
+        self.sc &= !SC_START;
+        true
+    }
+}
+
+impl Default for Serial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Snapshot for Serial {
+    fn save(&self, w: &mut StateWriter) {
+        w.u8(self.sb);
+        w.u8(self.sc);
+        w.u8(self.bits_left);
+        w.u16(self.counter);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        self.sb = r.u8();
+        self.sc = r.u8();
+        self.bits_left = r.u8();
+        self.counter = r.u16();
+    }
+}
+
+/// 同一プロセス内の 2 台をつなぐリンクケーブル。
+///
+/// ヒープを使わないため、ケーブル本体を呼び出し側が保持し、
+/// [`LinkCable::ports`] で得た 2 つの [`LinkPort`] を各 GameBoy に渡す。
+pub struct LinkCable {
+    sides: [CableSide; 2],
+}
+
+#[derive(Default)]
+struct CableSide {
+    /// 外部クロックで転送待ち中
+    waiting: Cell<bool>,
+    /// 転送待ち中の SB（相手のマスター転送で渡す値）
+    sb: Cell<u8>,
+    /// 相手のマスター転送で届いたバイト（次の poll で受け取る）
+    incoming: Cell<Option<u8>>,
+}
+
+impl LinkCable {
+    pub fn new() -> Self {
+        Self { sides: [CableSide::default(), CableSide::default()] }
+    }
+
+    /// 両端のポート
+    pub fn ports(&self) -> (LinkPort<'_>, LinkPort<'_>) {
+        (LinkPort { cable: self, side: 0 }, LinkPort { cable: self, side: 1 })
+    }
+}
+
+impl Default for LinkCable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// [`LinkCable`] の片端。
+pub struct LinkPort<'a> {
+    cable: &'a LinkCable,
+    side: usize,
+}
+
+impl SerialPort for LinkPort<'_> {
+    fn transfer(&mut self, out: u8) -> u8 {
+        let peer = &self.cable.sides[1 - self.side];
+        if peer.waiting.replace(false) {
+            peer.incoming.set(Some(out));
+            peer.sb.get()
+        } else {
+            // 相手が外部クロックで待っていない: 何も受信できない
+            0xFF
+        }
+    }
+
+    fn poll_external(&mut self, out: u8) -> Option<u8> {
+        let me = &self.cable.sides[self.side];
+        if let Some(byte) = me.incoming.take() {
+            return Some(byte);
+        }
+        me.waiting.set(true);
+        me.sb.set(out);
+        None
+    }
+}
+
+/// blargg テスト ROM のシリアル出力を記録するポート（host 専用）。
+///
+/// 内部クロック転送で送ったバイトを `log` に蓄積し、転送自体は `inner`
+/// （リンクケーブル・プリンタ・未接続）へそのまま渡す。
+#[cfg(feature = "test-harness")]
+pub struct TestSerial<S: SerialPort> {
+    pub inner: S,
+    /// 送信バイトのログ
+    pub log: heapless::Vec<u8, 8192>,
+}
+
+#[cfg(feature = "test-harness")]
+impl<S: SerialPort> TestSerial<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner, log: heapless::Vec::new() }
+    }
+
+    /// blargg テストは "Passed" または "Failed" を送って終了する
+    pub fn finished(&self) -> bool {
+        self.log.ends_with(b"Passed") || self.log.ends_with(b"Failed")
+    }
+}
+
+#[cfg(feature = "test-harness")]
+impl<S: SerialPort> SerialPort for TestSerial<S> {
+    fn transfer(&mut self, out: u8) -> u8 {
+        let _ = self.log.push(out);
+        self.inner.transfer(out)
+    }
+
+    fn poll_external(&mut self, out: u8) -> Option<u8> {
+        self.inner.poll_external(out)
+    }
+
+    fn tick(&mut self) {
+        self.inner.tick();
+    }
+}
+
+/// リンクケーブルでつないだ 2 台を 1 M-cycle ずつ進める。
+pub fn step_linked<'a, C1, D1, A1, I1, C2, D2, A2, I2>(
+    a: &mut GameBoy<C1, D1, A1, I1, LinkPort<'a>>,
+    b: &mut GameBoy<C2, D2, A2, I2, LinkPort<'a>>,
+) -> (StepResult, StepResult)
+where
+    C1: CartridgeBus,
+    D1: Display,
+    A1: AudioSink,
+    I1: InputSource,
+    C2: CartridgeBus,
+    D2: Display,
+    A2: AudioSink,
+    I2: InputSource,
+{
+    (a.step(), b.step())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bootrom::Bootrom;
+    use crate::cartridge::tests::banked_rom;
+    use crate::cartridge::{MbcCart, NoClock};
+    use crate::input::NullInput;
+    use crate::mmu::Mmu;
+    use crate::platform::{NullAudio, NullDisplay, NullSerial};
+
+    type LinkedGb<'a> =
+        GameBoy<MbcCart<Vec<u8>, Vec<u8>>, NullDisplay, NullAudio, NullInput, LinkPort<'a>>;
+
+    /// SB に `sb` を置いて SC に `sc` を書き、転送完了を待って受信バイトを B に入れる
+    fn linked_gb(sb: u8, sc: u8, port: LinkPort<'_>) -> LinkedGb<'_> {
+        let mut rom = banked_rom(2, 0x00);
+        let code = [
+            0x3E, sb, 0xE0, 0x01, // 0100 LD A,sb / LDH (SB),A
+            0x3E, sc, 0xE0, 0x02, // 0104 LD A,sc / LDH (SC),A
+            0xF0, 0x02, 0x17, 0x38, 0xFB, // 0108 LDH A,(SC) / RLA / JR C,0108
+            0xF0, 0x01, 0x47, // 010D LDH A,(SB) / LD B,A
+            0x18, 0xFE, // 0110 JR 0x0110
+        ];
+        rom[0x100..0x100 + code.len()].copy_from_slice(&code);
+        let cart = MbcCart::new(rom, Vec::new(), NoClock).unwrap();
+        let mmu = Mmu::new(Bootrom::disabled(), cart);
+        GameBoy::with_serial(mmu, NullDisplay, NullAudio, NullInput, port)
+    }
+
+    #[test]
+    fn linked_game_boys_exchange_bytes() {
+        // 先に進める側・後に進める側のどちらがマスターでも両方向に届く
+        for a_is_master in [true, false] {
+            let cable = LinkCable::new();
+            let (pa, pb) = cable.ports();
+            let sc = |master: bool| if master { 0x81 } else { 0x80 };
+            let mut a = linked_gb(0x42, sc(a_is_master), pa);
+            let mut b = linked_gb(0x99, sc(!a_is_master), pb);
+            for _ in 0..8 * BIT_CYCLES as usize + 100 {
+                step_linked(&mut a, &mut b);
+            }
+            assert_eq!((a.registers().b, b.registers().b), (0x99, 0x42), "{}", a_is_master);
+        }
+    }
+
+    #[test]
+    fn restored_zero_counter_finishes_transfer() {
+        // 転送中にカウンタ 0 のセーブステートを読み込んでも桁あふれしない
+        let mut s = Serial::new();
+        s.write(0xFF02, 0x81);
+        s.counter = 0;
+        s.bits_left = 0;
+        assert!(s.emulate_cycle(&mut NullSerial));
+        assert_eq!(s.read(0xFF01), 0xFF);
+    }
+
+    #[test]
+    fn linked_transfer_swaps_bytes() {
+        let cable = LinkCable::new();
+        let (mut pa, mut pb) = cable.ports();
+        let (mut master, mut slave) = (Serial::new(), Serial::new());
+        slave.write(0xFF01, 0x99);
+        slave.write(0xFF02, 0x80); // 外部クロックで待機
+        master.write(0xFF01, 0x42);
+        master.write(0xFF02, 0x81); // 内部クロックで開始
+
+        for cycle in 1..=8 * BIT_CYCLES {
+            let done = master.emulate_cycle(&mut pa);
+            // スレーブはマスターと同じサイクルで完了する
+            assert_eq!(slave.emulate_cycle(&mut pb), done);
+            assert_eq!(done, cycle == 8 * BIT_CYCLES);
+        }
+        assert_eq!(master.read(0xFF01), 0x99);
+        assert_eq!(slave.read(0xFF01), 0x42);
+        assert_eq!(master.read(0xFF02) & 0x80, 0);
+        assert_eq!(slave.read(0xFF02) & 0x80, 0);
+    }
+
+    #[test]
+    fn unconnected_master_receives_ff() {
+        let cable = LinkCable::new();
+        let (mut pa, _) = cable.ports();
+        let mut s = Serial::new();
+        s.cgb_mode = true;
+        s.write(0xFF01, 0x12);
+        s.write(0xFF02, 0x83); // 高速クロック
+        assert!((0..8 * BIT_CYCLES_FAST).any(|_| s.emulate_cycle(&mut pa)));
+        assert_eq!(s.read(0xFF01), 0xFF);
+    }
+}
@@ -1,78 +1,224 @@
+//! タイマー (DIV / TIMA / TMA / TAC)。
+//!
+//! 実機と同じく 16 bit のシステムカウンタ（T-cycle 単位）1 本で動かす。DIV はその上位 8 bit、
+//! TIMA は TAC で選んだカウンタのビット（とタイマー有効ビットの AND）の立ち下がりで増える。
+//! そのため DIV への書き込みや TAC の変更でも TIMA が進むことがある。
+//!
+//! TIMA のオーバーフローは 1 M-cycle 遅れて TMA の再ロードと割り込み要求になる:
+//! - オーバーフロー直後の M-cycle は TIMA = 0x00。ここで TIMA に書き込むと再ロードと割り込みは取り消される
+//! - 再ロードした M-cycle の TIMA への書き込みは無視され、TMA への書き込みは TIMA にも反映される
+
+use crate::savestate::{Snapshot, StateReader, StateWriter};
+
+/// TAC の入力クロック選択ごとのシステムカウンタのビット
+/// (4096 Hz, 262144 Hz, 65536 Hz, 16384 Hz)
+const TAC_BITS: [u16; 4] = [1 << 9, 1 << 3, 1 << 5, 1 << 7];
+const TAC_ENABLE: u8 = 0x04;
+
+/// APU の Frame Sequencer を進める DIV のビット（DIV bit4、ダブルスピード時は bit5）
+const DIV_APU_BIT: u16 = 1 << 12;
+const DIV_APU_BIT_DOUBLE: u16 = 1 << 13;
+
 pub struct Timer {
-    div: u8,
+    /// システムカウンタ（T-cycle 単位。上位 8 bit が DIV）
+    counter: u16,
     pub tima: u8,
     pub tma: u8,
     pub tac: u8,
-    div_counter: u32,
-    tima_counter: u32,
+    /// 前の M-cycle で TIMA がオーバーフローし、再ロード待ち
+    overflow: bool,
+    /// この M-cycle で TMA を再ロードした
+    reloading: bool,
+    /// CGB ダブルスピード中（Frame Sequencer のビット選択に使う）
+    pub double_speed: bool,
+    /// DIV のビットの立ち下がりで Frame Sequencer を 1 ステップ進める要求
+    pub apu_tick: bool,
 }
 
 impl Timer {
     pub fn new() -> Self {
         Self {
-            div: 0,
+            counter: 0,
             tima: 0,
             tma: 0,
             tac: 0,
-            div_counter: 0,
-            tima_counter: 0,
+            overflow: false,
+            reloading: false,
+            double_speed: false,
+            apu_tick: false,
         }
     }
 
+    /// BootROM 終了時点のシステムカウンタを設定する（BootROM スキップ用）
+    pub fn set_counter(&mut self, counter: u16) {
+        self.counter = counter;
+    }
+
     /// 1 M-cycle進める。タイマー割り込みが発生したら true を返す。
     pub fn emulate_cycle(&mut self) -> bool {
-        self.div_counter = self.div_counter.wrapping_add(1);
-        // DIV は 64 M-cycle ごとにインクリメント (4MHz / 64 = 16384Hz)
-        if self.div_counter % 64 == 0 {
-            self.div = self.div.wrapping_add(1);
+        self.reloading = false;
+        let mut irq = false;
+        if self.overflow {
+            self.overflow = false;
+            self.tima = self.tma;
+            self.reloading = true;
+            irq = true;
         }
+        self.set_counter_value(self.counter.wrapping_add(4));
+        irq
+    }
+
+    /// TIMA の入力信号（選択ビット AND 有効ビット）
+    fn timer_signal(&self) -> bool {
+        self.tac & TAC_ENABLE != 0 && self.counter & TAC_BITS[(self.tac & 0x03) as usize] != 0
+    }
 
-        if self.tac & 0x04 == 0 {
-            return false;
+    fn apu_bit(&self) -> u16 {
+        if self.double_speed { DIV_APU_BIT_DOUBLE } else { DIV_APU_BIT }
+    }
+
+    /// カウンタを更新し、立ち下がりエッジで TIMA と Frame Sequencer を進める
+    fn set_counter_value(&mut self, value: u16) {
+        let old_signal = self.timer_signal();
+        let old_apu = self.counter & self.apu_bit() != 0;
+        self.counter = value;
+        if old_signal && !self.timer_signal() {
+            self.increment_tima();
         }
+        if old_apu && self.counter & self.apu_bit() == 0 {
+            self.apu_tick = true;
+        }
+    }
 
-        self.tima_counter = self.tima_counter.wrapping_add(1);
-        let threshold: u32 = match self.tac & 0x03 {
-            0 => 256, // 4096 Hz
-            1 => 4,   // 262144 Hz
-            2 => 16,  // 65536 Hz
-            3 => 64,  // 16384 Hz
-            _ => unreachable!(),
-        };
-
-        if self.tima_counter >= threshold {
-            self.tima_counter = 0;
-            let (new_tima, overflow) = self.tima.overflowing_add(1);
-            if overflow {
-                self.tima = self.tma;
-                return true;
-            }
-            self.tima = new_tima;
+    fn increment_tima(&mut self) {
+        let (tima, overflow) = self.tima.overflowing_add(1);
+        self.tima = tima;
+        if overflow {
+            self.overflow = true;
         }
-        false
     }
 
     pub fn read(&self, addr: u16) -> u8 {
         match addr {
-            0xFF04 => self.div,
+            0xFF04 => (self.counter >> 8) as u8,
             0xFF05 => self.tima,
             0xFF06 => self.tma,
-            0xFF07 => self.tac,
+            0xFF07 => self.tac | 0xF8,
             _ => 0xFF,
         }
     }
 
     pub fn write(&mut self, addr: u16, val: u8) {
         match addr {
-            0xFF04 => {
-                // DIV への書き込みはリセット
-                self.div = 0;
-                self.div_counter = 0;
+            // DIV への書き込みはシステムカウンタ全体のリセット
+            0xFF04 => self.set_counter_value(0),
+            // 再ロードした M-cycle の書き込みは無視
+            0xFF05 if !self.reloading => {
+                self.tima = val;
+                // 再ロード待ちの間に書くと再ロードと割り込みが取り消される
+                self.overflow = false;
+            }
+            0xFF06 => {
+                self.tma = val;
+                if self.reloading {
+                    self.tima = val;
+                }
+            }
+            0xFF07 => {
+                let old_signal = self.timer_signal();
+                self.tac = val & 0x07;
+                if old_signal && !self.timer_signal() {
+                    self.increment_tima();
+                }
             }
-            0xFF05 => self.tima = val,
-            0xFF06 => self.tma = val,
-            0xFF07 => self.tac = val & 0x07,
             _ => {}
         }
     }
 }
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Snapshot for Timer {
+    fn save(&self, w: &mut StateWriter) {
+        w.u16(self.counter);
+        w.u8(self.tima);
+        w.u8(self.tma);
+        w.u8(self.tac);
+        w.bool(self.overflow);
+        w.bool(self.reloading);
+        w.bool(self.double_speed);
+        w.bool(self.apu_tick);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        self.counter = r.u16();
+        self.tima = r.u8();
+        self.tma = r.u8();
+        self.tac = r.u8();
+        self.overflow = r.bool();
+        self.reloading = r.bool();
+        self.double_speed = r.bool();
+        self.apu_tick = r.bool();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(t: &mut Timer, cycles: u32) -> bool {
+        (0..cycles).fold(false, |irq, _| t.emulate_cycle() | irq)
+    }
+
+    #[test]
+    fn div_write_and_tac_change_tick_tima() {
+        let mut t = Timer::new();
+        t.write(0xFF07, 0x05); // 262144 Hz (bit3)
+        run(&mut t, 2); // counter = 8: bit3 が立つ
+        assert_eq!(t.tima, 0);
+        // DIV リセットで bit3 が 1→0: TIMA が進む
+        t.write(0xFF04, 0);
+        assert_eq!(t.tima, 1);
+        assert_eq!(t.read(0xFF04), 0);
+
+        run(&mut t, 2);
+        // タイマー停止でも信号が 1→0 になるので進む
+        t.write(0xFF07, 0x01);
+        assert_eq!(t.tima, 2);
+        // 入力を bit3 → bit5 (0) に変えても立ち下がり
+        t.write(0xFF07, 0x05);
+        t.write(0xFF07, 0x06);
+        assert_eq!(t.tima, 3);
+    }
+
+    #[test]
+    fn overflow_reload_is_delayed_one_cycle() {
+        let mut t = Timer::new();
+        t.tma = 0x80;
+        t.tima = 0xFF;
+        t.write(0xFF07, 0x05);
+        // 4 M-cycle ごとに 1 増える
+        assert!(!run(&mut t, 4));
+        assert_eq!(t.tima, 0x00);
+        // 次の M-cycle で TMA の再ロードと割り込み
+        assert!(t.emulate_cycle());
+        assert_eq!(t.tima, 0x80);
+        // 再ロードした M-cycle の TIMA 書き込みは無視、TMA 書き込みは反映
+        t.write(0xFF05, 0x10);
+        t.write(0xFF06, 0x90);
+        assert_eq!(t.tima, 0x90);
+
+        // 再ロード待ちの間に TIMA へ書くと取り消し
+        let mut t = Timer::new();
+        t.tima = 0xFF;
+        t.write(0xFF07, 0x05);
+        run(&mut t, 4);
+        t.write(0xFF05, 0x42);
+        assert!(!t.emulate_cycle());
+        assert_eq!(t.tima, 0x42);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::savestate::{Snapshot, StateReader, StateWriter};
+
 /// CGB: 32KB WRAM（バンク 0 固定 0xC000–0xCFFF + バンク 1–7 を SVBK で 0xD000–0xDFFF に切替）
 /// DMG: 8KB 相当（バンク 0 + バンク 1 固定）
 pub struct WRam {
@@ -23,6 +25,11 @@ impl WRam {
         self.svbk = if n == 0 { 1 } else { n };
     }
 
+    /// 0xD000–0xDFFF に割り当て中のバンク番号（1–7）
+    pub fn bank(&self) -> u8 {
+        self.svbk
+    }
+
     pub fn read(&self, addr: u16) -> u8 {
         match addr {
             0xC000..=0xCFFF => self.banks[0][(addr - 0xC000) as usize],
@@ -44,3 +51,19 @@ impl WRam {
         }
     }
 }
+
+impl Snapshot for WRam {
+    fn save(&self, w: &mut StateWriter) {
+        for bank in &self.banks {
+            w.bytes(bank);
+        }
+        w.u8(self.svbk);
+    }
+
+    fn load(&mut self, r: &mut StateReader) {
+        for bank in &mut self.banks {
+            r.bytes(bank);
+        }
+        self.write_svbk(r.u8());
+    }
+}
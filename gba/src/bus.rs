@@ -25,6 +25,31 @@ const EWRAM_SIZE: usize = 0x4_0000;
 const IWRAM_SIZE: usize = 0x8000;
 const SRAM_SIZE: usize = 0x1_0000;
 
+/// デバッガのウォッチポイント（`addr` から `len` バイト）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub addr: u32,
+    pub len: u32,
+    pub read: bool,
+    pub write: bool,
+}
+
+impl Watchpoint {
+    fn matches(&self, addr: u32, len: u32, write: bool) -> bool {
+        let hit = if write { self.write } else { self.read };
+        hit && addr < self.addr.wrapping_add(self.len) && self.addr < addr.wrapping_add(len)
+    }
+}
+
+/// ウォッチポイントに掛かったアクセス
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchHit {
+    /// `Bus::watchpoints` の添字
+    pub index: usize,
+    pub addr: u32,
+    pub write: bool,
+}
+
 pub struct Bus {
     pub bios: Option<Vec<u8>>,
     ewram: Box<[u8]>,
@@ -50,6 +75,10 @@ pub struct Bus {
     pub halt_request: bool,
     /// APU 未実装のためサウンドレジスタ (0x60-0xAF) は RAM バッキングで代用
     sound_regs: [u8; 0x50],
+    /// 空でなければ全アクセスを照合する（命令フェッチと DMA も含む）
+    pub watchpoints: Vec<Watchpoint>,
+    /// 最初にヒットしたアクセス。取り出すまで上書きしない
+    pub watch_hit: Option<WatchHit>,
 }
 
 impl Bus {
@@ -73,6 +102,8 @@ impl Bus {
             postflg: 0,
             halt_request: false,
             sound_regs: [0; 0x50],
+            watchpoints: Vec::new(),
+            watch_hit: None,
         }
     }
 
@@ -101,7 +132,21 @@ impl Bus {
         }
     }
 
+    #[inline]
+    fn watch(&mut self, addr: u32, len: u32, write: bool) {
+        if !self.watchpoints.is_empty() && self.watch_hit.is_none() {
+            let index = self.watchpoints.iter().position(|w| w.matches(addr, len, write));
+            self.watch_hit = index.map(|index| WatchHit { index, addr, write });
+        }
+    }
+
     pub fn read8(&mut self, addr: u32) -> u8 {
+        self.watch(addr, 1, false);
+        self.peek8(addr)
+    }
+
+    /// 副作用のない 1 バイト読み出し（ウォッチポイントも発火しない）。デバッガ用
+    pub fn peek8(&self, addr: u32) -> u8 {
         match addr >> 24 {
             0x0 => {
                 let a = addr as usize;
@@ -135,6 +180,7 @@ impl Bus {
 
     pub fn read16(&mut self, addr: u32) -> u16 {
         let addr = addr & !1;
+        self.watch(addr, 2, false);
         if addr >> 24 == 0x4 {
             return self.io_read16(addr);
         }
@@ -143,10 +189,12 @@ impl Bus {
 
     pub fn read32(&mut self, addr: u32) -> u32 {
         let addr = addr & !3;
+        self.watch(addr, 4, false);
         self.read16(addr) as u32 | (self.read16(addr + 2) as u32) << 16
     }
 
     pub fn write8(&mut self, addr: u32, val: u8) {
+        self.watch(addr, 1, true);
         match addr >> 24 {
             0x2 => self.ewram[addr as usize & (EWRAM_SIZE - 1)] = val,
             0x3 => self.iwram[addr as usize & (IWRAM_SIZE - 1)] = val,
@@ -181,6 +229,7 @@ impl Bus {
 
     pub fn write16(&mut self, addr: u32, val: u16) {
         let addr = addr & !1;
+        self.watch(addr, 2, true);
         match addr >> 24 {
             0x4 => self.io_write16(addr, val, 0xFFFF),
             0x5 => {
@@ -207,11 +256,12 @@ impl Bus {
 
     pub fn write32(&mut self, addr: u32, val: u32) {
         let addr = addr & !3;
+        self.watch(addr, 4, true);
         self.write16(addr, val as u16);
         self.write16(addr + 2, (val >> 16) as u16);
     }
 
-    fn io_read16(&mut self, addr: u32) -> u16 {
+    fn io_read16(&self, addr: u32) -> u16 {
         let off = (addr & 0x3FF) as usize;
         match off {
             0x000..=0x056 => self.ppu.read_io(off),
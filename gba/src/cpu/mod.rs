@@ -49,6 +49,13 @@ pub struct Cpu {
     extra_cycles: u32,
 }
 
+/// [`Cpu::banked_reg`] の退避先
+enum BankSlot {
+    R8_12(usize),
+    R8_12Fiq(usize),
+    R13_14(usize, usize),
+}
+
 /// モード → バンク添字 (0:usr/sys 1:fiq 2:irq 3:svc 4:abt 5:und)
 fn bank_index(mode: u32) -> usize {
     match mode {
@@ -152,6 +159,55 @@ impl Cpu {
         }
     }
 
+    /// モード `mode` から見た r8-r14 を返す（デバッガ用）。
+    /// 現在のモードと同じバンクなら `regs` の値そのもの。
+    pub fn banked_reg(&self, mode: u32, r: usize) -> u32 {
+        match self.bank_slot(mode, r) {
+            Some(BankSlot::R8_12(i)) => self.bank_r8_12[i],
+            Some(BankSlot::R8_12Fiq(i)) => self.bank_r8_12_fiq[i],
+            Some(BankSlot::R13_14(b, i)) => self.bank_r13_14[b][i],
+            None => self.regs[r],
+        }
+    }
+
+    pub fn set_banked_reg(&mut self, mode: u32, r: usize, val: u32) {
+        match self.bank_slot(mode, r) {
+            Some(BankSlot::R8_12(i)) => self.bank_r8_12[i] = val,
+            Some(BankSlot::R8_12Fiq(i)) => self.bank_r8_12_fiq[i] = val,
+            Some(BankSlot::R13_14(b, i)) => self.bank_r13_14[b][i] = val,
+            None => self.regs[r] = val,
+        }
+    }
+
+    /// 退避先の位置。現在のモードで `regs` に載っていれば None
+    fn bank_slot(&self, mode: u32, r: usize) -> Option<BankSlot> {
+        debug_assert!((8..15).contains(&r));
+        let cur = self.mode();
+        if r >= 13 {
+            let b = bank_index(mode);
+            (b != bank_index(cur)).then_some(BankSlot::R13_14(b, r - 13))
+        } else if (mode == MODE_FIQ) == (cur == MODE_FIQ) {
+            None
+        } else if mode == MODE_FIQ {
+            Some(BankSlot::R8_12Fiq(r - 8))
+        } else {
+            Some(BankSlot::R8_12(r - 8))
+        }
+    }
+
+    /// モード `mode` の SPSR（usr/sys は持たないので None）
+    pub fn spsr_of(&self, mode: u32) -> Option<u32> {
+        let i = bank_index(mode);
+        (i != 0).then(|| self.spsr[i])
+    }
+
+    pub fn set_spsr_of(&mut self, mode: u32, val: u32) {
+        let i = bank_index(mode);
+        if i != 0 {
+            self.spsr[i] = val;
+        }
+    }
+
     /// SPSR を CPSR へ復帰する（例外からの復帰）。モードバンクも切り替わる。
     fn restore_cpsr(&mut self) {
         let spsr = self.spsr();
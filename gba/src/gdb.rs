@@ -0,0 +1,649 @@
+//! GDB リモートシリアルプロトコル (RSP) のスタブ。
+//!
+//! パケットの分解/組み立てとコマンド処理だけを行い、通信路はホストが用意する
+//! （gb-host は `--gdb PORT` で TCP 待ち受けする）。ブレークポイントはメモリを
+//! 書き換えず PC 比較で実装するため、ソフトウェア (Z0) とハードウェア (Z1) は同じ扱い。
+//!
+//! レジスタ番号は [`target.xml`](target_xml) の通り:
+//! r0-r15 (0-15)、cpsr (25)、モード別バンクレジスタと SPSR (26-52)。
+//! ARM/Thumb 状態は cpsr の T ビットで GDB に伝わる。
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::bus::{WatchHit, Watchpoint};
+use crate::cpu::{MODE_ABT, MODE_FIQ, MODE_IRQ, MODE_SVC, MODE_UND, MODE_USR};
+use crate::gba::{CYCLES_PER_FRAME, Gba};
+
+const SIGINT: u8 = 2;
+const SIGTRAP: u8 = 5;
+
+/// `qSupported` で通知するパケットの最大長
+const PACKET_SIZE: usize = 0x1000;
+/// `m` で一度に返すバイト数の上限（16 進 2 文字ずつで PACKET_SIZE に収まる）
+const MAX_READ: u32 = (PACKET_SIZE / 2) as u32;
+
+/// `g` パケットに含めるレジスタ数（r0-r15, cpsr）。残りは `p` で個別に読まれる
+const G_REGS: usize = 17;
+const REG_CPSR: usize = 25;
+
+/// 26 番以降のバンクレジスタ（名前, モード, レジスタ番号）
+const BANKED: [(&str, u32, usize); 22] = [
+    ("r8_usr", MODE_USR, 8),
+    ("r9_usr", MODE_USR, 9),
+    ("r10_usr", MODE_USR, 10),
+    ("r11_usr", MODE_USR, 11),
+    ("r12_usr", MODE_USR, 12),
+    ("r13_usr", MODE_USR, 13),
+    ("r14_usr", MODE_USR, 14),
+    ("r8_fiq", MODE_FIQ, 8),
+    ("r9_fiq", MODE_FIQ, 9),
+    ("r10_fiq", MODE_FIQ, 10),
+    ("r11_fiq", MODE_FIQ, 11),
+    ("r12_fiq", MODE_FIQ, 12),
+    ("r13_fiq", MODE_FIQ, 13),
+    ("r14_fiq", MODE_FIQ, 14),
+    ("r13_irq", MODE_IRQ, 13),
+    ("r14_irq", MODE_IRQ, 14),
+    ("r13_svc", MODE_SVC, 13),
+    ("r14_svc", MODE_SVC, 14),
+    ("r13_abt", MODE_ABT, 13),
+    ("r14_abt", MODE_ABT, 14),
+    ("r13_und", MODE_UND, 13),
+    ("r14_und", MODE_UND, 14),
+];
+
+/// バンクレジスタに続く SPSR（名前, モード）
+const SPSRS: [(&str, u32); 5] = [
+    ("spsr_fiq", MODE_FIQ),
+    ("spsr_irq", MODE_IRQ),
+    ("spsr_svc", MODE_SVC),
+    ("spsr_abt", MODE_ABT),
+    ("spsr_und", MODE_UND),
+];
+
+const REG_BANKED: usize = REG_CPSR + 1;
+const REG_SPSR: usize = REG_BANKED + BANKED.len();
+const REG_END: usize = REG_SPSR + SPSRS.len();
+
+/// 受信バイト列から切り出したもの
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incoming {
+    /// `$...#xx` の本体（チェックサム検証済み）。ホストは `+` を返す
+    Packet(Vec<u8>),
+    /// チェックサム不一致。ホストは `-` を返して再送させる
+    BadChecksum,
+    /// 実行中の中断要求 (0x03, Ctrl-C)
+    Interrupt,
+}
+
+enum ReadState {
+    Idle,
+    Body,
+    Sum1,
+    Sum2(u8),
+}
+
+/// 1 バイトずつ受け取ってパケットを組み立てる
+pub struct PacketReader {
+    buf: Vec<u8>,
+    state: ReadState,
+}
+
+impl Default for PacketReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PacketReader {
+    pub fn new() -> Self {
+        Self { buf: Vec::new(), state: ReadState::Idle }
+    }
+
+    pub fn push(&mut self, byte: u8) -> Option<Incoming> {
+        match self.state {
+            // `+` / `-` の応答確認は読み捨てる（再送はしない）
+            ReadState::Idle => match byte {
+                b'$' => {
+                    self.buf.clear();
+                    self.state = ReadState::Body;
+                }
+                0x03 => return Some(Incoming::Interrupt),
+                _ => {}
+            },
+            ReadState::Body => match byte {
+                b'#' => self.state = ReadState::Sum1,
+                _ => self.buf.push(byte),
+            },
+            ReadState::Sum1 => self.state = ReadState::Sum2(hex_digit(byte).unwrap_or(0xFF)),
+            ReadState::Sum2(hi) => {
+                self.state = ReadState::Idle;
+                let ok = hex_digit(byte).is_some_and(|lo| hi << 4 | lo == checksum(&self.buf));
+                return Some(if hi <= 0xF && ok {
+                    Incoming::Packet(core::mem::take(&mut self.buf))
+                } else {
+                    Incoming::BadChecksum
+                });
+            }
+        }
+        None
+    }
+}
+
+/// 応答本体を `$...#xx` に包む（`$ # } *` はエスケープする）
+pub fn encode(body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 4);
+    out.push(b'$');
+    for &b in body {
+        if matches!(b, b'$' | b'#' | b'}' | b'*') {
+            out.extend_from_slice(&[b'}', b ^ 0x20]);
+        } else {
+            out.push(b);
+        }
+    }
+    let sum = checksum(&out[1..]);
+    out.push(b'#');
+    out.extend_from_slice(format!("{:02x}", sum).as_bytes());
+    out
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |s, &b| s.wrapping_add(b))
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+fn parse_hex(s: &[u8]) -> Option<u32> {
+    if s.is_empty() || s.len() > 8 {
+        return None;
+    }
+    s.iter().try_fold(0u32, |v, &b| Some(v << 4 | hex_digit(b)? as u32))
+}
+
+fn parse_bytes(s: &[u8]) -> Option<Vec<u8>> {
+    s.chunks(2)
+        .map(|c| match c {
+            [hi, lo] => Some(hex_digit(*hi)? << 4 | hex_digit(*lo)?),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 32 ビット値をリトルエンディアンの 16 進 8 桁にする
+fn push_reg(out: &mut String, v: u32) {
+    for b in v.to_le_bytes() {
+        let _ = write!(out, "{:02x}", b);
+    }
+}
+
+fn parse_reg(s: &[u8]) -> Option<u32> {
+    let bytes: [u8; 4] = parse_bytes(s)?.try_into().ok()?;
+    Some(u32::from_le_bytes(bytes))
+}
+
+/// `addr,len` を分解する
+fn parse_range(s: &[u8]) -> Option<(u32, u32)> {
+    let comma = s.iter().position(|&b| b == b',')?;
+    Some((parse_hex(&s[..comma])?, parse_hex(&s[comma + 1..])?))
+}
+
+/// GDB に渡すターゲット記述
+pub fn target_xml() -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\"?><!DOCTYPE target SYSTEM \"gdb-target.dtd\">\
+         <target version=\"1.0\"><architecture>armv4t</architecture>\
+         <feature name=\"org.gnu.gdb.arm.core\">",
+    );
+    for r in 0..13 {
+        let _ = write!(xml, "<reg name=\"r{}\" bitsize=\"32\" type=\"uint32\"/>", r);
+    }
+    xml.push_str(
+        "<reg name=\"sp\" bitsize=\"32\" type=\"data_ptr\"/>\
+         <reg name=\"lr\" bitsize=\"32\"/>\
+         <reg name=\"pc\" bitsize=\"32\" type=\"code_ptr\"/>\
+         <reg name=\"cpsr\" bitsize=\"32\" regnum=\"25\"/></feature>\
+         <feature name=\"org.gnu.gdb.arm.banked\">",
+    );
+    let names = BANKED.iter().map(|b| b.0).chain(SPSRS.iter().map(|s| s.0));
+    for name in names {
+        let _ = write!(xml, "<reg name=\"{}\" bitsize=\"32\" group=\"banked\"/>", name);
+    }
+    xml.push_str("</feature></target>");
+    xml
+}
+
+/// パケット処理の結果、ホストが取る動作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// 応答パケットを返す（空文字列は「未対応」）
+    Reply(String),
+    /// 実行を再開した。停止すると [`GdbStub::run_frame`] が停止応答を返す
+    Resume,
+    /// `D`: `OK` を返して切断し、エミュレーションは続ける
+    Detach,
+    /// `k`: 応答せず切断し、エミュレーションを終える
+    Kill,
+}
+
+/// ブレークポイント/ウォッチポイントと実行状態を持つ RSP スタブ
+pub struct GdbStub {
+    breakpoints: Vec<u32>,
+    running: bool,
+    /// 再開直後の 1 命令は現在 PC のブレークポイントを無視する
+    resuming: bool,
+    last_stop: String,
+}
+
+impl Default for GdbStub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GdbStub {
+    /// 停止状態で始まる（接続直後の `?` には SIGTRAP を返す）
+    pub fn new() -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            running: false,
+            resuming: false,
+            last_stop: stop_reply(SIGTRAP, None, ""),
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        self.running
+    }
+
+    /// Ctrl-C による中断。停止応答を返す
+    pub fn interrupt(&mut self) -> String {
+        self.stop(SIGINT, None, "")
+    }
+
+    /// 切断時: ブレークポイントとウォッチポイントを外して実行を続ける状態にする
+    pub fn detach(&mut self, gba: &mut Gba) {
+        self.breakpoints.clear();
+        gba.bus.watchpoints.clear();
+        gba.bus.watch_hit = None;
+        self.running = true;
+    }
+
+    fn stop(&mut self, signal: u8, hit: Option<WatchHit>, kind: &str) -> String {
+        self.running = false;
+        self.last_stop = stop_reply(signal, hit, kind);
+        self.last_stop.clone()
+    }
+
+    fn resume(&mut self, gba: &mut Gba) {
+        self.running = true;
+        self.resuming = true;
+        gba.bus.watch_hit = None;
+    }
+
+    /// 実行中に 1 フレーム分進める。ブレーク/ウォッチで止まったら停止応答を返す。
+    pub fn run_frame(&mut self, gba: &mut Gba) -> Option<String> {
+        while self.running {
+            if !gba.cpu.halted && !self.resuming && self.breakpoints.contains(&gba.cpu.regs[15]) {
+                return Some(self.stop(SIGTRAP, None, ""));
+            }
+            self.resuming = false;
+            let frame_done = gba.step();
+            if let Some(reply) = self.check_watch(gba) {
+                return Some(reply);
+            }
+            if frame_done {
+                break;
+            }
+        }
+        None
+    }
+
+    /// 1 命令実行する。halt 中は割り込みで起きるまで（最長 1 フレーム）進める
+    fn single_step(&mut self, gba: &mut Gba) -> String {
+        gba.bus.watch_hit = None;
+        let mut cycles = 0;
+        loop {
+            let halted = gba.cpu.halted;
+            gba.step();
+            if let Some(reply) = self.check_watch(gba) {
+                return reply;
+            }
+            cycles += 16;
+            if !halted || cycles >= CYCLES_PER_FRAME {
+                return self.stop(SIGTRAP, None, "");
+            }
+        }
+    }
+
+    fn check_watch(&mut self, gba: &mut Gba) -> Option<String> {
+        let hit = gba.bus.watch_hit.take()?;
+        let w = gba.bus.watchpoints[hit.index];
+        let kind = match (w.read, w.write) {
+            (true, true) => "awatch",
+            (true, false) => "rwatch",
+            _ => "watch",
+        };
+        Some(self.stop(SIGTRAP, Some(hit), kind))
+    }
+
+    /// パケット本体を 1 つ処理する
+    pub fn handle(&mut self, gba: &mut Gba, packet: &[u8]) -> Action {
+        let Some((&cmd, args)) = packet.split_first() else {
+            return Action::Reply(String::new());
+        };
+        let reply = match cmd {
+            b'?' => self.last_stop.clone(),
+            b'g' => {
+                let mut out = String::new();
+                for n in (0..16).chain([REG_CPSR]) {
+                    push_reg(&mut out, read_reg(gba, n).unwrap_or(0));
+                }
+                out
+            }
+            b'G' => {
+                let regs = args.chunks(8).take(G_REGS);
+                for (n, chunk) in (0..16).chain([REG_CPSR]).zip(regs) {
+                    let Some(v) = parse_reg(chunk) else { return error() };
+                    write_reg(gba, n, v);
+                }
+                ok()
+            }
+            b'p' => match parse_hex(args).and_then(|n| read_reg(gba, n as usize)) {
+                Some(v) => {
+                    let mut out = String::new();
+                    push_reg(&mut out, v);
+                    out
+                }
+                None => return error(),
+            },
+            b'P' => {
+                let eq = args.iter().position(|&b| b == b'=');
+                let parsed =
+                    eq.and_then(|i| Some((parse_hex(&args[..i])?, parse_reg(&args[i + 1..])?)));
+                match parsed {
+                    Some((n, v)) if write_reg(gba, n as usize, v) => ok(),
+                    _ => return error(),
+                }
+            }
+            b'm' => {
+                // 長すぎる要求は先頭だけ返す（GDB は残りを読み直す）
+                let Some((addr, len)) = parse_range(args) else { return error() };
+                let mut out = String::new();
+                for i in 0..len.min(MAX_READ) {
+                    let _ = write!(out, "{:02x}", gba.bus.peek8(addr.wrapping_add(i)));
+                }
+                out
+            }
+            b'M' => {
+                let colon = args.iter().position(|&b| b == b':');
+                let range = colon.and_then(|i| parse_range(&args[..i]));
+                let data = colon.and_then(|i| parse_bytes(&args[i + 1..]));
+                let (Some((addr, len)), Some(data)) = (range, data) else { return error() };
+                if data.len() != len as usize {
+                    return error();
+                }
+                let watches = core::mem::take(&mut gba.bus.watchpoints);
+                for (i, &b) in data.iter().enumerate() {
+                    gba.bus.write8(addr.wrapping_add(i as u32), b);
+                }
+                gba.bus.watchpoints = watches;
+                ok()
+            }
+            b'c' | b's' => {
+                if let Some(addr) = parse_hex(args) {
+                    write_reg(gba, 15, addr);
+                }
+                if cmd == b's' {
+                    self.single_step(gba)
+                } else {
+                    self.resume(gba);
+                    return Action::Resume;
+                }
+            }
+            b'Z' | b'z' => match self.set_point(gba, cmd == b'Z', args) {
+                Some(true) => ok(),
+                Some(false) => return error(),
+                None => String::new(),
+            },
+            b'D' => return Action::Detach,
+            b'k' => return Action::Kill,
+            b'H' | b'T' => ok(),
+            b'q' => self.query(args),
+            b'v' => {
+                if args == b"Cont?" {
+                    String::from("vCont;c;C;s;S")
+                } else if let Some(actions) = args.strip_prefix(b"Cont;") {
+                    // スレッドは 1 つなので最初の動作だけを見る
+                    match actions.first() {
+                        Some(b's' | b'S') => self.single_step(gba),
+                        Some(b'c' | b'C') => {
+                            self.resume(gba);
+                            return Action::Resume;
+                        }
+                        _ => return error(),
+                    }
+                } else {
+                    String::new()
+                }
+            }
+            _ => String::new(),
+        };
+        Action::Reply(reply)
+    }
+
+    /// `Z`/`z` の `type,addr,kind`。未対応の種類は None
+    fn set_point(&mut self, gba: &mut Gba, insert: bool, args: &[u8]) -> Option<bool> {
+        let mut fields = args.split(|&b| b == b',');
+        let ty = fields.next()?;
+        let (Some(addr), Some(kind)) = (
+            fields.next().and_then(parse_hex),
+            fields.next().and_then(parse_hex),
+        ) else {
+            return Some(false);
+        };
+        let (read, write) = match ty {
+            b"0" | b"1" => {
+                if insert {
+                    if !self.breakpoints.contains(&addr) {
+                        self.breakpoints.push(addr);
+                    }
+                } else {
+                    self.breakpoints.retain(|&a| a != addr);
+                }
+                return Some(true);
+            }
+            b"2" => (false, true),
+            b"3" => (true, false),
+            b"4" => (true, true),
+            _ => return None,
+        };
+        let w = Watchpoint { addr, len: kind.max(1), read, write };
+        let watches = &mut gba.bus.watchpoints;
+        if insert {
+            watches.push(w);
+        } else if let Some(i) = watches.iter().position(|x| *x == w) {
+            watches.remove(i);
+            gba.bus.watch_hit = None;
+        }
+        Some(true)
+    }
+
+    fn query(&self, args: &[u8]) -> String {
+        if args.starts_with(b"Supported") {
+            format!("PacketSize={:x};qXfer:features:read+", PACKET_SIZE)
+        } else if let Some(range) = args.strip_prefix(b"Xfer:features:read:target.xml:") {
+            let Some((offset, len)) = parse_range(range) else { return String::from("E01") };
+            let xml = target_xml();
+            let start = (offset as usize).min(xml.len());
+            let end = start.saturating_add(len as usize).min(xml.len());
+            let more = if end < xml.len() { "m" } else { "l" };
+            format!("{}{}", more, &xml[start..end])
+        } else if args == b"Attached" {
+            String::from("1")
+        } else if args == b"C" {
+            String::from("QC1")
+        } else if args == b"fThreadInfo" {
+            String::from("m1")
+        } else if args == b"sThreadInfo" {
+            String::from("l")
+        } else {
+            String::new()
+        }
+    }
+}
+
+fn ok() -> String {
+    String::from("OK")
+}
+
+fn error() -> Action {
+    Action::Reply(String::from("E01"))
+}
+
+/// `T` 停止応答。ウォッチポイントなら種類とアクセスしたアドレスを付ける
+fn stop_reply(signal: u8, hit: Option<WatchHit>, kind: &str) -> String {
+    match hit {
+        Some(hit) => format!("T{:02x}{}:{:08x};thread:1;", signal, kind, hit.addr),
+        None => format!("T{:02x}thread:1;", signal),
+    }
+}
+
+/// GDB のレジスタ番号で読む。存在しない番号は None
+pub fn read_reg(gba: &Gba, n: usize) -> Option<u32> {
+    let cpu = &gba.cpu;
+    match n {
+        0..=15 => Some(cpu.regs[n]),
+        REG_CPSR => Some(cpu.cpsr),
+        REG_BANKED..REG_SPSR => {
+            let (_, mode, r) = BANKED[n - REG_BANKED];
+            Some(cpu.banked_reg(mode, r))
+        }
+        REG_SPSR..REG_END => cpu.spsr_of(SPSRS[n - REG_SPSR].1),
+        _ => None,
+    }
+}
+
+/// GDB のレジスタ番号で書く。存在しない番号なら false
+pub fn write_reg(gba: &mut Gba, n: usize, v: u32) -> bool {
+    let cpu = &mut gba.cpu;
+    match n {
+        15 => cpu.regs[15] = if cpu.thumb() { v & !1 } else { v & !3 },
+        0..=14 => cpu.regs[n] = v,
+        // モードビットが変わればバンクも切り替える
+        REG_CPSR => {
+            cpu.set_mode(v & 0x1F);
+            cpu.cpsr = v;
+        }
+        REG_BANKED..REG_SPSR => {
+            let (_, mode, r) = BANKED[n - REG_BANKED];
+            cpu.set_banked_reg(mode, r, v);
+        }
+        REG_SPSR..REG_END => cpu.set_spsr_of(SPSRS[n - REG_SPSR].1, v),
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::{FLAG_T, MODE_SYS};
+    use alloc::vec;
+
+    const IWRAM: u32 = 0x0300_0000;
+
+    fn packet(stub: &mut GdbStub, gba: &mut Gba, body: &str) -> String {
+        match stub.handle(gba, body.as_bytes()) {
+            Action::Reply(r) => r,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    /// MOV r0,#1 / STR r0,[r1] / B . を IWRAM に置く
+    fn setup() -> Gba {
+        let mut gba = Gba::new(vec![], None);
+        for (i, op) in [0xE3A0_0001u32, 0xE581_0000, 0xEAFF_FFFE].iter().enumerate() {
+            gba.bus.write32(IWRAM + i as u32 * 4, *op);
+        }
+        gba.cpu.regs[1] = 0x0200_0000;
+        gba.cpu.regs[15] = IWRAM;
+        gba
+    }
+
+    #[test]
+    fn frames_and_checks_packets() {
+        let mut reader = PacketReader::new();
+        let mut got = vec![];
+        for &b in b"+$g#67$m0,4#00\x03" {
+            got.extend(reader.push(b));
+        }
+        let expected = [Incoming::Packet(b"g".to_vec()), Incoming::BadChecksum, Incoming::Interrupt];
+        assert_eq!(got, expected);
+        assert_eq!(encode(b"OK"), b"$OK#9a");
+        assert_eq!(encode(b"}"), b"$}]#da");
+    }
+
+    #[test]
+    fn reads_and_writes_registers() {
+        let mut gba = setup();
+        let mut stub = GdbStub::new();
+        let g = packet(&mut stub, &mut gba, "g");
+        assert_eq!(g.len(), G_REGS * 8);
+        assert_eq!(&g[15 * 8..16 * 8], "00000003");
+        assert_eq!(packet(&mut stub, &mut gba, "p19"), "1f000000"); // cpsr = SYS
+
+        // バンクレジスタ: SVC の r13 は post-BIOS で 0x03007FE0
+        let n = REG_BANKED + BANKED.iter().position(|b| b.0 == "r13_svc").unwrap();
+        assert_eq!(packet(&mut stub, &mut gba, &format!("p{:x}", n)), "e07f0003");
+        packet(&mut stub, &mut gba, &format!("P{:x}=00010000", n + 1));
+        assert_eq!(gba.cpu.banked_reg(MODE_SVC, 14), 0x100);
+
+        // cpsr を SVC + Thumb に書くと r13 がバンク切り替えされる
+        packet(&mut stub, &mut gba, &format!("P19={:08x}", (MODE_SVC | FLAG_T).swap_bytes()));
+        assert!(gba.cpu.thumb());
+        assert_eq!(gba.cpu.regs[13], 0x0300_7FE0);
+        assert_eq!(gba.cpu.banked_reg(MODE_SYS, 13), 0x0300_7F00);
+        assert_eq!(packet(&mut stub, &mut gba, "p99"), "E01");
+    }
+
+    #[test]
+    fn memory_breakpoints_and_watchpoints() {
+        let mut gba = setup();
+        let mut stub = GdbStub::new();
+        assert_eq!(packet(&mut stub, &mut gba, "m3000000,4"), "0100a0e3");
+        assert_eq!(packet(&mut stub, &mut gba, "m2000000,ffffffff").len(), PACKET_SIZE);
+        assert_eq!(packet(&mut stub, &mut gba, "M2000010,2:beef"), "OK");
+        assert_eq!(gba.bus.read16(0x0200_0010), 0xEFBE);
+
+        assert_eq!(packet(&mut stub, &mut gba, "s"), "T05thread:1;");
+        assert_eq!(gba.cpu.regs[0], 1);
+
+        // 書き込みウォッチポイントで STR の直後に止まる
+        assert_eq!(packet(&mut stub, &mut gba, "Z2,2000000,4"), "OK");
+        assert_eq!(stub.handle(&mut gba, b"c"), Action::Resume);
+        assert_eq!(stub.run_frame(&mut gba).unwrap(), "T05watch:02000000;thread:1;");
+        assert_eq!(gba.cpu.regs[15], IWRAM + 8);
+        // GDB からの読み出しはウォッチポイントに掛からない
+        assert_eq!(packet(&mut stub, &mut gba, "Z3,3000000,4"), "OK");
+        assert_eq!(packet(&mut stub, &mut gba, "m3000000,4"), "0100a0e3");
+        assert_eq!(gba.bus.watch_hit, None);
+        assert_eq!(packet(&mut stub, &mut gba, "z3,3000000,4"), "OK");
+
+        // ブレークポイントは現在地から再開しても 1 周後にまた止まる
+        assert_eq!(packet(&mut stub, &mut gba, "z2,2000000,4"), "OK");
+        assert_eq!(packet(&mut stub, &mut gba, "Z0,3000008,4"), "OK");
+        stub.handle(&mut gba, b"c");
+        assert_eq!(stub.run_frame(&mut gba).unwrap(), "T05thread:1;");
+        assert_eq!(gba.cpu.regs[15], IWRAM + 8);
+        assert_eq!(packet(&mut stub, &mut gba, "Z9,0,0"), "");
+
+        stub.handle(&mut gba, b"c");
+        assert_eq!(stub.interrupt(), "T02thread:1;");
+        assert!(!stub.running());
+    }
+}
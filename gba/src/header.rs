@@ -0,0 +1,144 @@
+//! カートリッジヘッダ（0x00–0xBF）の解析と検証。
+//!
+//! BIOS が照合するのはロゴと補数チェック (0xBD) で、ここでは補数チェックと
+//! 固定値 (0xB2) だけを [`HeaderWarning`] として報告する。
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// ヘッダを含む最小の ROM 長
+pub const HEADER_END: usize = 0xC0;
+
+/// 起動できないヘッダ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderError {
+    /// ROM がヘッダ末尾 (0xC0) に届かない（実際の長さ）
+    TooSmall(usize),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::TooSmall(len) => write!(f, "ROM file too small ({} bytes)", len),
+        }
+    }
+}
+
+impl core::error::Error for HeaderError {}
+
+/// 動作はするが正規の ROM とは食い違う点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderWarning {
+    /// 0xB2 が固定値 0x96 でない
+    FixedValue(u8),
+    /// 補数チェック (0xBD) の不一致（実機の BIOS はここで止まる）
+    Complement { stored: u8, computed: u8 },
+}
+
+impl fmt::Display for HeaderWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            HeaderWarning::FixedValue(v) => write!(f, "fixed value 0x{:02X} (expected 0x96)", v),
+            HeaderWarning::Complement { stored, computed } => write!(
+                f,
+                "complement check mismatch (stored 0x{:02X}, computed 0x{:02X})",
+                stored, computed
+            ),
+        }
+    }
+}
+
+/// 解析済みのカートリッジヘッダ
+#[derive(Debug, Clone)]
+pub struct Header {
+    /// 0xA0–0xAB のタイトル（NUL 埋め）
+    title: [u8; 12],
+    /// 0xAC–0xAF のゲームコード（例: "AXVJ"）
+    pub game_code: [u8; 4],
+    /// 0xB0–0xB1 のメーカーコード（GB の新発売元コードと共通）
+    pub maker_code: [u8; 2],
+    /// 0xBC: ソフトのバージョン
+    pub version: u8,
+    /// 0xBD: 補数チェック
+    pub complement: u8,
+    warnings: Vec<HeaderWarning>,
+}
+
+impl Header {
+    /// ROM イメージからヘッダを解析し、固定値と補数チェックを検証する。
+    pub fn parse(rom: &[u8]) -> Result<Self, HeaderError> {
+        if rom.len() < HEADER_END {
+            return Err(HeaderError::TooSmall(rom.len()));
+        }
+        let mut warnings = Vec::new();
+        if rom[0xB2] != 0x96 {
+            warnings.push(HeaderWarning::FixedValue(rom[0xB2]));
+        }
+        let computed = complement(rom);
+        if computed != rom[0xBD] {
+            warnings.push(HeaderWarning::Complement { stored: rom[0xBD], computed });
+        }
+        let mut title = [0; 12];
+        title.copy_from_slice(&rom[0xA0..0xAC]);
+        Ok(Header {
+            title,
+            game_code: [rom[0xAC], rom[0xAD], rom[0xAE], rom[0xAF]],
+            maker_code: [rom[0xB0], rom[0xB1]],
+            version: rom[0xBC],
+            complement: rom[0xBD],
+            warnings,
+        })
+    }
+
+    /// タイトル（NUL 以降と ASCII でない部分を除く）
+    pub fn title(&self) -> &str {
+        ascii_prefix(&self.title)
+    }
+
+    /// ゲームコード（ASCII でない部分を除く）
+    pub fn game_code(&self) -> &str {
+        ascii_prefix(&self.game_code)
+    }
+
+    /// 検証で見つかった問題（無ければ空）
+    pub fn warnings(&self) -> &[HeaderWarning] {
+        &self.warnings
+    }
+}
+
+/// 0xA0–0xBC から計算した補数チェック
+pub fn complement(rom: &[u8]) -> u8 {
+    rom[0xA0..0xBD].iter().fold(0u8, |x, &b| x.wrapping_sub(b)).wrapping_sub(0x19)
+}
+
+fn ascii_prefix(bytes: &[u8]) -> &str {
+    let len = bytes.iter().position(|&b| b == 0 || !b.is_ascii()).unwrap_or(bytes.len());
+    // ASCII だけなので必ず UTF-8 として正しい
+    core::str::from_utf8(&bytes[..len]).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_title_codes_and_checks_complement() {
+        let mut rom = vec![0; 0x200];
+        rom[0xA0..0xA8].copy_from_slice(b"POKEMON ");
+        rom[0xAC..0xB0].copy_from_slice(b"AXVJ");
+        rom[0xB0..0xB2].copy_from_slice(b"01");
+        rom[0xB2] = 0x96;
+        rom[0xBD] = complement(&rom);
+        let header = Header::parse(&rom).unwrap();
+        assert_eq!(header.title(), "POKEMON ");
+        assert_eq!(header.game_code(), "AXVJ");
+        assert_eq!(&header.maker_code, b"01");
+        assert!(header.warnings().is_empty());
+
+        rom[0xBD] ^= 1;
+        rom[0xB2] = 0;
+        let header = Header::parse(&rom).unwrap();
+        assert_eq!(header.warnings().len(), 2);
+        assert_eq!(Header::parse(&rom[..0x80]).unwrap_err(), HeaderError::TooSmall(0x80));
+    }
+}
@@ -12,5 +12,7 @@ pub mod bus;
 pub mod cpu;
 pub mod dma;
 pub mod gba;
+pub mod gdb;
+pub mod header;
 pub mod ppu;
 pub mod timer;
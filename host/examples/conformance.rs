@@ -0,0 +1,86 @@
+//! 適合テスト ROM（blargg・mooneye・acid2）のヘッドレス一括実行。
+//!
+//! 使い方: cargo run --release -p gb-host --example conformance <dir> [manifest] [jobs]
+//!
+//! マニフェスト（省略時 `<dir>/manifest.txt`）の ROM を `jobs` 並列（省略時は CPU 数）で実行し、
+//! ROM ごとの結果とスイートごとの合格数を表示する。期待（`expect=`）と違う結果が
+//! 1 つでもあれば終了コード 1。書式は `gb_host::conformance` を参照。
+
+use gb_host::conformance::{self, Entry, Outcome, Suite};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let usage = "usage: conformance <dir> [manifest] [jobs]";
+    let dir = args.next().expect(usage);
+    let manifest = args.next().unwrap_or_else(|| format!("{}/manifest.txt", dir));
+    let jobs = args
+        .next()
+        .map(|s| s.parse().expect(usage))
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+
+    let text = std::fs::read_to_string(&manifest)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", manifest, e));
+    let entries = conformance::parse_manifest(&text, Path::new(&dir))
+        .unwrap_or_else(|e| panic!("{}: {}", manifest, e));
+
+    // 取り出した順に実行し、表示はマニフェストの順
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; entries.len()]);
+    thread::scope(|s| {
+        for _ in 0..jobs.max(1) {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(entry) = entries.get(i) else { break };
+                    let outcome = conformance::run(entry);
+                    results.lock().unwrap()[i] = Some(outcome);
+                }
+            });
+        }
+    });
+    let results: Vec<Outcome> = results.into_inner().unwrap().into_iter().flatten().collect();
+
+    let width = entries.iter().map(|e| rom_name(e, &dir).len()).max().unwrap_or(0);
+    let mut unexpected = 0;
+    for (entry, outcome) in entries.iter().zip(&results) {
+        let mark = if outcome.passed() == entry.expect_pass {
+            ""
+        } else {
+            unexpected += 1;
+            "  <- unexpected"
+        };
+        println!(
+            "{:<width$}  {:<7}  {}{}",
+            rom_name(entry, &dir),
+            entry.suite.name(),
+            outcome,
+            mark,
+        );
+    }
+
+    println!();
+    for suite in Suite::ALL {
+        let (total, passed) = entries
+            .iter()
+            .zip(&results)
+            .filter(|(e, _)| e.suite == suite)
+            .fold((0, 0), |(t, p), (_, o)| (t + 1, p + o.passed() as usize));
+        if total > 0 {
+            println!("{:<7}  {:>3}/{:<3} passed", suite.name(), passed, total);
+        }
+    }
+    if unexpected > 0 {
+        println!("{} unexpected result(s)", unexpected);
+        std::process::exit(1);
+    }
+}
+
+/// 表示用に `<dir>/` を除いたパス
+fn rom_name(entry: &Entry, dir: &str) -> String {
+    let rom = entry.rom.strip_prefix(dir).unwrap_or(&entry.rom);
+    rom.display().to_string()
+}
@@ -1,12 +1,16 @@
 //! 一時的な調査用ツール: ROM を高速実行し、PC/LCDC/CGB パレット等を定期的にダンプする。
 //! GBC ROM が白画面のまま固まる問題の切り分けに使用。
 //! 使い方: cargo run --example trace -- <rom_path> [max_frames]
+//! `<rom>.sym` があれば PC の領域名にラベルを添える。
 
 use gb_core::bootrom::Bootrom;
+use gb_core::cpu::disasm::disassemble;
+use gb_core::debugger::{Debugger, RunCommand};
 use gb_core::gameboy::GameBoy;
 use gb_core::input::NullInput;
 use gb_core::mmu::Mmu;
 use gb_core::platform::NullAudio;
+use gb_host::symbols::SymbolTable;
 use std::env;
 use std::path::Path;
 
@@ -55,32 +59,40 @@ fn main() {
     let cart = gb_host::cartridge::Cartridge::new(rom_path).expect("failed to load ROM");
     println!(
         "cart: title={:?} type={:?} cgb_flag=0x{:02X}",
-        cart.header().title,
+        cart.header().title(),
         cart.header().cartridge_type,
         cart.header().cgb_flag
     );
 
+    let sym_path = Path::new(rom_path).with_extension("sym");
+    let syms = if sym_path.exists() {
+        SymbolTable::load(&sym_path).expect("failed to load symbols")
+    } else {
+        SymbolTable::default()
+    };
+
     let mmu = Mmu::new(bootrom, cart);
     let mut gb = GameBoy::new(mmu, DumpDisplay, NullAudio, NullInput);
 
     if instr_mode {
-        let mut last_pc = u16::MAX;
-        let mut count = 0u64;
-        loop {
-            gb.step();
-            let (pc, halted, ime) = gb.debug_cpu();
-            if pc != last_pc {
-                last_pc = pc;
-                count += 1;
-                let (a, hl, sp) = gb.debug_regs();
-                println!(
-                    "#{count:5} pc=0x{pc:04X} a=0x{a:02X} hl=0x{hl:04X} sp=0x{sp:04X} halted={halted} ime={ime} region={}",
-                    region(pc)
-                );
-                if count >= max_instrs {
-                    break;
-                }
-            }
+        // 1 命令ずつ実行する（HALT 中は割り込みで次の命令を終えるまで進む）
+        let dbg = Debugger::new();
+        for count in 1..=max_instrs {
+            dbg.run(&mut gb, RunCommand::StepInstruction, u64::MAX);
+            let r = gb.registers();
+            let (pc, a, hl, sp) = (r.pc, r.a, r.hl(), r.sp);
+            let bytes: Vec<u8> = (0..3).map(|i| gb.mmu().read(pc.wrapping_add(i))).collect();
+            let location = match syms.name(gb.bank_at(pc), pc) {
+                Some(name) => format!("{}:{}", region(pc), name),
+                None => region(pc).to_string(),
+            };
+            println!(
+                "#{count:5} pc=0x{pc:04X} a=0x{a:02X} hl=0x{hl:04X} sp=0x{sp:04X} halted={} ime={} region={} {}",
+                r.halted,
+                r.ime,
+                location,
+                disassemble(pc, &bytes)
+            );
         }
         return;
     }
@@ -0,0 +1,450 @@
+//! ポケットカメラ（MAC-GBD マッパ + M64282FP イメージセンサ）。
+//!
+//! RAM バンクに 0x10 を書くと 0xA000–0xA07F にセンサのレジスタが現れる。A000 の bit0 に
+//! 1 を書くと撮影を開始し、露光時間に応じた時間の後に処理済みの 2bpp タイル (16x14) が
+//! RAM バンク 0 の 0x0100 から書き込まれる。
+//!
+//! センサの処理は次だけを再現する:
+//! - 露光時間 (A002/A003) に比例した明るさ（0x1000 で入力そのまま）
+//! - 2D エッジ強調（A001 の N と VH がすべて 1 のとき。強調比は A004 の bit4–6）
+//! - A006–A035 の 4x4 しきい値行列によるディザリング
+//!
+//! 画像は [`ImageSource`] から受け取るので、Web カメラが無くても静止画ファイルで撮影できる。
+
+use crate::cartridge::MemoryBankController;
+use gb_core::savestate::{StateReader, StateWriter};
+
+/// センサの有効画素数
+pub const SENSOR_WIDTH: usize = 128;
+pub const SENSOR_HEIGHT: usize = 112;
+pub const SENSOR_PIXELS: usize = SENSOR_WIDTH * SENSOR_HEIGHT;
+
+/// カメラの外部 RAM (128KB)
+const RAM_SIZE: usize = 0x20000;
+/// 撮影結果を書き込む RAM バンク 0 内のオフセット
+const IMAGE_OFFSET: usize = 0x0100;
+/// レジスタ数 (A000–A035)
+const REG_COUNT: usize = 0x36;
+/// ディザリング行列の先頭レジスタ
+const REG_DITHER: usize = 0x06;
+/// 2D エッジ強調の強調比（A004 の bit4–6）
+const EDGE_RATIOS: [f32; 8] = [0.5, 0.75, 1.0, 1.25, 2.0, 3.0, 4.0, 5.0];
+
+/// カメラへ画像を供給する。1 画素 1 バイトのグレースケール（0=黒, 255=白）。
+pub trait ImageSource {
+    /// 撮影時に呼ばれ、128x112 の画像を `out` に書く。
+    fn capture(&mut self, out: &mut [u8; SENSOR_PIXELS]);
+}
+
+/// 一様な灰色の画像（画像が指定されていないときの既定）
+pub struct BlankImageSource;
+
+impl ImageSource for BlankImageSource {
+    fn capture(&mut self, out: &mut [u8; SENSOR_PIXELS]) {
+        out.fill(0x80);
+    }
+}
+
+/// 静止画ファイル (PGM / PNG) を毎回そのまま返す。センササイズへは最近傍で拡大縮小する。
+pub struct FileImageSource {
+    image: Box<[u8; SENSOR_PIXELS]>,
+}
+
+impl FileImageSource {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::from_bytes(&data).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// ファイルの中身から生成する（先頭のマジックで PGM か PNG かを判定）
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let (width, height, pixels) = if data.starts_with(b"\x89PNG") {
+            crate::png::decode_gray(data)?
+        } else {
+            decode_pgm(data)?
+        };
+        if width == 0 || height == 0 {
+            return Err("image is empty".into());
+        }
+        let mut image = Box::new([0u8; SENSOR_PIXELS]);
+        for (i, out) in image.iter_mut().enumerate() {
+            let x = (i % SENSOR_WIDTH) * width as usize / SENSOR_WIDTH;
+            let y = (i / SENSOR_WIDTH) * height as usize / SENSOR_HEIGHT;
+            *out = pixels[y * width as usize + x];
+        }
+        Ok(Self { image })
+    }
+}
+
+impl ImageSource for FileImageSource {
+    fn capture(&mut self, out: &mut [u8; SENSOR_PIXELS]) {
+        out.copy_from_slice(&self.image[..]);
+    }
+}
+
+/// PGM（P5 バイナリ / P2 テキスト）を 8 bit グレースケールにデコードする。
+pub fn decode_pgm(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let binary = match data.get(..2) {
+        Some(b"P5") => true,
+        Some(b"P2") => false,
+        _ => return Err("PGM: bad magic".into()),
+    };
+    let mut pos = 2;
+    // ヘッダのトークン（空白区切り、# から行末まではコメント）
+    let mut token = || -> Result<u32, String> {
+        loop {
+            match data.get(pos) {
+                Some(b'#') => {
+                    while data.get(pos).is_some_and(|&c| c != b'\n') {
+                        pos += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => pos += 1,
+                Some(_) => break,
+                None => return Err("PGM: truncated header".into()),
+            }
+        }
+        let start = pos;
+        while data.get(pos).is_some_and(u8::is_ascii_digit) {
+            pos += 1;
+        }
+        std::str::from_utf8(&data[start..pos])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| "PGM: bad number".to_string())
+    };
+    let (width, height, maxval) = (token()?, token()?, token()?);
+    if maxval == 0 || maxval > 0xFFFF {
+        return Err(format!("PGM: bad maxval {}", maxval));
+    }
+    let count = width as usize * height as usize;
+    let scale = |v: u32| (v.min(maxval) * 255 / maxval) as u8;
+    let pixels = if binary {
+        // ヘッダ末尾の空白 1 文字の後がデータ
+        let body = data.get(pos + 1..).unwrap_or(&[]);
+        let wide = maxval > 0xFF;
+        let need = count * if wide { 2 } else { 1 };
+        if body.len() < need {
+            return Err("PGM: image data too short".into());
+        }
+        if wide {
+            let (samples, _) = body.as_chunks::<2>();
+            samples.iter().take(count).map(|&b| scale(u16::from_be_bytes(b) as u32)).collect()
+        } else {
+            body[..count].iter().map(|&b| scale(b as u32)).collect()
+        }
+    } else {
+        (0..count).map(|_| token().map(scale)).collect::<Result<_, _>>()?
+    };
+    Ok((width, height, pixels))
+}
+
+/// ポケットカメラのマッパ
+pub struct PocketCamera {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    rom_bank: u8,
+    /// RAM バンクレジスタ（bit4 が立っているとセンサのレジスタ）
+    ram_bank: u8,
+    ram_write_enabled: bool,
+    regs: [u8; REG_COUNT],
+    /// 撮影完了までの残り M-cycle（0 なら撮影中でない）
+    capture_cycles: u32,
+    source: Box<dyn ImageSource>,
+}
+
+impl PocketCamera {
+    pub fn new(rom: Vec<u8>, source: Box<dyn ImageSource>) -> Self {
+        Self {
+            rom,
+            ram: vec![0; RAM_SIZE],
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_write_enabled: false,
+            regs: [0; REG_COUNT],
+            capture_cycles: 0,
+            source,
+        }
+    }
+
+    pub fn set_image_source(&mut self, source: Box<dyn ImageSource>) {
+        self.source = source;
+    }
+
+    fn registers_mapped(&self) -> bool {
+        self.ram_bank & 0x10 != 0
+    }
+
+    fn ram_offset(&self, addr: u16) -> usize {
+        (self.ram_bank & 0x0F) as usize * 0x2000 + (addr as usize - 0xA000)
+    }
+
+    fn exposure(&self) -> u16 {
+        u16::from_be_bytes([self.regs[2], self.regs[3]])
+    }
+
+    /// 撮影開始から完了までの M-cycle 数（A001 の N が 0 なら 512 増える）
+    fn capture_duration(&self) -> u32 {
+        let n = self.regs[1] & 0x80 != 0;
+        32446 + if n { 0 } else { 512 } + 16 * self.exposure() as u32
+    }
+
+    /// センサ処理（露光・エッジ強調・ディザリング）をして 2bpp タイルを RAM へ書く
+    fn finish_capture(&mut self) {
+        let mut raw = [0u8; SENSOR_PIXELS];
+        self.source.capture(&mut raw);
+        let gain = self.exposure() as f32 / 0x1000 as f32;
+        let pixel = |x: isize, y: isize| {
+            let x = x.clamp(0, SENSOR_WIDTH as isize - 1) as usize;
+            let y = y.clamp(0, SENSOR_HEIGHT as isize - 1) as usize;
+            raw[y * SENSOR_WIDTH + x] as f32 * gain
+        };
+        let edge =
+            (self.regs[1] & 0xE0 == 0xE0).then(|| EDGE_RATIOS[(self.regs[4] >> 4) as usize & 7]);
+
+        let image = &mut self.ram[IMAGE_OFFSET..IMAGE_OFFSET + SENSOR_PIXELS / 4];
+        image.fill(0);
+        for y in 0..SENSOR_HEIGHT {
+            for x in 0..SENSOR_WIDTH {
+                let (xi, yi) = (x as isize, y as isize);
+                let mut v = pixel(xi, yi);
+                if let Some(ratio) = edge {
+                    let around = pixel(xi - 1, yi)
+                        + pixel(xi + 1, yi)
+                        + pixel(xi, yi - 1)
+                        + pixel(xi, yi + 1);
+                    v += (v * 4.0 - around) * ratio;
+                }
+                let t = REG_DITHER + ((y & 3) * 4 + (x & 3)) * 3;
+                let color = if v < self.regs[t] as f32 {
+                    3
+                } else if v < self.regs[t + 1] as f32 {
+                    2
+                } else if v < self.regs[t + 2] as f32 {
+                    1
+                } else {
+                    0
+                };
+                // タイル (16 タイル/行) 内の行ごとに下位プレーン・上位プレーンの 2 バイト
+                let tile = (y / 8) * (SENSOR_WIDTH / 8) + x / 8;
+                let row = tile * 16 + (y % 8) * 2;
+                let bit = 0x80 >> (x % 8);
+                if color & 1 != 0 {
+                    image[row] |= bit;
+                }
+                if color & 2 != 0 {
+                    image[row + 1] |= bit;
+                }
+            }
+        }
+        self.regs[0] &= !0x01;
+    }
+}
+
+impl MemoryBankController for PocketCamera {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
+            0x4000..=0x7FFF => {
+                let offset = (self.rom_bank as usize * 0x4000) + (addr as usize - 0x4000);
+                self.rom.get(offset).copied().unwrap_or(0xFF)
+            }
+            // 読めるのは A000（撮影中フラグなど）だけで、他のレジスタは 0x00
+            0xA000..=0xBFFF if self.registers_mapped() => {
+                if addr & 0x7F == 0 { self.regs[0] & 0x07 } else { 0x00 }
+            }
+            0xA000..=0xBFFF => self.ram[self.ram_offset(addr)],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> bool {
+        match addr {
+            0x0000..=0x1FFF => self.ram_write_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = value & 0x3F,
+            0x4000..=0x5FFF => self.ram_bank = value & 0x1F,
+            0xA000..=0xBFFF if self.registers_mapped() => match (addr & 0x7F) as usize {
+                0 => {
+                    let start = value & 0x01 != 0 && self.capture_cycles == 0;
+                    self.regs[0] = value & 0x07;
+                    if start {
+                        self.capture_cycles = self.capture_duration();
+                    } else if value & 0x01 == 0 {
+                        // 撮影中に 0 を書くと中止
+                        self.capture_cycles = 0;
+                    }
+                }
+                reg if reg < REG_COUNT => self.regs[reg] = value,
+                _ => {}
+            },
+            0xA000..=0xBFFF if self.ram_write_enabled => {
+                let offset = self.ram_offset(addr);
+                self.ram[offset] = value;
+                return true;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn tick(&mut self) {
+        if self.capture_cycles > 0 {
+            self.capture_cycles -= 1;
+            if self.capture_cycles == 0 {
+                self.finish_capture();
+            }
+        }
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.rom_bank);
+        w.u8(self.ram_bank);
+        w.bool(self.ram_write_enabled);
+        w.bytes(&self.regs);
+        w.u32(self.capture_cycles);
+        w.bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) {
+        self.rom_bank = r.u8();
+        self.ram_bank = r.u8();
+        self.ram_write_enabled = r.bool();
+        r.bytes(&mut self.regs);
+        self.capture_cycles = r.u32();
+        r.bytes(&mut self.ram);
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn rom_bank_at(&self, addr: u16) -> usize {
+        if addr < 0x4000 { 0 } else { self.rom_bank as usize }
+    }
+
+    fn ram_bank(&self) -> usize {
+        (self.ram_bank & 0x0F) as usize
+    }
+
+    fn camera_mut(&mut self) -> Option<&mut PocketCamera> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 左半分と右半分がそれぞれ一様な画像
+    struct HalfSource(u8, u8);
+
+    impl ImageSource for HalfSource {
+        fn capture(&mut self, out: &mut [u8; SENSOR_PIXELS]) {
+            for (i, p) in out.iter_mut().enumerate() {
+                *p = if i % SENSOR_WIDTH < SENSOR_WIDTH / 2 { self.0 } else { self.1 };
+            }
+        }
+    }
+
+    fn camera(left: u8, right: u8) -> PocketCamera {
+        let mut cam = PocketCamera::new(vec![0; 0x8000], Box::new(HalfSource(left, right)));
+        cam.write(0x4000, 0x10);
+        // 露光 1.0、エッジ強調なし、行列はすべて (0x40, 0x80, 0xC0)
+        cam.write(0xA002, 0x10);
+        cam.write(0xA003, 0x00);
+        for t in 0..16 {
+            for (i, v) in [0x40, 0x80, 0xC0].into_iter().enumerate() {
+                cam.write(0xA006 + t * 3 + i as u16, v);
+            }
+        }
+        cam
+    }
+
+    fn run_until_idle(cam: &mut PocketCamera) -> u32 {
+        let mut cycles = 0;
+        while cam.read(0xA000) & 0x01 != 0 {
+            cam.tick();
+            cycles += 1;
+        }
+        cycles
+    }
+
+    /// 先頭行のタイル `tile` の 1 行目（下位・上位プレーン）
+    fn tile_row(cam: &PocketCamera, tile: usize) -> [u8; 2] {
+        let i = IMAGE_OFFSET + tile * 16;
+        [cam.ram()[i], cam.ram()[i + 1]]
+    }
+
+    #[test]
+    fn capture_writes_dithered_tiles_after_delay() {
+        let mut cam = camera(0, 255);
+        cam.write(0xA000, 0x01);
+        assert_eq!(cam.read(0xA000), 0x01);
+        // 他のレジスタは読めない
+        assert_eq!(cam.read(0xA002), 0x00);
+        assert_eq!(run_until_idle(&mut cam), 32446 + 512 + 16 * 0x1000);
+
+        // 左端のタイルは黒 (3)、右端のタイルは白 (0)
+        cam.write(0x4000, 0x00);
+        assert_eq!([cam.read(0xA100), cam.read(0xA101)], [0xFF, 0xFF]);
+        assert_eq!([cam.read(0xA100 + 15 * 16), cam.read(0xA101 + 15 * 16)], [0x00, 0x00]);
+        // 最終行のタイルまで書かれる
+        assert_eq!(cam.ram()[IMAGE_OFFSET + 13 * 16 * 16 + 14], 0xFF);
+
+        // RAM 書き込みは 0x0A を書いてから
+        cam.write(0xA000, 0x12);
+        assert_eq!(cam.read(0xA000), 0x00);
+        cam.write(0x0000, 0x0A);
+        cam.write(0xA000, 0x12);
+        assert_eq!(cam.read(0xA000), 0x12);
+    }
+
+    #[test]
+    fn exposure_and_edge_enhancement() {
+        // 露光 0.5 なら白 (255) が 127.5 になり、しきい値 0x80 未満で 2
+        let mut cam = camera(0, 255);
+        cam.write(0xA002, 0x08);
+        cam.write(0xA000, 0x01);
+        run_until_idle(&mut cam);
+        assert_eq!(tile_row(&cam, 15), [0x00, 0xFF]);
+
+        // 0x60 は 2、0xA0 は 1
+        let mut cam = camera(0x60, 0xA0);
+        cam.write(0xA000, 0x01);
+        run_until_idle(&mut cam);
+        assert_eq!(tile_row(&cam, 7), [0x00, 0xFF]);
+        assert_eq!(tile_row(&cam, 8), [0xFF, 0x00]);
+
+        // 強調比 1 の 2D エッジ強調: 境界の暗い側は 0x20 (3)、明るい側は 0xE0 (0) になる
+        let mut cam = camera(0x60, 0xA0);
+        cam.write(0xA001, 0xE0);
+        cam.write(0xA004, 0x20);
+        cam.write(0xA000, 0x01);
+        run_until_idle(&mut cam);
+        assert_eq!(tile_row(&cam, 7), [0x01, 0xFF]);
+        assert_eq!(tile_row(&cam, 8), [0x7F, 0x00]);
+    }
+
+    #[test]
+    fn file_source_decodes_and_scales_pgm_and_png() {
+        // 2x1 の P2 (黒, 白) を 128x112 に拡大
+        let mut src = FileImageSource::from_bytes(b"P2\n# comment\n2 1\n15\n0 15\n").unwrap();
+        let mut out = [0; SENSOR_PIXELS];
+        src.capture(&mut out);
+        assert_eq!((out[0], out[63], out[64], out[SENSOR_PIXELS - 1]), (0, 0, 255, 255));
+
+        let mut p5 = b"P5 3 1 255\n".to_vec();
+        p5.extend_from_slice(&[10, 20, 30]);
+        assert_eq!(decode_pgm(&p5).unwrap(), (3, 1, vec![10, 20, 30]));
+        assert!(decode_pgm(b"P5 3 1 255\n\x01").is_err());
+
+        let png = crate::png::encode_gray(1, 2, &[200, 100]);
+        let mut src = FileImageSource::from_bytes(&png).unwrap();
+        src.capture(&mut out);
+        assert_eq!((out[0], out[SENSOR_PIXELS - 1]), (200, 100));
+    }
+}
@@ -1,367 +1,164 @@
+//! ROM ファイルからのカートリッジ生成と .sav の入出力。
+//!
+//! マッパ本体は teensy と共有する [`gb_core::cartridge`] にあり、ここでは `Vec` の記憶域と
+//! OS の時計を渡して使う。ポケットカメラだけは画像の供給元が要るので [`crate::camera`] にある。
+
+use crate::camera::{BlankImageSource, ImageSource, PocketCamera};
+pub use gb_core::cartridge::{CartridgeType, Header, HeaderError, Rtc};
+use gb_core::cartridge::{Clock, MbcCart};
 use gb_core::platform::CartridgeBus;
+use gb_core::savestate::{StateReader, StateWriter};
 use std::fs;
 
-#[derive(Debug, Clone, Copy)]
-pub enum CartridgeType {
-    RomOnly = 0x00,
-    Mbc1 = 0x01,
-    Mbc1Ram = 0x02,
-    Mbc1RamBattery = 0x03,
-    Mbc3 = 0x11,
-    Mbc3Ram = 0x12,
-    Mbc3RamBattery = 0x13,
-    Mbc3Timer = 0x0F,
-    Mbc3TimerRam = 0x10,
-    Mbc5 = 0x19,
-    Mbc5Ram = 0x1A,
-    Mbc5RamBattery = 0x1B,
-}
-
-impl From<u8> for CartridgeType {
-    fn from(value: u8) -> Self {
-        match value {
-            0x00 => CartridgeType::RomOnly,
-            0x01 => CartridgeType::Mbc1,
-            0x02 => CartridgeType::Mbc1Ram,
-            0x03 => CartridgeType::Mbc1RamBattery,
-            0x0F => CartridgeType::Mbc3Timer,
-            0x10 => CartridgeType::Mbc3TimerRam,
-            0x11 => CartridgeType::Mbc3,
-            0x12 => CartridgeType::Mbc3Ram,
-            0x13 => CartridgeType::Mbc3RamBattery,
-            0x19 => CartridgeType::Mbc5,
-            0x1A => CartridgeType::Mbc5Ram,
-            0x1B => CartridgeType::Mbc5RamBattery,
-            _ => CartridgeType::RomOnly,
-        }
-    }
-}
-
 pub trait MemoryBankController {
     fn read(&self, addr: u16) -> u8;
-    fn write(&mut self, addr: u16, value: u8);
+    /// 外部 RAM（RTC・EEPROM を含む）を書き換えたら true
+    fn write(&mut self, addr: u16, value: u8) -> bool;
+    /// セーブステート: バンクレジスタと外部 RAM（ROM 本体は含めない）
+    fn save_state(&self, _w: &mut StateWriter) {}
+    fn load_state(&mut self, _r: &mut StateReader) {}
+    /// 外部 RAM 全体（バッテリーバックアップの保存対象）
+    fn ram(&self) -> &[u8] {
+        &[]
+    }
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut []
+    }
+    /// RTC を持つ場合はその参照と時刻源の現在値
+    fn rtc_mut(&mut self) -> Option<(&mut Rtc, u64)> {
+        None
+    }
+    /// 傾きセンサへの入力（MBC7 のみ）
+    fn set_tilt(&mut self, _x: f32, _y: f32) {}
+    /// 毎 M-cycle 呼ばれる（ポケットカメラの撮影時間に使う）
+    fn tick(&mut self) {}
+    /// `addr` (0x0000–0x7FFF) に割り当て中の ROM バンク
+    fn rom_bank_at(&self, addr: u16) -> usize {
+        if addr < 0x4000 { 0 } else { 1 }
+    }
+    /// 0xA000–0xBFFF に割り当て中の RAM バンク
+    fn ram_bank(&self) -> usize {
+        0
+    }
+    /// ポケットカメラならその参照
+    fn camera_mut(&mut self) -> Option<&mut PocketCamera> {
+        None
+    }
 }
 
-pub struct RomOnly {
-    rom: Vec<u8>,
-}
+/// OS の壁時計
+pub struct SystemClock;
 
-impl RomOnly {
-    pub fn new(rom: Vec<u8>) -> Self {
-        Self { rom }
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
     }
 }
 
-impl MemoryBankController for RomOnly {
+/// gb-core の共有マッパ（ROM・外部 RAM はヒープに置く）
+type SharedMbc = MbcCart<Vec<u8>, Vec<u8>, SystemClock>;
+
+impl MemoryBankController for SharedMbc {
     fn read(&self, addr: u16) -> u8 {
-        if (addr as usize) < self.rom.len() {
-            self.rom[addr as usize]
-        } else {
-            0xFF
-        }
+        CartridgeBus::read(self, addr)
     }
 
-    fn write(&mut self, _addr: u16, _value: u8) {
-        // ROM only cartridges don't support writing
+    fn write(&mut self, addr: u16, value: u8) -> bool {
+        self.write_tracked(addr, value)
     }
-}
 
-pub struct Mbc1 {
-    rom: Vec<u8>,
-    ram: Vec<u8>,
-    rom_bank: u8,
-    ram_bank: u8,
-    ram_enabled: bool,
-    mode: bool, // false = ROM banking mode, true = RAM banking mode
-    rom_size: usize,
-    ram_size: usize,
-}
+    fn save_state(&self, w: &mut StateWriter) {
+        CartridgeBus::save_state(self, w);
+    }
 
-impl Mbc1 {
-    pub fn new(rom: Vec<u8>, ram_size: usize) -> Self {
-        let rom_size = rom.len();
-        Self {
-            rom,
-            ram: vec![0; ram_size],
-            rom_bank: 1,
-            ram_bank: 0,
-            ram_enabled: false,
-            mode: false,
-            rom_size,
-            ram_size,
-        }
+    fn load_state(&mut self, r: &mut StateReader) {
+        CartridgeBus::load_state(self, r);
     }
-}
 
-impl MemoryBankController for Mbc1 {
-    fn read(&self, addr: u16) -> u8 {
-        match addr {
-            0x0000..=0x3FFF => {
-                let bank = if self.mode && self.rom_size > 0x80000 {
-                    (self.ram_bank << 5) & 0x60
-                } else {
-                    0
-                };
-                let offset = (bank as usize * 0x4000) + addr as usize;
-                if offset < self.rom_size {
-                    self.rom[offset]
-                } else {
-                    0xFF
-                }
-            }
-            0x4000..=0x7FFF => {
-                let mut bank = self.rom_bank;
-                if self.mode && self.rom_size > 0x80000 {
-                    bank |= (self.ram_bank << 5) & 0x60;
-                }
-                if bank == 0 {
-                    bank = 1;
-                }
-                let offset = (bank as usize * 0x4000) + (addr as usize - 0x4000);
-                if offset < self.rom_size {
-                    self.rom[offset]
-                } else {
-                    0xFF
-                }
-            }
-            0xA000..=0xBFFF => {
-                if self.ram_enabled && self.ram_size > 0 {
-                    let bank = if self.mode { self.ram_bank } else { 0 };
-                    let offset = (bank as usize * 0x2000) + (addr as usize - 0xA000);
-                    if offset < self.ram_size {
-                        self.ram[offset]
-                    } else {
-                        0xFF
-                    }
-                } else {
-                    0xFF
-                }
-            }
-            _ => 0xFF,
-        }
+    fn ram(&self) -> &[u8] {
+        MbcCart::ram(self)
     }
 
-    fn write(&mut self, addr: u16, value: u8) {
-        match addr {
-            0x0000..=0x1FFF => {
-                self.ram_enabled = (value & 0x0F) == 0x0A;
-            }
-            0x2000..=0x3FFF => {
-                let bank = value & 0x1F;
-                self.rom_bank = if bank == 0 { 1 } else { bank };
-            }
-            0x4000..=0x5FFF => {
-                self.ram_bank = value & 0x03;
-            }
-            0x6000..=0x7FFF => {
-                self.mode = (value & 0x01) != 0;
-            }
-            0xA000..=0xBFFF => {
-                if self.ram_enabled && self.ram_size > 0 {
-                    let bank = if self.mode { self.ram_bank } else { 0 };
-                    let offset = (bank as usize * 0x2000) + (addr as usize - 0xA000);
-                    if offset < self.ram_size {
-                        self.ram[offset] = value;
-                    }
-                }
-            }
-            _ => {}
-        }
+    fn ram_mut(&mut self) -> &mut [u8] {
+        MbcCart::ram_mut(self)
     }
-}
 
-pub struct Mbc3 {
-    rom: Vec<u8>,
-    ram: Vec<u8>,
-    rom_bank: u8,
-    ram_bank: u8,
-    ram_enabled: bool,
-    rom_size: usize,
-    ram_size: usize,
-}
+    fn rtc_mut(&mut self) -> Option<(&mut Rtc, u64)> {
+        MbcCart::rtc_mut(self)
+    }
 
-impl Mbc3 {
-    pub fn new(rom: Vec<u8>, ram_size: usize) -> Self {
-        let rom_size = rom.len();
-        Self {
-            rom,
-            ram: vec![0; ram_size],
-            rom_bank: 1,
-            ram_bank: 0,
-            ram_enabled: false,
-            rom_size,
-            ram_size,
-        }
+    fn rom_bank_at(&self, addr: u16) -> usize {
+        CartridgeBus::rom_bank_at(self, addr)
     }
-}
 
-impl MemoryBankController for Mbc3 {
-    fn read(&self, addr: u16) -> u8 {
-        match addr {
-            0x0000..=0x3FFF => {
-                if (addr as usize) < self.rom_size { self.rom[addr as usize] } else { 0xFF }
-            }
-            0x4000..=0x7FFF => {
-                let bank = if self.rom_bank == 0 { 1 } else { self.rom_bank };
-                let offset = (bank as usize * 0x4000) + (addr as usize - 0x4000);
-                if offset < self.rom_size { self.rom[offset] } else { 0xFF }
-            }
-            0xA000..=0xBFFF => {
-                if !self.ram_enabled { return 0xFF; }
-                match self.ram_bank {
-                    0x00..=0x03 => {
-                        let offset = (self.ram_bank as usize * 0x2000) + (addr as usize - 0xA000);
-                        if offset < self.ram_size { self.ram[offset] } else { 0xFF }
-                    }
-                    0x08..=0x0C => 0x00, // RTC レジスタ（スタブ）
-                    _ => 0xFF,
-                }
-            }
-            _ => 0xFF,
-        }
+    fn ram_bank(&self) -> usize {
+        CartridgeBus::ram_bank(self)
     }
 
-    fn write(&mut self, addr: u16, value: u8) {
-        match addr {
-            0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
-            0x2000..=0x3FFF => {
-                let bank = value & 0x7F;
-                self.rom_bank = if bank == 0 { 1 } else { bank };
-            }
-            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
-            0x6000..=0x7FFF => {} // RTC ラッチ（未実装）
-            0xA000..=0xBFFF => {
-                if !self.ram_enabled { return; }
-                if self.ram_bank <= 0x03 {
-                    let offset = (self.ram_bank as usize * 0x2000) + (addr as usize - 0xA000);
-                    if offset < self.ram_size { self.ram[offset] = value; }
-                }
-            }
-            _ => {}
-        }
+    fn set_tilt(&mut self, x: f32, y: f32) {
+        CartridgeBus::set_tilt(self, x, y);
     }
 }
 
-pub struct Mbc5 {
-    rom: Vec<u8>,
-    ram: Vec<u8>,
-    rom_bank: u16,
-    ram_bank: u8,
-    ram_enabled: bool,
-    rom_size: usize,
-    ram_size: usize,
+pub struct Cartridge {
+    mbc: Box<dyn MemoryBankController>,
+    header: Header,
+    /// 前回の保存以降に外部 RAM / RTC が書き換えられたか（RAM 無効中の書き込みは数えない）
+    pub ram_dirty: bool,
 }
 
-impl Mbc5 {
-    pub fn new(rom: Vec<u8>, ram_size: usize) -> Self {
-        let rom_size = rom.len();
-        Self {
-            rom,
-            ram: vec![0; ram_size],
-            rom_bank: 1,
-            ram_bank: 0,
-            ram_enabled: false,
-            rom_size,
-            ram_size,
-        }
-    }
+/// ROM を読み込めなかった理由
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Header(HeaderError),
 }
 
-impl MemoryBankController for Mbc5 {
-    fn read(&self, addr: u16) -> u8 {
-        match addr {
-            0x0000..=0x3FFF => {
-                if (addr as usize) < self.rom_size { self.rom[addr as usize] } else { 0xFF }
-            }
-            0x4000..=0x7FFF => {
-                let offset = (self.rom_bank as usize * 0x4000) + (addr as usize - 0x4000);
-                if offset < self.rom_size { self.rom[offset] } else { 0xFF }
-            }
-            0xA000..=0xBFFF => {
-                if !self.ram_enabled || self.ram_size == 0 { return 0xFF; }
-                let offset = (self.ram_bank as usize * 0x2000) + (addr as usize - 0xA000);
-                if offset < self.ram_size { self.ram[offset] } else { 0xFF }
-            }
-            _ => 0xFF,
-        }
-    }
-
-    fn write(&mut self, addr: u16, value: u8) {
-        match addr {
-            0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
-            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | (value as u16),
-            0x3000..=0x3FFF => self.rom_bank = (self.rom_bank & 0x0FF) | (((value & 0x01) as u16) << 8),
-            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
-            0xA000..=0xBFFF => {
-                if !self.ram_enabled || self.ram_size == 0 { return; }
-                let offset = (self.ram_bank as usize * 0x2000) + (addr as usize - 0xA000);
-                if offset < self.ram_size { self.ram[offset] = value; }
-            }
-            _ => {}
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(e) => e.fmt(f),
+            LoadError::Header(e) => e.fmt(f),
         }
     }
 }
 
-pub struct Cartridge {
-    mbc: Box<dyn MemoryBankController>,
-    header: CartridgeHeader,
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> Self {
+        LoadError::Io(e)
+    }
 }
 
-#[derive(Debug)]
-pub struct CartridgeHeader {
-    pub title: String,
-    pub cartridge_type: CartridgeType,
-    pub rom_size: u8,
-    pub ram_size: u8,
-    /// ROM ヘッダ 0x0143: 0x80=CGB 対応、0xC0=CGB 専用、その他=DMG
-    pub cgb_flag: u8,
+impl From<HeaderError> for LoadError {
+    fn from(e: HeaderError) -> Self {
+        LoadError::Header(e)
+    }
 }
 
 impl Cartridge {
-    pub fn new(rom_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    /// ROM ファイルを読み込む。ヘッダの不一致は [`Header::warnings`] に残り、読み込みは続ける。
+    pub fn new(rom_path: &str) -> Result<Self, LoadError> {
         let rom = fs::read(rom_path)?;
-        
-        if rom.len() < 0x150 {
-            return Err("ROM file too small".into());
-        }
-
-        let header = CartridgeHeader {
-            // 0x0143 は CGB フラグのためタイトルは 0x134..0x143（15 バイト）まで
-            title: String::from_utf8_lossy(&rom[0x134..0x143])
-                .trim_end_matches('\0')
-                .to_string(),
-            cartridge_type: CartridgeType::from(rom[0x147]),
-            rom_size: rom[0x148],
-            ram_size: rom[0x149],
-            cgb_flag: rom[0x0143],
-        };
-
-        let ram_size = match header.ram_size {
-            0x00 => 0,
-            0x01 => 0x800,    // 2KB
-            0x02 => 0x2000,   // 8KB
-            0x03 => 0x8000,   // 32KB
-            0x04 => 0x20000,  // 128KB
-            0x05 => 0x10000,  // 64KB
-            _ => 0,
-        };
+        let header = Header::parse(&rom)?;
+        let ram_size = header.ram_bytes();
 
         let mbc: Box<dyn MemoryBankController> = match header.cartridge_type {
-            CartridgeType::RomOnly => Box::new(RomOnly::new(rom)),
-            CartridgeType::Mbc1 | CartridgeType::Mbc1Ram | CartridgeType::Mbc1RamBattery => {
-                Box::new(Mbc1::new(rom, ram_size))
-            }
-            CartridgeType::Mbc3
-            | CartridgeType::Mbc3Ram
-            | CartridgeType::Mbc3RamBattery
-            | CartridgeType::Mbc3Timer
-            | CartridgeType::Mbc3TimerRam => Box::new(Mbc3::new(rom, ram_size)),
-            CartridgeType::Mbc5 | CartridgeType::Mbc5Ram | CartridgeType::Mbc5RamBattery => {
-                Box::new(Mbc5::new(rom, ram_size))
-            }
+            // 画像は set_image_source で差し替えるまで一様な灰色
+            CartridgeType::PocketCamera => {
+                Box::new(PocketCamera::new(rom, Box::new(BlankImageSource)))
+            }
+            _ => Box::new(
+                SharedMbc::new(rom, vec![0; ram_size], SystemClock)
+                    .map_err(HeaderError::UnsupportedType)?,
+            ),
         };
 
-        Ok(Self { mbc, header })
+        Ok(Self { mbc, header, ram_dirty: false })
     }
 
     pub fn read(&self, addr: u16) -> u8 {
@@ -369,12 +166,41 @@ impl Cartridge {
     }
 
     pub fn write(&mut self, addr: u16, value: u8) {
-        self.mbc.write(addr, value);
+        CartridgeBus::write(self, addr, value);
     }
 
-    pub fn header(&self) -> &CartridgeHeader {
+    pub fn header(&self) -> &Header {
         &self.header
     }
+
+    /// バッテリーバックアップ付き（.sav に保存すべき）カートか
+    pub fn has_battery(&self) -> bool {
+        self.header.cartridge_type.has_battery()
+    }
+
+    /// ポケットカメラの画像の供給元を設定する。カメラでなければ false。
+    pub fn set_image_source(&mut self, source: Box<dyn ImageSource>) -> bool {
+        self.mbc.camera_mut().map(|cam| cam.set_image_source(source)).is_some()
+    }
+
+    /// .sav の内容（外部 RAM + MBC3 は RTC フッタ）
+    pub fn save_data(&mut self) -> Vec<u8> {
+        let mut data = self.mbc.ram().to_vec();
+        if let Some((rtc, now)) = self.mbc.rtc_mut() {
+            data.extend_from_slice(&rtc.to_footer(now));
+        }
+        data
+    }
+
+    /// .sav の内容を外部 RAM（と RTC）へ読み込む。RAM サイズと異なる部分は無視する。
+    pub fn load_save_data(&mut self, data: &[u8]) {
+        let ram = self.mbc.ram_mut();
+        let n = data.len().min(ram.len());
+        ram[..n].copy_from_slice(&data[..n]);
+        if let Some((rtc, _)) = self.mbc.rtc_mut() {
+            rtc.load_footer(&data[n..]);
+        }
+    }
 }
 
 impl CartridgeBus for Cartridge {
@@ -382,6 +208,26 @@ impl CartridgeBus for Cartridge {
         self.mbc.read(addr)
     }
     fn write(&mut self, addr: u16, val: u8) {
-        self.mbc.write(addr, val);
+        if self.mbc.write(addr, val) {
+            self.ram_dirty = true;
+        }
+    }
+    fn save_state(&self, w: &mut StateWriter) {
+        self.mbc.save_state(w);
+    }
+    fn load_state(&mut self, r: &mut StateReader) {
+        self.mbc.load_state(r);
     }
-}
\ No newline at end of file
+    fn set_tilt(&mut self, x: f32, y: f32) {
+        self.mbc.set_tilt(x, y);
+    }
+    fn tick(&mut self) {
+        self.mbc.tick();
+    }
+    fn rom_bank_at(&self, addr: u16) -> usize {
+        self.mbc.rom_bank_at(addr)
+    }
+    fn ram_bank(&self) -> usize {
+        self.mbc.ram_bank()
+    }
+}
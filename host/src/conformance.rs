@@ -0,0 +1,288 @@
+//! 適合テスト ROM（blargg・mooneye・dmg/cgb-acid2）をヘッドレスで実行して合否を判定する。
+//!
+//! マニフェストは 1 行 1 ROM で `ROM SUITE [timeout=秒] [ref=PNG] [expect=fail]`。
+//! パスはマニフェストのあるディレクトリからの相対、`#` 以降はコメント。
+//!
+//! - blargg: シリアル / 0xA000 の出力が "Passed" で終われば合格（[`TestSerial`]・[`TestHarness`] の判定）
+//! - mooneye: `LD B,B` の時点で B/C/D/E/H/L がフィボナッチ数 3/5/8/13/21/34 なら合格
+//! - acid2: `LD B,B` の後に完成したフレームを参照画像と比べる
+//!
+//! タイムアウトは通常速度の M-cycle 数で数える（ダブルスピード中は実時間の半分）。
+//!
+//! [`TestSerial`]: gb_core::serial::TestSerial
+//! [`TestHarness`]: gb_core::mmu::TestHarness
+
+use crate::cartridge::Cartridge;
+use crate::png;
+use gb_core::bootrom::Bootrom;
+use gb_core::debugger::CpuRegisters;
+use gb_core::gameboy::GameBoy;
+use gb_core::input::NullInput;
+use gb_core::mmu::Mmu;
+use gb_core::platform::{NullAudio, NullDisplay, NullSerial};
+use gb_core::ppu::{LCD_HEIGHT, LCD_WIDTH};
+use gb_core::serial::TestSerial;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 1 秒あたりの M-cycle 数
+const CYCLES_PER_SEC: u64 = 1_048_576;
+const DEFAULT_TIMEOUT_SECS: u32 = 30;
+/// `LD B,B`（mooneye / acid2 の終了の合図）
+const LD_B_B: u8 = 0x40;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Suite {
+    Blargg,
+    Mooneye,
+    Acid2,
+}
+
+impl Suite {
+    pub const ALL: [Suite; 3] = [Suite::Blargg, Suite::Mooneye, Suite::Acid2];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Suite::Blargg => "blargg",
+            Suite::Mooneye => "mooneye",
+            Suite::Acid2 => "acid2",
+        }
+    }
+}
+
+/// マニフェストの 1 行
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub rom: PathBuf,
+    pub suite: Suite,
+    pub timeout_secs: u32,
+    /// acid2 の参照画像（160×144 の PNG）
+    pub reference: Option<PathBuf>,
+    /// 既知の不合格なら false（結果が期待と違うものだけを問題として数える）
+    pub expect_pass: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    /// 不合格（理由: 最後の出力行・レジスタ・不一致ピクセル数）
+    Fail(String),
+    Timeout,
+    /// ROM や参照画像を読めなかった
+    Error(String),
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        *self == Outcome::Pass
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail(why) => write!(f, "FAIL ({})", why),
+            Outcome::Timeout => write!(f, "TIMEOUT"),
+            Outcome::Error(e) => write!(f, "ERROR ({})", e),
+        }
+    }
+}
+
+/// マニフェストを読む。ROM・参照画像のパスは `base` からの相対
+pub fn parse_manifest(text: &str, base: &Path) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let err = |msg: String| format!("line {}: {}", i + 1, msg);
+        let mut words = line.split('#').next().unwrap_or("").split_whitespace();
+        let Some(rom) = words.next() else { continue };
+        let suite = match words.next() {
+            Some("blargg") => Suite::Blargg,
+            Some("mooneye") => Suite::Mooneye,
+            Some("acid2") => Suite::Acid2,
+            other => return Err(err(format!("unknown suite {:?}", other))),
+        };
+        let mut entry = Entry {
+            rom: base.join(rom),
+            suite,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            reference: None,
+            expect_pass: true,
+        };
+        for word in words {
+            match word.split_once('=') {
+                Some(("timeout", secs)) => {
+                    entry.timeout_secs =
+                        secs.parse().map_err(|_| err(format!("bad timeout '{}'", secs)))?;
+                }
+                Some(("ref", path)) => entry.reference = Some(base.join(path)),
+                Some(("expect", "pass")) => entry.expect_pass = true,
+                Some(("expect", "fail")) => entry.expect_pass = false,
+                _ => return Err(err(format!("unknown option '{}'", word))),
+            }
+        }
+        if suite == Suite::Acid2 && entry.reference.is_none() {
+            return Err(err("acid2 needs ref=PNG".into()));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// 1 つの ROM を BootROM なしで実行して判定する
+pub fn run(entry: &Entry) -> Outcome {
+    let reference = match &entry.reference {
+        Some(path) => match load_reference(path) {
+            Ok(pixels) => Some(pixels),
+            Err(e) => return Outcome::Error(format!("{}: {}", path.display(), e)),
+        },
+        None => None,
+    };
+    let cart = match Cartridge::new(&entry.rom.to_string_lossy()) {
+        Ok(cart) => cart,
+        Err(e) => return Outcome::Error(e.to_string()),
+    };
+    let mmu = Mmu::new(Bootrom::disabled(), cart);
+    let serial = TestSerial::new(NullSerial);
+    let mut gb = GameBoy::with_serial(mmu, NullDisplay, NullAudio, NullInput, serial);
+
+    let mut breakpoint = false;
+    for _ in 0..entry.timeout_secs as u64 * CYCLES_PER_SEC {
+        let result = gb.step();
+        if entry.suite == Suite::Blargg {
+            if gb.mmu().test.test_done || gb.serial().finished() {
+                return blargg_outcome(&gb.serial().log, &gb.mmu().test.ram_text_buf);
+            }
+            continue;
+        }
+        // 命令の直後なら PC は次の命令を指している
+        if result.instruction_done && gb.mmu().read(gb.registers().pc) == LD_B_B {
+            if entry.suite == Suite::Mooneye {
+                return mooneye_outcome(&gb.registers());
+            }
+            breakpoint = true;
+        }
+        if breakpoint
+            && result.frame_ready
+            && let Some(reference) = &reference
+        {
+            return match screen_mismatches(gb.mmu().ppu.pixel_buffer(), reference) {
+                0 => Outcome::Pass,
+                n => Outcome::Fail(format!("{} pixels differ", n)),
+            };
+        }
+    }
+    Outcome::Timeout
+}
+
+fn blargg_outcome(serial: &[u8], ram_text: &[u8]) -> Outcome {
+    let text = String::from_utf8_lossy(if serial.is_empty() { ram_text } else { serial });
+    if text.contains("Passed") && !text.contains("Failed") {
+        return Outcome::Pass;
+    }
+    let last = text.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("");
+    Outcome::Fail(last.trim().to_string())
+}
+
+fn mooneye_outcome(r: &CpuRegisters) -> Outcome {
+    let regs = [r.b, r.c, r.d, r.e, r.h, r.l];
+    if regs == [3, 5, 8, 13, 21, 34] {
+        return Outcome::Pass;
+    }
+    Outcome::Fail(format!(
+        "B={:02X} C={:02X} D={:02X} E={:02X} H={:02X} L={:02X}",
+        r.b, r.c, r.d, r.e, r.h, r.l
+    ))
+}
+
+fn load_reference(path: &Path) -> Result<Vec<[u8; 3]>, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let (width, height, pixels) = png::decode_rgb(&data)?;
+    if (width as usize, height as usize) != (LCD_WIDTH, LCD_HEIGHT) {
+        return Err(format!("expected {}x{}, got {}x{}", LCD_WIDTH, LCD_HEIGHT, width, height));
+    }
+    Ok(pixels)
+}
+
+/// 画面 (RGB555) と参照画像の違うピクセル数。パレットの違いは問わず、
+/// 画面の色と参照画像の色が 1 対 1 に対応するかで比べる（DMG の色合いに依存しない）。
+pub fn screen_mismatches(frame: &[u16], reference: &[[u8; 3]]) -> usize {
+    let mut to_ref = HashMap::new();
+    let mut from_ref = HashMap::new();
+    frame
+        .iter()
+        .zip(reference)
+        .filter(|&(&color, &rgb)| {
+            let mapped = *to_ref.entry(color).or_insert(rgb);
+            let back = *from_ref.entry(rgb).or_insert(color);
+            mapped != rgb || back != color
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_manifest() {
+        let text = "\
+# ROM                 suite    options
+cpu_instrs.gb         blargg   timeout=60
+acceptance/di_timing-GS.gb mooneye expect=fail   # 既知の不合格
+
+dmg-acid2.gb          acid2    ref=dmg-acid2.png
+";
+        let entries = parse_manifest(text, Path::new("roms")).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].rom, Path::new("roms/cpu_instrs.gb"));
+        assert_eq!((entries[0].suite, entries[0].timeout_secs), (Suite::Blargg, 60));
+        assert!(!entries[1].expect_pass);
+        assert_eq!(entries[1].timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(entries[2].reference.as_deref(), Some(Path::new("roms/dmg-acid2.png")));
+
+        let err = parse_manifest("a.gb wario", Path::new("")).unwrap_err();
+        assert_eq!(err, "line 1: unknown suite Some(\"wario\")");
+        assert!(parse_manifest("a.gb acid2", Path::new("")).is_err());
+        assert!(parse_manifest("a.gb blargg timeout=x", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn compares_screens_up_to_palette() {
+        let (white, black) = ([0xFF; 3], [0; 3]);
+        assert_eq!(screen_mismatches(&[1, 2, 1, 2], &[white, black, white, black]), 0);
+        assert_eq!(screen_mismatches(&[1, 2, 1, 1], &[white, black, white, black]), 1);
+        // 2 色を 1 色に潰したものも不一致
+        assert_eq!(screen_mismatches(&[1, 1, 1, 1], &[white, black, white, black]), 2);
+    }
+
+    #[test]
+    fn detects_mooneye_signature() {
+        // LD B,3 / LD C,5 / LD D,8 / LD E,13 / LD H,21 / LD L,34 / LD B,B / JR -2
+        let mut rom = vec![0u8; 0x8000];
+        let code = [
+            0x06, 3, 0x0E, 5, 0x16, 8, 0x1E, 13, 0x26, 21, 0x2E, 34, 0x40, 0x18, 0xFE,
+        ];
+        rom[0x100..0x100 + code.len()].copy_from_slice(&code);
+        let path = std::env::temp_dir().join(format!("gb-conformance-{}.gb", std::process::id()));
+        std::fs::write(&path, &rom).unwrap();
+        let mut entry = Entry {
+            rom: path.clone(),
+            suite: Suite::Mooneye,
+            timeout_secs: 1,
+            reference: None,
+            expect_pass: true,
+        };
+        assert_eq!(run(&entry), Outcome::Pass);
+
+        // 0x42 で埋めるのは mooneye の不合格の合図
+        rom[0x101] = 0x42;
+        std::fs::write(&path, &rom).unwrap();
+        assert!(matches!(run(&entry), Outcome::Fail(why) if why.starts_with("B=42 C=05")));
+
+        entry.suite = Suite::Blargg;
+        assert_eq!(run(&entry), Outcome::Timeout);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
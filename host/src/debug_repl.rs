@@ -0,0 +1,433 @@
+//! `--debug`: gb-core のデバッガを標準入力のコマンドで操作する REPL。
+//!
+//! 数値は 16 進（`0x` / `$` は省略可）、回数だけは 10 進。空行は直前のコマンドを繰り返す。
+//! シンボルファイルがあればアドレスの代わりにラベル名も使え、表示にもラベルを添える。
+
+use gb_core::debugger::{
+    Access, Breakpoint, CmpOp, Condition, CpuRegisters, Debugger, Reg, RunCommand, StopReason,
+    Watchpoint,
+};
+use gb_core::cpu::disasm::disassemble;
+use gb_core::gameboy::GameBoy;
+use gb_core::input::InputSource;
+use gb_core::platform::{AudioSink, CartridgeBus, Display, SerialPort};
+use gb_host::symbols::{self, SymbolTable};
+use std::io::{BufRead, Write};
+
+/// 1 回の run で進める M-cycle 数の上限（約 1 秒）。超えたら Limit として一度戻る
+const RUN_SLICE: u64 = 1 << 20;
+
+const HELP: &str = "\
+commands:
+  s | step [N]              execute N instructions (default 1)
+  n | next                  step over CALL / RST
+  out | finish              run until the current subroutine returns
+  f | frame [N]             run until N frames complete (default 1)
+  c | continue              run until a breakpoint / watchpoint / condition
+  b | break [BANK:]ADDR|LABEL [if REG OP VALUE]
+                            PC breakpoint (optionally bank-qualified / conditional;
+                            a label in a switchable bank only stops in that bank)
+  w | watch r|w|rw|x START[-END]
+                            watch reads / writes / execution in an address range
+  when REG OP VALUE         stop when the condition becomes true (OP: == != < <= > >=)
+  l | list                  list breakpoints, watchpoints and conditions
+  d | delete b|w|when N     delete an entry by its number
+  r | regs                  show registers
+  x ADDR|LABEL [LEN]        dump memory (LEN in hex, default 0x40)
+  u | dis [ADDR|LABEL] [N]  disassemble N instructions (default: PC, 10)
+  q | quit                  exit";
+
+/// REPL を標準入力が閉じるか `quit` まで回す
+pub fn run<C, D, A, I, S>(gb: &mut GameBoy<C, D, A, I, S>, syms: &SymbolTable)
+where
+    C: CartridgeBus,
+    D: Display,
+    A: AudioSink,
+    I: InputSource,
+    S: SerialPort,
+{
+    let mut dbg = Debugger::new();
+    let stdin = std::io::stdin();
+    let mut last = String::new();
+    println!("Debugger ready. Type 'help' for commands.");
+    print_regs(gb, syms);
+    loop {
+        print!("(gb) ");
+        let _ = std::io::stdout().flush();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let line = match line.trim() {
+            "" => last.clone(),
+            l => l.to_string(),
+        };
+        last.clone_from(&line);
+        match execute(gb, &mut dbg, syms, &line) {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
+
+/// 1 行のコマンドを実行する。終了要求なら true
+fn execute<C, D, A, I, S>(
+    gb: &mut GameBoy<C, D, A, I, S>,
+    dbg: &mut Debugger,
+    syms: &SymbolTable,
+    line: &str,
+) -> Result<bool, String>
+where
+    C: CartridgeBus,
+    D: Display,
+    A: AudioSink,
+    I: InputSource,
+    S: SerialPort,
+{
+    let mut words = line.split_whitespace();
+    let Some(cmd) = words.next() else { return Ok(false) };
+    let args: Vec<&str> = words.collect();
+    match cmd {
+        "s" | "step" => {
+            let n = count(args.first())?;
+            return resume(gb, dbg, syms, RunCommand::StepInstruction, n);
+        }
+        "n" | "next" => return resume(gb, dbg, syms, RunCommand::StepOver, 1),
+        "out" | "finish" => return resume(gb, dbg, syms, RunCommand::StepOut, 1),
+        "f" | "frame" => {
+            let n = count(args.first())?;
+            return resume(gb, dbg, syms, RunCommand::RunToFrame, n);
+        }
+        "c" | "continue" => return resume(gb, dbg, syms, RunCommand::Continue, 1),
+        "b" | "break" => {
+            let bp = parse_breakpoint(&args, syms)?;
+            let n = dbg.add_breakpoint(bp).ok_or("too many breakpoints")?;
+            println!("breakpoint #{}: {}", n, describe_breakpoint(&bp, syms));
+        }
+        "w" | "watch" => {
+            let wp = parse_watchpoint(&args)?;
+            let n = dbg.add_watchpoint(wp).ok_or("too many watchpoints")?;
+            println!("watchpoint #{}: {}", n, describe_watchpoint(&wp));
+        }
+        "when" => {
+            let cond = parse_condition(&args)?;
+            let n = dbg.add_condition(cond).ok_or("too many conditions")?;
+            println!("condition #{}: {}", n, cond);
+        }
+        "l" | "list" => list(dbg, syms),
+        "d" | "delete" => {
+            let [kind, n] = args[..] else { return Err("usage: delete b|w|when N".into()) };
+            let n: usize = n.parse().map_err(|_| format!("bad number '{}'", n))?;
+            let removed = match kind {
+                "b" => dbg.remove_breakpoint(n).is_some(),
+                "w" => dbg.remove_watchpoint(n).is_some(),
+                "when" => dbg.remove_condition(n).is_some(),
+                _ => return Err(format!("unknown kind '{}'", kind)),
+            };
+            if !removed {
+                return Err(format!("no entry #{}", n));
+            }
+        }
+        "r" | "regs" => print_regs(gb, syms),
+        "x" => {
+            let addr = parse_addr(args.first().ok_or("usage: x ADDR [LEN]")?, syms)?;
+            let len = args.get(1).map(|s| parse_hex(s)).transpose()?.unwrap_or(0x40);
+            dump(gb, addr, len);
+        }
+        "u" | "dis" => {
+            let addr = match args.first() {
+                Some(s) => parse_addr(s, syms)?,
+                None => gb.registers().pc,
+            };
+            let n = if args.len() > 1 { count(args.get(1))? } else { 10 };
+            disasm(gb, syms, addr, n);
+        }
+        "h" | "help" => println!("{}", HELP),
+        "q" | "quit" => return Ok(true),
+        _ => return Err(format!("unknown command '{}' (try 'help')", cmd)),
+    }
+    Ok(false)
+}
+
+/// `cmd` を `times` 回実行し、止まった理由とレジスタを表示する。入力の quit なら true
+fn resume<C, D, A, I, S>(
+    gb: &mut GameBoy<C, D, A, I, S>,
+    dbg: &Debugger,
+    syms: &SymbolTable,
+    cmd: RunCommand,
+    times: u32,
+) -> Result<bool, String>
+where
+    C: CartridgeBus,
+    D: Display,
+    A: AudioSink,
+    I: InputSource,
+    S: SerialPort,
+{
+    let mut reason = StopReason::Step;
+    for _ in 0..times {
+        reason = loop {
+            match dbg.run(gb, cmd, RUN_SLICE) {
+                // ステップ以外は止まるまで回し続ける。抜けるのはウィンドウを閉じたとき（SDL が
+                // SIGINT も終了要求にするので Ctrl-C も同じ）で、プロンプトには戻らず終了する
+                StopReason::Limit if cmd != RunCommand::StepInstruction => continue,
+                r => break r,
+            }
+        };
+        if !matches!(reason, StopReason::Step | StopReason::Frame) {
+            break;
+        }
+    }
+    match reason {
+        StopReason::Step => {}
+        StopReason::Frame => println!("frame complete"),
+        StopReason::Breakpoint { index } => {
+            let bp = &dbg.breakpoints()[index];
+            println!("breakpoint #{}: {}", index, describe_breakpoint(bp, syms));
+        }
+        StopReason::Watchpoint(hit) => {
+            let access = match hit.access {
+                Access::Read => "read",
+                Access::Write => "write",
+                Access::Execute => "execute",
+            };
+            println!(
+                "watchpoint #{}: {} 0x{:04X}{} = 0x{:02X}",
+                hit.index,
+                access,
+                hit.addr,
+                label_suffix(syms, gb.bank_at(hit.addr), hit.addr),
+                hit.value
+            );
+        }
+        StopReason::Condition { index } => {
+            println!("condition #{}: {}", index, dbg.conditions()[index])
+        }
+        StopReason::Quit => return Ok(true),
+        StopReason::Limit => println!("CPU is halted (no instruction completed)"),
+    }
+    print_regs(gb, syms);
+    Ok(false)
+}
+
+fn list(dbg: &Debugger, syms: &SymbolTable) {
+    for (i, bp) in dbg.breakpoints().iter().enumerate() {
+        println!("b #{}: {}", i, describe_breakpoint(bp, syms));
+    }
+    for (i, wp) in dbg.watchpoints().iter().enumerate() {
+        println!("w #{}: {}", i, describe_watchpoint(wp));
+    }
+    for (i, cond) in dbg.conditions().iter().enumerate() {
+        println!("when #{}: {}", i, cond);
+    }
+}
+
+fn describe_breakpoint(bp: &Breakpoint, syms: &SymbolTable) -> String {
+    let mut s = match bp.bank {
+        Some(bank) => format!("{:02X}:{:04X}", bank, bp.addr),
+        None => format!("{:04X}", bp.addr),
+    };
+    s += &label_suffix(syms, bp.bank.unwrap_or(0), bp.addr);
+    if let Some(cond) = bp.condition {
+        s += &format!(" if {}", cond);
+    }
+    s
+}
+
+fn describe_watchpoint(wp: &Watchpoint) -> String {
+    let kinds = [(wp.read, 'r'), (wp.write, 'w'), (wp.execute, 'x')];
+    let kind: String = kinds.iter().filter(|(on, _)| *on).map(|(_, c)| *c).collect();
+    format!("{} {:04X}-{:04X}", kind, wp.start, wp.end)
+}
+
+/// ` <Label+$off>`（ラベルがなければ空）
+fn label_suffix(syms: &SymbolTable, bank: usize, addr: u16) -> String {
+    syms.name(bank, addr).map(|name| format!(" <{}>", name)).unwrap_or_default()
+}
+
+fn print_regs<C, D, A, I, S>(gb: &GameBoy<C, D, A, I, S>, syms: &SymbolTable)
+where
+    C: CartridgeBus,
+    D: Display,
+    A: AudioSink,
+    I: InputSource,
+    S: SerialPort,
+{
+    let r: CpuRegisters = gb.registers();
+    let flags = r.flags();
+    let flag = |on: bool, c: char| if on { c } else { '-' };
+    println!(
+        "AF={:04X} BC={:04X} DE={:04X} HL={:04X} SP={:04X} PC={:02X}:{:04X}{} [{}{}{}{}] IME={}{}",
+        r.af(),
+        r.bc(),
+        r.de(),
+        r.hl(),
+        r.sp,
+        gb.bank_at(r.pc),
+        r.pc,
+        label_suffix(syms, gb.bank_at(r.pc), r.pc),
+        flag(flags.z, 'Z'),
+        flag(flags.n, 'N'),
+        flag(flags.h, 'H'),
+        flag(flags.c, 'C'),
+        r.ime as u8,
+        if r.halted { " HALT" } else { "" }
+    );
+    disasm(gb, syms, r.pc, 1);
+}
+
+/// `addr` から `n` 命令を逆アセンブルして表示する
+fn disasm<C, D, A, I, S>(
+    gb: &GameBoy<C, D, A, I, S>,
+    syms: &SymbolTable,
+    mut addr: u16,
+    n: u32,
+) where
+    C: CartridgeBus,
+    D: Display,
+    A: AudioSink,
+    I: InputSource,
+    S: SerialPort,
+{
+    for _ in 0..n {
+        let bytes: Vec<u8> = (0..3).map(|i| gb.mmu().read(addr.wrapping_add(i))).collect();
+        let ins = disassemble(addr, &bytes);
+        let bank = gb.bank_at(addr);
+        if let Some(label) = syms.label_at(bank, addr) {
+            println!("{}:", label);
+        }
+        let target = ins.target().and_then(|t| syms.name(gb.bank_at(t), t));
+        println!("{:02X}:{}", bank, crate::rom_disasm::line(&ins, target.as_deref()));
+        addr = ins.next_addr();
+    }
+}
+
+fn dump<C, D, A, I, S>(gb: &GameBoy<C, D, A, I, S>, addr: u16, len: u16)
+where
+    C: CartridgeBus,
+    D: Display,
+    A: AudioSink,
+    I: InputSource,
+    S: SerialPort,
+{
+    for row in (0..len).step_by(16) {
+        let start = addr.wrapping_add(row);
+        let bytes: Vec<String> = (0..16.min(len - row))
+            .map(|i| format!("{:02X}", gb.mmu().read(start.wrapping_add(i))))
+            .collect();
+        println!("{:04X}: {}", start, bytes.join(" "));
+    }
+}
+
+fn count(arg: Option<&&str>) -> Result<u32, String> {
+    match arg {
+        None => Ok(1),
+        Some(s) => s.parse().map_err(|_| format!("bad count '{}'", s)),
+    }
+}
+
+fn parse_hex(s: &str) -> Result<u16, String> {
+    let digits = s.trim_start_matches("0x").trim_start_matches('$');
+    u16::from_str_radix(digits, 16).map_err(|_| format!("bad hex number '{}'", s))
+}
+
+/// ラベル名か 16 進のアドレス（ラベルを優先する）
+fn parse_addr(s: &str, syms: &SymbolTable) -> Result<u16, String> {
+    match syms.resolve(s) {
+        Some((_, addr)) => Ok(addr),
+        None => parse_hex(s),
+    }
+}
+
+/// `[BANK:]ADDR|LABEL [if REG OP VALUE]`。切替バンク内のラベルはそのバンク限定にする
+fn parse_breakpoint(args: &[&str], syms: &SymbolTable) -> Result<Breakpoint, String> {
+    let usage = "usage: break [BANK:]ADDR|LABEL [if REG OP VALUE]";
+    let (target, rest) = args.split_first().ok_or(usage)?;
+    let mut bp = match (syms.resolve(target), target.split_once(':')) {
+        (Some((bank, addr)), _) => Breakpoint {
+            bank: symbols::is_banked(addr).then_some(bank),
+            ..Breakpoint::new(addr)
+        },
+        (None, Some((bank, addr))) => Breakpoint {
+            bank: Some(parse_hex(bank)? as usize),
+            ..Breakpoint::new(parse_hex(addr)?)
+        },
+        (None, None) => Breakpoint::new(parse_hex(target)?),
+    };
+    match rest {
+        [] => {}
+        ["if", cond @ ..] => bp.condition = Some(parse_condition(cond)?),
+        _ => return Err(usage.into()),
+    }
+    Ok(bp)
+}
+
+/// `r|w|rw|x START[-END]`
+fn parse_watchpoint(args: &[&str]) -> Result<Watchpoint, String> {
+    let [kind, range] = args[..] else { return Err("usage: watch r|w|rw|x START[-END]".into()) };
+    if kind.is_empty() || !kind.chars().all(|c| matches!(c, 'r' | 'w' | 'x')) {
+        return Err(format!("bad watch kind '{}'", kind));
+    }
+    let (start, end) = match range.split_once('-') {
+        Some((start, end)) => (parse_hex(start)?, parse_hex(end)?),
+        None => (parse_hex(range)?, parse_hex(range)?),
+    };
+    if end < start {
+        return Err(format!("bad range '{}'", range));
+    }
+    Ok(Watchpoint {
+        start,
+        end,
+        read: kind.contains('r'),
+        write: kind.contains('w'),
+        execute: kind.contains('x'),
+    })
+}
+
+/// `REG OP VALUE`
+fn parse_condition(args: &[&str]) -> Result<Condition, String> {
+    let [reg, op, value] = args[..] else { return Err("expected REG OP VALUE".into()) };
+    Ok(Condition {
+        reg: Reg::parse(reg).ok_or_else(|| format!("unknown register '{}'", reg))?,
+        op: CmpOp::parse(op).ok_or_else(|| format!("unknown operator '{}'", op))?,
+        value: parse_hex(value)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_breakpoints_watchpoints_and_conditions() {
+        let syms = SymbolTable::default();
+        let bp = parse_breakpoint(&["2:4000", "if", "a", ">=", "$10"], &syms).unwrap();
+        assert_eq!((bp.addr, bp.bank), (0x4000, Some(2)));
+        assert_eq!(bp.condition, Some(Condition { reg: Reg::A, op: CmpOp::Ge, value: 0x10 }));
+        assert!(parse_breakpoint(&["4000", "unless"], &syms).is_err());
+
+        let wp = parse_watchpoint(&["rw", "C000-C0FF"]).unwrap();
+        assert_eq!((wp.start, wp.end), (0xC000, 0xC0FF));
+        assert!(wp.read && wp.write && !wp.execute);
+        assert!(parse_watchpoint(&["z", "C000"]).is_err());
+        assert!(parse_watchpoint(&["r", "C0FF-C000"]).is_err());
+
+        assert!(parse_condition(&["hl", "!=", "0x1234"]).is_ok());
+        assert!(parse_condition(&["q", "==", "1"]).is_err());
+    }
+
+    #[test]
+    fn breakpoints_by_label() {
+        let syms = SymbolTable::parse("00:0150 Main\n03:4abc Add\n01:D000 wBuf\n").unwrap();
+        let bp = parse_breakpoint(&["Main"], &syms).unwrap();
+        assert_eq!((bp.addr, bp.bank), (0x0150, None));
+        // ラベルは 16 進の数値より優先し、切替バンク内ならバンクを限定する
+        let bp = parse_breakpoint(&["Add"], &syms).unwrap();
+        assert_eq!((bp.addr, bp.bank), (0x4ABC, Some(3)));
+        assert_eq!(describe_breakpoint(&bp, &syms), "03:4ABC <Add>");
+        assert_eq!(parse_breakpoint(&["ADE"], &syms).unwrap().addr, 0x0ADE);
+        assert_eq!(parse_addr("wBuf", &syms), Ok(0xD000));
+        assert!(parse_breakpoint(&["Missing"], &syms).is_err());
+    }
+}
@@ -4,10 +4,12 @@
 //! 一気に実行してから描画・待機する方式（GBA はフレーム内のリアルタイム性を
 //! ホスト側で保つ必要がないため単純な方を選んだ）。
 
+use gb_host::gdb::GdbServer;
+use gb_host::screenshot;
 use gba_core::gba::Gba;
 use gba_core::ppu::{HEIGHT, WIDTH};
 use sdl2::event::Event;
-use sdl2::keyboard::Scancode;
+use sdl2::keyboard::{Keycode, Scancode};
 use sdl2::pixels::PixelFormatEnum;
 use std::time::{Duration, Instant};
 
@@ -15,7 +17,8 @@ const SCALE: u32 = 3;
 /// 59.7275 Hz
 const FRAME_NS: u64 = 16_742_706;
 
-pub fn run(rom_path: &str) {
+/// `gdb_port` を指定すると GDB の接続を待ってから停止状態で始める
+pub fn run(rom_path: &str, gdb_port: Option<u16>) {
     let rom = match std::fs::read(rom_path) {
         Ok(r) => r,
         Err(e) => {
@@ -41,6 +44,14 @@ pub fn run(rom_path: &str) {
         println!("Loaded save: {}", sav_path.display());
     }
 
+    let mut gdb = gdb_port.map(|port| match GdbServer::accept(port) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("GDB: failed to listen on port {}: {}", port, e);
+            std::process::exit(1);
+        }
+    });
+
     let sdl = sdl2::init().unwrap();
     let video = sdl.video().unwrap();
     let mut event_pump = sdl.event_pump().unwrap();
@@ -60,7 +71,14 @@ pub fn run(rom_path: &str) {
     let mut next_frame = Duration::ZERO;
     let mut rgb = vec![0u8; WIDTH * HEIGHT * 3];
     'main: loop {
-        gba.run_frame();
+        match &mut gdb {
+            Some(server) => {
+                if !server.run_frame(&mut gba) {
+                    break 'main;
+                }
+            }
+            None => gba.run_frame(),
+        }
 
         for (i, &px) in gba.framebuffer().iter().enumerate() {
             rgb[i * 3] = ((px & 0x1F) << 3) as u8;
@@ -73,8 +91,17 @@ pub fn run(rom_path: &str) {
         canvas.present();
 
         for event in event_pump.poll_iter() {
-            if let Event::Quit { .. } = event {
-                break 'main;
+            match event {
+                Event::Quit { .. } => break 'main,
+                // F12: 表示中の画面を <rom>-001.png から順に保存
+                Event::KeyDown { keycode: Some(Keycode::F12), repeat: false, .. } => {
+                    let path = screenshot::next_path(std::path::Path::new(rom_path));
+                    match screenshot::save(&path, WIDTH, HEIGHT, gba.framebuffer()) {
+                        Ok(()) => println!("Screenshot saved: {}", path.display()),
+                        Err(e) => eprintln!("Failed to save screenshot: {}", e),
+                    }
+                }
+                _ => {}
             }
         }
         let kb = event_pump.keyboard_state();
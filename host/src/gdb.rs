@@ -0,0 +1,153 @@
+//! `--gdb PORT`: GBA ROM を GDB (`arm-none-eabi-gdb`) からリモートデバッグする。
+//!
+//! プロトコル処理は [`gba_core::gdb`] が行い、ここは TCP の入出力だけを受け持つ。
+//! ソケットはノンブロッキングで、表示ループの 1 フレームごとに受信分を処理する。
+//! 停止中はエミュレーションを進めず同じ画面を表示し続ける。
+
+use gba_core::gba::Gba;
+use gba_core::gdb::{Action, GdbStub, Incoming, PacketReader, encode};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+pub struct GdbServer {
+    /// 切断（`D` や接続断）後は None で、以後は通常実行する
+    stream: Option<TcpStream>,
+    reader: PacketReader,
+    stub: GdbStub,
+}
+
+impl GdbServer {
+    /// 127.0.0.1:`port` で GDB の接続を待つ。接続直後のターゲットは停止状態
+    pub fn accept(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        println!("GDB: waiting for connection on 127.0.0.1:{}", port);
+        let (stream, peer) = listener.accept()?;
+        println!("GDB: connected from {}", peer);
+        Self::new(stream)
+    }
+
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+        Ok(Self { stream: Some(stream), reader: PacketReader::new(), stub: GdbStub::new() })
+    }
+
+    pub fn connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// 受信済みのパケットを処理し、実行中なら 1 フレーム進める。
+    /// GDB から `k` (kill) を受けたら false を返す。
+    pub fn run_frame(&mut self, gba: &mut Gba) -> bool {
+        if self.stream.is_none() {
+            gba.run_frame();
+            return true;
+        }
+        match self.poll(gba) {
+            Ok(true) => {}
+            Ok(false) => return false,
+            Err(e) => {
+                println!("GDB: disconnected ({})", e);
+                self.disconnect(gba);
+            }
+        }
+        if self.stub.running()
+            && let Some(reply) = self.stub.run_frame(gba)
+            && let Err(e) = self.send(&reply)
+        {
+            println!("GDB: disconnected ({})", e);
+            self.disconnect(gba);
+        }
+        true
+    }
+
+    fn poll(&mut self, gba: &mut Gba) -> io::Result<bool> {
+        let mut buf = [0u8; 1024];
+        while let Some(stream) = &mut self.stream {
+            let n = match stream.read(&mut buf) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            };
+            for &b in &buf[..n] {
+                let Some(incoming) = self.reader.push(b) else { continue };
+                match incoming {
+                    Incoming::Packet(packet) => {
+                        self.write(b"+")?;
+                        match self.stub.handle(gba, &packet) {
+                            Action::Reply(reply) => self.send(&reply)?,
+                            Action::Resume => {}
+                            Action::Detach => {
+                                self.send("OK")?;
+                                println!("GDB: detached");
+                                self.disconnect(gba);
+                                return Ok(true);
+                            }
+                            Action::Kill => return Ok(false),
+                        }
+                    }
+                    Incoming::BadChecksum => self.write(b"-")?,
+                    Incoming::Interrupt if self.stub.running() => {
+                        let reply = self.stub.interrupt();
+                        self.send(&reply)?;
+                    }
+                    Incoming::Interrupt => {}
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn disconnect(&mut self, gba: &mut Gba) {
+        self.stream = None;
+        self.stub.detach(gba);
+    }
+
+    fn send(&mut self, reply: &str) -> io::Result<()> {
+        self.write(&encode(reply.as_bytes()))
+    }
+
+    /// ノンブロッキングのまま書き切る（応答は小さいので送信バッファ待ちは短い）
+    fn write(&mut self, mut bytes: &[u8]) -> io::Result<()> {
+        let Some(stream) = &mut self.stream else { return Ok(()) };
+        while !bytes.is_empty() {
+            match stream.write(bytes) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(n) => bytes = &bytes[n..],
+                Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::yield_now(),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_until_detach() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            stream.write_all(b"$?#3f$D#44").unwrap();
+            let mut got = Vec::new();
+            stream.read_to_end(&mut got).unwrap();
+            got
+        });
+        let (stream, _) = listener.accept().unwrap();
+        let mut server = GdbServer::new(stream).unwrap();
+        let mut gba = Gba::new(vec![], None);
+        while server.connected() {
+            assert!(server.run_frame(&mut gba));
+        }
+
+        let mut expected = b"+".to_vec();
+        expected.extend(encode(b"T05thread:1;"));
+        expected.extend(b"+$OK#9a");
+        assert_eq!(client.join().unwrap(), expected);
+    }
+}
@@ -0,0 +1,271 @@
+//! 依存クレートなしの zlib / deflate 展開（PNG の読み込み用）。
+//!
+//! 無圧縮・固定ハフマン・動的ハフマンの 3 種類のブロックに対応する。
+
+/// zlib ストリームを展開する（Adler-32 も検証する）
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 6 {
+        return Err("zlib: stream too short".into());
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || !u16::from_be_bytes([cmf, flg]).is_multiple_of(31) {
+        return Err("zlib: bad header".into());
+    }
+    if flg & 0x20 != 0 {
+        return Err("zlib: preset dictionary not supported".into());
+    }
+    let out = inflate(&data[2..])?;
+    let tail = data.len() - 4;
+    let expected = u32::from_be_bytes([data[tail], data[tail + 1], data[tail + 2], data[tail + 3]]);
+    if crate::png::adler32(&out) != expected {
+        return Err("zlib: Adler-32 mismatch".into());
+    }
+    Ok(out)
+}
+
+/// 生の deflate ストリームを展開する
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bits = BitReader { data, pos: 0, bit: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => stored_block(&mut bits, &mut out)?,
+            1 => {
+                let (lit, dist) = fixed_tables();
+                huffman_block(&mut bits, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(&mut bits)?;
+                huffman_block(&mut bits, &mut out, &lit, &dist)?;
+            }
+            _ => return Err("deflate: invalid block type".into()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+/// LSB から読むビットストリーム
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u8,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, n: u8) -> Result<u32, String> {
+        let mut value = 0;
+        for i in 0..n {
+            let byte = *self.data.get(self.pos).ok_or("deflate: unexpected end of data")?;
+            value |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// 符号長から作る正準ハフマン符号の復号表
+struct Huffman {
+    /// 符号長ごとの符号数
+    counts: [u16; 16],
+    /// 符号長・符号順に並べたシンボル
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for len in 1..16 {
+            offsets[len] = offsets[len - 1] + counts[len - 1];
+        }
+        let mut symbols = vec![0; lengths.iter().filter(|&&l| l != 0).count()];
+        for (sym, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = sym as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, String> {
+        // 符号長 1 から順に、その長さの符号の範囲に入るかを調べる
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.read(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("deflate: invalid Huffman code".into())
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] =
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// 動的ハフマンブロックで符号長の符号長が並ぶ順序
+const CODE_LENGTH_ORDER: [usize; 19] =
+    [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn stored_block(bits: &mut BitReader, out: &mut Vec<u8>) -> Result<(), String> {
+    bits.align_to_byte();
+    let header = bits.data.get(bits.pos..bits.pos + 4).ok_or("deflate: truncated stored block")?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen {
+        return Err("deflate: stored block length mismatch".into());
+    }
+    let start = bits.pos + 4;
+    let body = bits.data.get(start..start + len as usize).ok_or("deflate: truncated stored block")?;
+    out.extend_from_slice(body);
+    bits.pos = start + len as usize;
+    Ok(())
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_tables(bits: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let hlit = bits.read(5)? as usize + 257;
+    let hdist = bits.read(5)? as usize + 1;
+    let hclen = bits.read(4)? as usize + 4;
+    let mut cl_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..hclen] {
+        cl_lengths[i] = bits.read(3)? as u8;
+    }
+    let cl = Huffman::new(&cl_lengths);
+
+    let mut lengths = vec![0u8; hlit + hdist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let (value, repeat) = match cl.decode(bits)? {
+            sym @ 0..=15 => (sym as u8, 1),
+            16 => {
+                let prev = *lengths[..i].last().ok_or("deflate: repeat with no previous length")?;
+                (prev, 3 + bits.read(2)? as usize)
+            }
+            17 => (0, 3 + bits.read(3)? as usize),
+            _ => (0, 11 + bits.read(7)? as usize),
+        };
+        let end = i + repeat;
+        lengths.get_mut(i..end).ok_or("deflate: too many code lengths")?.fill(value);
+        i = end;
+    }
+    Ok((Huffman::new(&lengths[..hlit]), Huffman::new(&lengths[hlit..])))
+}
+
+fn huffman_block(
+    bits: &mut BitReader,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let sym = lit.decode(bits)? as usize;
+        match sym {
+            0..=255 => out.push(sym as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = sym - 257;
+                if i >= LENGTH_BASE.len() {
+                    return Err("deflate: invalid length symbol".into());
+                }
+                let len = LENGTH_BASE[i] as usize + bits.read(LENGTH_EXTRA[i])? as usize;
+                let d = dist.decode(bits)? as usize;
+                if d >= DIST_BASE.len() {
+                    return Err("deflate: invalid distance symbol".into());
+                }
+                let distance = DIST_BASE[d] as usize + bits.read(DIST_EXTRA[d])? as usize;
+                if distance > out.len() {
+                    return Err("deflate: distance too far back".into());
+                }
+                // 重なりのあるコピー（distance < len）があるので 1 バイトずつ
+                let start = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_huffman_with_back_reference() {
+        // zlib.compress(b"abcabcabcabc")
+        let z = [0x78, 0x9C, 0x4B, 0x4C, 0x4A, 0x4E, 0x84, 0x21, 0x00, 0x1D, 0xE0, 0x04, 0x99];
+        assert_eq!(zlib_decompress(&z).unwrap(), b"abcabcabcabc");
+    }
+
+    #[test]
+    fn dynamic_huffman_block() {
+        // 偏りのある乱数列を zlib.compress(data, 9) したもの（動的ハフマンブロックになる）
+        let mut x = 1u32;
+        let data: Vec<u8> = (0..64)
+            .map(|_| {
+                x = x.wrapping_mul(1103515245).wrapping_add(12345) & 0x7FFF_FFFF;
+                b"aaaaaaaabbbbccd"[(x >> 16) as usize % 15]
+            })
+            .collect();
+        let z = [
+            0x78, 0xDA, 0x2D, 0x8A, 0x81, 0x09, 0x00, 0x30, 0x0C, 0xC2, 0x6E, 0x8D, 0xE9, 0xFF,
+            0x37, 0x6C, 0x96, 0x82, 0x08, 0xC6, 0x44, 0x02, 0xC4, 0xD6, 0x4F, 0x70, 0x87, 0x4B,
+            0x87, 0x29, 0x75, 0x8F, 0x0A, 0x39, 0xDB, 0xA2, 0xAA, 0x0F, 0x1A, 0x07, 0x18, 0x6D,
+        ];
+        assert_eq!(z[2] >> 1 & 3, 2);
+        assert_eq!(zlib_decompress(&z).unwrap(), data);
+    }
+
+    #[test]
+    fn stored_blocks_round_trip_and_corruption_is_detected() {
+        let data: Vec<u8> = (0..70_000u32).map(|i| (i * 7) as u8).collect();
+        let mut z = crate::png::zlib_stored(&data);
+        assert_eq!(zlib_decompress(&z).unwrap(), data);
+        let n = z.len();
+        z[n - 1] ^= 1;
+        assert!(zlib_decompress(&z).is_err());
+    }
+}
@@ -1,23 +1,28 @@
-use gb_core::input::{ButtonState, InputSource};
+use gb_core::input::{AnalogState, ButtonState, InputSource};
 use gb_core::platform::{AudioSink, Display};
 use gb_core::ppu::{LCD_HEIGHT, LCD_WIDTH};
+use gb_host::screenshot;
 use sdl2::audio::{AudioQueue, AudioSpecDesired};
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Scancode};
 use sdl2::pixels::PixelFormatEnum;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 use sdl2::EventPump;
-use sdl2::Sdl;
+use sdl2::{Sdl, VideoSubsystem};
+use std::path::Path;
 
 const SCALE: u32 = 4;
 // AudioQueue のバッファ上限: 約 100ms 分（44100 * 2ch * 4bytes * 0.1sec）
 const AUDIO_QUEUE_MAX_BYTES: u32 = 35280;
+/// ビューアウィンドウの開閉キー（[`HostKeys::toggle_viewer`] の順）
+const VIEWER_KEYS: [Keycode; 4] = [Keycode::F1, Keycode::F2, Keycode::F3, Keycode::F4];
 
 pub struct SdlDisplay {
     canvas: Canvas<Window>,
-    #[allow(dead_code)]
     sdl_context: Sdl,
+    /// 最後に表示したフレーム（スクリーンショット用）
+    frame: Vec<u16>,
 }
 
 pub struct SdlAudio {
@@ -26,6 +31,33 @@ pub struct SdlAudio {
 
 pub struct SdlInput {
     event_pump: EventPump,
+    hotkeys: HostKeys,
+    /// マウスでの傾き入力に使うウィンドウサイズ
+    window_size: (i32, i32),
+    /// ゲーム画面のウィンドウ。これを閉じたら終了する（ビューアは閉じるだけ）
+    main_window: u32,
+}
+
+/// ゲーム入力以外のホスト操作キー。poll() 中に押下を記録し、メインループが取り出す。
+#[derive(Default, Clone, Copy)]
+pub struct HostKeys {
+    /// F5: セーブステート書き出し
+    pub save_state: bool,
+    /// F8: セーブステート読み込み
+    pub load_state: bool,
+    /// F12: スクリーンショット
+    pub screenshot: bool,
+    /// F1-F4: ビューアウィンドウ（タイル・マップ・OAM・パレット）の開閉
+    pub toggle_viewer: [bool; 4],
+    /// 閉じるボタンが押されたビューアウィンドウの ID
+    pub closed_window: Option<u32>,
+}
+
+impl SdlInput {
+    /// 前回呼び出し以降に押されたホスト操作キーを取り出す。
+    pub fn take_hotkeys(&mut self) -> HostKeys {
+        std::mem::take(&mut self.hotkeys)
+    }
 }
 
 pub fn create_sdl_backends() -> (SdlDisplay, SdlAudio, SdlInput) {
@@ -64,25 +96,48 @@ pub fn create_sdl_backends() -> (SdlDisplay, SdlAudio, SdlInput) {
         eprintln!("Warning: audio device unavailable, running without sound");
     }
 
+    let main_window = canvas.window().id();
     (
-        SdlDisplay { canvas, sdl_context },
+        SdlDisplay { canvas, sdl_context, frame: vec![0; LCD_WIDTH * LCD_HEIGHT] },
         SdlAudio { audio_queue },
-        SdlInput { event_pump },
+        SdlInput {
+            event_pump,
+            hotkeys: HostKeys::default(),
+            window_size: ((LCD_WIDTH as u32 * SCALE) as i32, (LCD_HEIGHT as u32 * SCALE) as i32),
+            main_window,
+        },
     )
 }
 
+impl SdlDisplay {
+    /// ビューアウィンドウを作るためのビデオサブシステム
+    pub fn video(&self) -> VideoSubsystem {
+        self.sdl_context.video().unwrap()
+    }
+
+    /// 表示中の画面を PNG で保存する
+    pub fn save_screenshot(&self, path: &Path) -> Result<(), String> {
+        screenshot::save(path, LCD_WIDTH, LCD_HEIGHT, &self.frame)
+    }
+}
+
+/// RGB555 (bits 0-4=R, 5-9=G, 10-14=B) → RGB24
+pub fn to_rgb24(buffer: &[u16]) -> Vec<u8> {
+    buffer
+        .iter()
+        .flat_map(|&px| {
+            let r = ((px & 0x1F) as u8) << 3;
+            let g = (((px >> 5) & 0x1F) as u8) << 3;
+            let b = (((px >> 10) & 0x1F) as u8) << 3;
+            [r, g, b]
+        })
+        .collect()
+}
+
 impl Display for SdlDisplay {
     fn draw(&mut self, buffer: &[u16]) {
-        // RGB555 (bits 0-4=R, 5-9=G, 10-14=B) → RGB24
-        let rgb_buffer: Vec<u8> = buffer
-            .iter()
-            .flat_map(|&px| {
-                let r = ((px & 0x1F) as u8) << 3;
-                let g = (((px >> 5) & 0x1F) as u8) << 3;
-                let b = (((px >> 10) & 0x1F) as u8) << 3;
-                [r, g, b]
-            })
-            .collect();
+        self.frame.copy_from_slice(buffer);
+        let rgb_buffer = to_rgb24(buffer);
 
         let texture_creator = self.canvas.texture_creator();
         let mut texture = texture_creator
@@ -113,8 +168,33 @@ impl InputSource for SdlInput {
     fn poll(&mut self) -> ButtonState {
         let mut state = ButtonState::default();
         for event in self.event_pump.poll_iter() {
-            if let Event::Quit { .. } = event {
-                state.quit = true;
+            match event {
+                Event::Quit { .. } => state.quit = true,
+                Event::KeyDown { keycode: Some(Keycode::F5), repeat: false, .. } => {
+                    self.hotkeys.save_state = true;
+                }
+                Event::KeyDown { keycode: Some(Keycode::F8), repeat: false, .. } => {
+                    self.hotkeys.load_state = true;
+                }
+                Event::KeyDown { keycode: Some(Keycode::F12), repeat: false, .. } => {
+                    self.hotkeys.screenshot = true;
+                }
+                Event::KeyDown { keycode: Some(key), repeat: false, .. } => {
+                    if let Some(n) = VIEWER_KEYS.iter().position(|&k| k == key) {
+                        self.hotkeys.toggle_viewer[n] = true;
+                    }
+                }
+                Event::Window { window_id, win_event: WindowEvent::Close, .. } => {
+                    if window_id == self.main_window {
+                        state.quit = true;
+                    } else {
+                        self.hotkeys.closed_window = Some(window_id);
+                    }
+                }
+                Event::Window { win_event: WindowEvent::Resized(w, h), .. } => {
+                    self.window_size = (w, h);
+                }
+                _ => {}
             }
         }
         let keys: std::collections::HashSet<Keycode> = self
@@ -134,4 +214,22 @@ impl InputSource for SdlInput {
         state.right = keys.contains(&Keycode::Right);
         state
     }
+
+    /// 傾き: I/J/K/L キーで最大まで傾け、左ボタンを押している間はウィンドウ中央からの
+    /// マウスの位置で傾ける（端で ±1.0）。
+    fn poll_analog(&mut self) -> AnalogState {
+        let mouse = self.event_pump.mouse_state();
+        if mouse.left() {
+            let (w, h) = self.window_size;
+            let axis = |pos: i32, size: i32| {
+                ((pos * 2 - size) as f32 / size.max(1) as f32).clamp(-1.0, 1.0)
+            };
+            return AnalogState { x: axis(mouse.x(), w), y: axis(mouse.y(), h) };
+        }
+        let keys = self.event_pump.keyboard_state();
+        let axis = |neg: Scancode, pos: Scancode| {
+            keys.is_scancode_pressed(pos) as i8 as f32 - keys.is_scancode_pressed(neg) as i8 as f32
+        };
+        AnalogState { x: axis(Scancode::J, Scancode::L), y: axis(Scancode::I, Scancode::K) }
+    }
 }
@@ -1 +1,11 @@
+pub mod camera;
 pub mod cartridge;
+pub mod conformance;
+pub mod gdb;
+pub mod inflate;
+pub mod link;
+pub mod png;
+pub mod printer;
+pub mod screenshot;
+pub mod symbols;
+pub mod vram_view;
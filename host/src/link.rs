@@ -0,0 +1,273 @@
+//! TCP / Unix ドメインソケット経由のリンクケーブル（2 プロセス間）。
+//!
+//! ロックステップ方式: 両端は [`SYNC_CYCLES`] M-cycle ごとに同期メッセージを交換し、
+//! 相手のメッセージが届くまでブロックする。転送の判断は同期時点で交換した状態だけで
+//! 行うため、両プロセスのサイクル数が揃い、結果は実行速度に依存しない。
+//!
+//! 同期メッセージ（4 バイト）:
+//! - `[0]` フラグ: bit0=マスター転送あり, bit1=外部クロックで待機中, bit7=切断
+//! - `[1]` マスター転送で送ったバイト
+//! - `[2]` 待機中の SB（相手がマスター転送したときに返す値）
+//! - `[3]` 予約
+//!
+//! マスター側の転送は直前の同期で受け取った「相手が待機中か・相手の SB」を使って即座に完了し、
+//! 送信バイトは次の同期で相手に届く（相手側の完了は最大 1 同期周期遅れる）。
+
+use gb_core::platform::SerialPort;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// 同期周期（M-cycle）。通常速度の 1 バイト転送 (1024 M-cycle) より短くする
+pub const SYNC_CYCLES: u32 = 256;
+
+const FLAG_TRANSFER: u8 = 1 << 0;
+const FLAG_WAITING: u8 = 1 << 1;
+const FLAG_QUIT: u8 = 1 << 7;
+
+/// 接続方法（コマンドライン引数から決まる）
+pub enum LinkAddr {
+    /// `--link-listen PORT`（127.0.0.1 のみ）/ `--link-listen HOST:PORT`
+    TcpListen(String),
+    /// `--link-connect HOST:PORT`
+    TcpConnect(String),
+    /// `--link-listen unix:PATH`
+    #[cfg(unix)]
+    UnixListen(String),
+    /// `--link-connect unix:PATH`
+    #[cfg(unix)]
+    UnixConnect(String),
+}
+
+impl LinkAddr {
+    /// `--link-listen` の引数を解釈する。ポート番号だけなら 127.0.0.1 で待つ
+    /// （他のホストから受け付けるには `0.0.0.0:PORT` のようにアドレスを明示する）
+    pub fn parse_listen(arg: &str) -> Result<Self, String> {
+        #[cfg(unix)]
+        if let Some(path) = arg.strip_prefix("unix:") {
+            return Ok(LinkAddr::UnixListen(path.to_string()));
+        }
+        let (host, port) = arg.rsplit_once(':').unwrap_or(("127.0.0.1", arg));
+        let port: u16 = port.parse().map_err(|_| format!("invalid port '{}'", arg))?;
+        Ok(LinkAddr::TcpListen(format!("{}:{}", host, port)))
+    }
+
+    /// `--link-connect` の引数を解釈する
+    pub fn parse_connect(arg: &str) -> Result<Self, String> {
+        #[cfg(unix)]
+        if let Some(path) = arg.strip_prefix("unix:") {
+            return Ok(LinkAddr::UnixConnect(path.to_string()));
+        }
+        if !arg.contains(':') {
+            return Err(format!("expected HOST:PORT, got '{}'", arg));
+        }
+        Ok(LinkAddr::TcpConnect(arg.to_string()))
+    }
+}
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+/// ソケット越しのリンクケーブル端。
+pub struct SocketLink {
+    stream: Option<Box<dyn Stream>>,
+    /// 前回の同期からの M-cycle 数
+    cycles: u32,
+    /// 今回の周期中にマスター転送で送ったバイト
+    sent: Option<u8>,
+    /// 自分が外部クロックで待機中か（今回の周期の最後に poll された状態）
+    waiting: bool,
+    sb: u8,
+    /// 直前の同期で得た相手の状態
+    peer_waiting: bool,
+    peer_sb: u8,
+    /// 相手のマスター転送で届いたバイト
+    incoming: Option<u8>,
+}
+
+impl SocketLink {
+    /// 相手と接続する（listen 側は接続を待つ）
+    pub fn connect(addr: &LinkAddr) -> io::Result<Self> {
+        let stream: Box<dyn Stream> = match addr {
+            LinkAddr::TcpListen(addr) => {
+                let listener = TcpListener::bind(addr.as_str())?;
+                println!("Link: waiting for peer on {}", addr);
+                let (stream, peer) = listener.accept()?;
+                println!("Link: connected from {}", peer);
+                stream.set_nodelay(true)?;
+                Box::new(stream)
+            }
+            LinkAddr::TcpConnect(host) => {
+                let stream = TcpStream::connect(host)?;
+                println!("Link: connected to {}", host);
+                stream.set_nodelay(true)?;
+                Box::new(stream)
+            }
+            #[cfg(unix)]
+            LinkAddr::UnixListen(path) => {
+                let _ = std::fs::remove_file(path);
+                let listener = std::os::unix::net::UnixListener::bind(path)?;
+                println!("Link: waiting for peer on {}", path);
+                let (stream, _) = listener.accept()?;
+                println!("Link: connected");
+                Box::new(stream)
+            }
+            #[cfg(unix)]
+            LinkAddr::UnixConnect(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                println!("Link: connected to {}", path);
+                Box::new(stream)
+            }
+        };
+        Ok(Self::from_stream(stream))
+    }
+
+    fn from_stream(stream: Box<dyn Stream>) -> Self {
+        Self {
+            stream: Some(stream),
+            cycles: 0,
+            sent: None,
+            waiting: false,
+            sb: 0xFF,
+            peer_waiting: false,
+            peer_sb: 0xFF,
+            incoming: None,
+        }
+    }
+
+    /// 相手と同期メッセージを交換する。失敗したら切断扱いにする。
+    fn sync(&mut self) {
+        let Some(stream) = &mut self.stream else {
+            return;
+        };
+        let mut msg = [0u8; 4];
+        if let Some(byte) = self.sent {
+            msg[0] |= FLAG_TRANSFER;
+            msg[1] = byte;
+        }
+        if self.waiting {
+            msg[0] |= FLAG_WAITING;
+            msg[2] = self.sb;
+        }
+        let mut peer = [0u8; 4];
+        let ok = stream.write_all(&msg).and_then(|_| stream.read_exact(&mut peer)).is_ok();
+        if !ok || peer[0] & FLAG_QUIT != 0 {
+            println!("Link: peer disconnected");
+            self.disconnect();
+            return;
+        }
+        if peer[0] & FLAG_TRANSFER != 0 {
+            self.incoming = Some(peer[1]);
+        }
+        // 自分がこの周期に転送したバイトは相手の待機を消費するので、
+        // 同じ同期で受け取った相手の待機状態は使わない（二重転送を防ぐ）
+        self.peer_waiting = peer[0] & FLAG_WAITING != 0 && self.sent.is_none();
+        self.peer_sb = peer[2];
+        self.sent = None;
+        self.waiting = false;
+    }
+
+    fn disconnect(&mut self) {
+        self.stream = None;
+        self.peer_waiting = false;
+    }
+}
+
+impl SerialPort for SocketLink {
+    fn transfer(&mut self, out: u8) -> u8 {
+        if self.stream.is_none() || !self.peer_waiting {
+            return 0xFF;
+        }
+        self.peer_waiting = false;
+        self.sent = Some(out);
+        self.peer_sb
+    }
+
+    fn poll_external(&mut self, out: u8) -> Option<u8> {
+        if let Some(byte) = self.incoming.take() {
+            return Some(byte);
+        }
+        self.waiting = true;
+        self.sb = out;
+        None
+    }
+
+    fn tick(&mut self) {
+        self.cycles += 1;
+        if self.cycles == SYNC_CYCLES {
+            self.cycles = 0;
+            self.sync();
+        }
+    }
+}
+
+impl Drop for SocketLink {
+    fn drop(&mut self) {
+        // 終了を通知して相手のブロックを解く
+        if let Some(stream) = &mut self.stream {
+            let _ = stream.write_all(&[FLAG_QUIT, 0, 0, 0]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gb_core::serial::Serial;
+
+    /// 1 台分のシリアルを `cycles` M-cycle 進め、完了したサイクルを返す
+    fn run(serial: &mut Serial, link: &mut SocketLink, cycles: u32) -> Option<u32> {
+        let mut done = None;
+        for c in 0..cycles {
+            if serial.emulate_cycle(link) && done.is_none() {
+                done = Some(c);
+            }
+            link.tick();
+        }
+        done
+    }
+
+    #[test]
+    fn listen_defaults_to_loopback() {
+        let listen = |arg| match LinkAddr::parse_listen(arg) {
+            Ok(LinkAddr::TcpListen(addr)) => Ok(addr),
+            Ok(_) => Err("not tcp".to_string()),
+            Err(e) => Err(e),
+        };
+        assert_eq!(listen("5000").unwrap(), "127.0.0.1:5000");
+        assert_eq!(listen("0.0.0.0:5000").unwrap(), "0.0.0.0:5000");
+        assert_eq!(listen("[::]:5000").unwrap(), "[::]:5000");
+        assert!(listen("host:port").is_err());
+        assert!(LinkAddr::parse_connect("5000").is_err());
+    }
+
+    #[test]
+    fn loopback_transfer_is_deterministic() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let slave = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut link = SocketLink::from_stream(Box::new(stream));
+            let mut serial = Serial::new();
+            serial.write(0xFF01, 0x99);
+            serial.write(0xFF02, 0x80);
+            let done = run(&mut serial, &mut link, 4 * SYNC_CYCLES + 1024);
+            (done, serial.read(0xFF01))
+        });
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut link = SocketLink::from_stream(Box::new(stream));
+        let mut serial = Serial::new();
+        // 相手の待機状態が同期で届いてから転送を始める
+        run(&mut serial, &mut link, SYNC_CYCLES);
+        serial.write(0xFF01, 0x42);
+        serial.write(0xFF02, 0x81);
+        let done = run(&mut serial, &mut link, 3 * SYNC_CYCLES + 1024);
+        assert_eq!(done, Some(1023));
+        assert_eq!(serial.read(0xFF01), 0x99);
+
+        let (slave_done, slave_sb) = slave.join().unwrap();
+        assert_eq!(slave_sb, 0x42);
+        // マスター完了（通算 SYNC_CYCLES + 1023 サイクル目）はちょうど同期境界なので、
+        // バイトはその同期で届き、スレーブは次のサイクルで完了する
+        assert_eq!(slave_done, Some(SYNC_CYCLES * 5));
+    }
+}
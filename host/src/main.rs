@@ -1,32 +1,301 @@
+mod debug_repl;
 mod gba_run;
 mod lcd;
 mod renderer;
+mod rom_disasm;
+mod rom_info;
+mod trace_log;
+mod viewer;
 
+use gb_host::camera::FileImageSource;
 use gb_host::cartridge;
+use gb_host::link::{LinkAddr, SocketLink};
+use gb_host::printer::Printer;
+use gb_host::screenshot;
+use gb_host::symbols::SymbolTable;
+use trace_log::TraceLog;
 
-use gb_core::bootrom::Bootrom;
+use gb_core::bootrom::{Bootrom, CGB_BOOTROM_SIZE, DMG_BOOTROM_SIZE};
 use gb_core::gameboy::{GameBoy, StepResult};
 use gb_core::input::NullInput;
 use gb_core::mmu::Mmu;
-use gb_core::platform::{CartridgeBus, NullAudio, NullDisplay};
+use gb_core::platform::{AudioSink, CartridgeBus, Display, NullAudio, NullDisplay, SerialPort};
+use gb_core::ppu::{LCD_HEIGHT, LCD_WIDTH, Renderer};
+use gb_core::serial::TestSerial;
 use std::time::{Duration, Instant};
 
 const M_CYCLE_NS: u128 = 4 * 1_000_000_000 / 4_194_304;
+/// バッテリーバックアップ RAM を書き出す間隔（約 5 秒、書き込みがあった場合のみ）
+const BATTERY_FLUSH_FRAMES: u32 = 300;
+
+/// コマンドライン引数
+struct Options {
+    headless: bool,
+    /// `--info`: ROM ヘッダを表示して終了する（実行しない）
+    info: bool,
+    /// `--debug`: 標準入力のデバッガ REPL から実行する
+    debug: bool,
+    /// `--disasm BANK[-BANK]`: ROM バンクを逆アセンブルして終了する（実行しない）
+    disasm: Option<(usize, usize)>,
+    rom_path: Option<String>,
+    /// `--link-listen PORT|HOST:PORT` / `--link-connect HOST:PORT`
+    link: Option<LinkAddr>,
+    /// `--printer DIR`: Game Boy Printer の出力先
+    printer: Option<std::path::PathBuf>,
+    /// `--renderer scanline|fifo`
+    renderer: Renderer,
+    /// `--cgb`: DMG ソフトも CGB 本体（DMG 互換モード）で動かす
+    cgb: bool,
+    /// `--camera-image FILE`: ポケットカメラに写す静止画 (PGM / PNG)
+    camera_image: Option<String>,
+    /// `--gdb PORT`: GBA ROM を GDB リモートデバッグで起動する
+    gdb: Option<u16>,
+    /// `--trace-log FILE`: 命令ごとの CPU 状態を gameboy-doctor 形式で書き出す。
+    /// doctor の参照ログは BootROM の後から始まるため、BootROM は読み込まない
+    trace_log: Option<String>,
+    /// `--ly-stub`: LY を常に 0x90 として読ませる（gameboy-doctor との比較用）
+    ly_stub: bool,
+    /// `--sym FILE`: シンボルファイル（省略時は `<rom>.sym` があれば読む）
+    sym: Option<String>,
+    /// `--no-sym`: シンボルファイルを読まない
+    no_sym: bool,
+    /// `--screenshot-at-frame N FILE`: ヘッドレス実行で N フレーム目を PNG に保存して終了する
+    screenshot_at: Option<(u64, std::path::PathBuf)>,
+}
+
+fn parse_args() -> Options {
+    let mut opts = Options {
+        headless: false,
+        info: false,
+        debug: false,
+        disasm: None,
+        rom_path: None,
+        link: None,
+        printer: None,
+        renderer: Renderer::Scanline,
+        cgb: false,
+        camera_image: None,
+        gdb: None,
+        trace_log: None,
+        ly_stub: false,
+        sym: None,
+        no_sym: false,
+        screenshot_at: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => opts.headless = true,
+            "--info" => opts.info = true,
+            "--debug" => opts.debug = true,
+            "--cgb" => opts.cgb = true,
+            "--ly-stub" => opts.ly_stub = true,
+            "--no-sym" => opts.no_sym = true,
+            "--sym" => match args.next() {
+                Some(path) => opts.sym = Some(path),
+                None => {
+                    eprintln!("--sym: missing symbol file");
+                    std::process::exit(1);
+                }
+            },
+            "--disasm" => match args.next().as_deref().and_then(parse_banks) {
+                Some(banks) => opts.disasm = Some(banks),
+                None => {
+                    eprintln!("--disasm: expected BANK or BANK-BANK (hex)");
+                    std::process::exit(1);
+                }
+            },
+            "--link-listen" | "--link-connect" => {
+                let value = args.next().unwrap_or_default();
+                let addr = if arg == "--link-listen" {
+                    LinkAddr::parse_listen(&value)
+                } else {
+                    LinkAddr::parse_connect(&value)
+                };
+                match addr {
+                    Ok(addr) => opts.link = Some(addr),
+                    Err(e) => {
+                        eprintln!("{}: {}", arg, e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--printer" => match args.next() {
+                Some(dir) => opts.printer = Some(dir.into()),
+                None => {
+                    eprintln!("--printer: missing output directory");
+                    std::process::exit(1);
+                }
+            },
+            "--camera-image" => match args.next() {
+                Some(path) => opts.camera_image = Some(path),
+                None => {
+                    eprintln!("--camera-image: missing image file");
+                    std::process::exit(1);
+                }
+            },
+            "--trace-log" => match args.next() {
+                Some(path) => opts.trace_log = Some(path),
+                None => {
+                    eprintln!("--trace-log: missing output file");
+                    std::process::exit(1);
+                }
+            },
+            "--screenshot-at-frame" => {
+                let frame = args.next().and_then(|n| n.parse().ok()).filter(|&n| n > 0);
+                match (frame, args.next()) {
+                    (Some(n), Some(path)) => opts.screenshot_at = Some((n, path.into())),
+                    _ => {
+                        eprintln!("--screenshot-at-frame: expected a frame number and a PNG file");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--gdb" => match args.next().and_then(|p| p.parse().ok()) {
+                Some(port) => opts.gdb = Some(port),
+                None => {
+                    eprintln!("--gdb: expected a TCP port number");
+                    std::process::exit(1);
+                }
+            },
+            "--renderer" => {
+                opts.renderer = match args.next().as_deref() {
+                    Some("scanline") => Renderer::Scanline,
+                    Some("fifo") => Renderer::Fifo,
+                    other => {
+                        eprintln!("--renderer: expected 'scanline' or 'fifo', got {:?}", other);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option: {}", arg);
+                std::process::exit(1);
+            }
+            _ => {
+                opts.rom_path.get_or_insert(arg);
+            }
+        }
+    }
+    if opts.link.is_some() && opts.printer.is_some() {
+        eprintln!("--printer cannot be combined with --link-listen/--link-connect");
+        std::process::exit(1);
+    }
+    if opts.debug && opts.trace_log.is_some() {
+        eprintln!("--trace-log cannot be combined with --debug");
+        std::process::exit(1);
+    }
+    if opts.screenshot_at.is_some() && (!opts.headless || opts.debug) {
+        eprintln!("--screenshot-at-frame requires --headless (without --debug)");
+        std::process::exit(1);
+    }
+    opts
+}
+
+/// `--disasm` の `BANK` / `BANK-BANK`（16 進）
+fn parse_banks(arg: &str) -> Option<(usize, usize)> {
+    let (first, last) = arg.split_once('-').unwrap_or((arg, arg));
+    let first = usize::from_str_radix(first, 16).ok()?;
+    let last = usize::from_str_radix(last, 16).ok()?;
+    (first <= last).then_some((first, last))
+}
+
+/// GB のシリアルポートに接続する機器（コマンドライン引数で選択）
+enum SerialDevice {
+    Disconnected,
+    Link(SocketLink),
+    Printer(Printer),
+}
+
+impl SerialDevice {
+    fn open(opts: &Options) -> Self {
+        if let Some(dir) = &opts.printer {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                eprintln!("Printer: failed to create '{}': {}", dir.display(), e);
+                std::process::exit(1);
+            }
+            return SerialDevice::Printer(Printer::new(dir));
+        }
+        match &opts.link {
+            Some(addr) => match SocketLink::connect(addr) {
+                Ok(link) => SerialDevice::Link(link),
+                Err(e) => {
+                    eprintln!("Link: failed to connect: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            None => SerialDevice::Disconnected,
+        }
+    }
+}
+
+impl SerialPort for SerialDevice {
+    fn transfer(&mut self, out: u8) -> u8 {
+        match self {
+            SerialDevice::Disconnected => 0xFF,
+            SerialDevice::Link(link) => link.transfer(out),
+            SerialDevice::Printer(printer) => printer.transfer(out),
+        }
+    }
+
+    fn poll_external(&mut self, out: u8) -> Option<u8> {
+        match self {
+            SerialDevice::Disconnected => None,
+            SerialDevice::Link(link) => link.poll_external(out),
+            SerialDevice::Printer(printer) => printer.poll_external(out),
+        }
+    }
+
+    fn tick(&mut self) {
+        match self {
+            SerialDevice::Disconnected => {}
+            SerialDevice::Link(link) => link.tick(),
+            SerialDevice::Printer(printer) => printer.tick(),
+        }
+    }
+}
 
 pub fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let headless = args.iter().any(|a| a == "--headless");
-    let rom_path =
-        args.iter().skip(1).find(|a| *a != "--headless").map(|s| s.as_str());
+    let opts = parse_args();
+    let headless = opts.headless;
+    let rom_path = opts.rom_path.as_deref();
+
+    if opts.info {
+        let Some(path) = rom_path else {
+            eprintln!("--info: missing ROM file");
+            std::process::exit(1);
+        };
+        if let Err(e) = rom_info::print(path) {
+            eprintln!("Failed to read '{}': {}", path, e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some((first, last)) = opts.disasm {
+        let Some(path) = rom_path else {
+            eprintln!("--disasm: missing ROM file");
+            std::process::exit(1);
+        };
+        if let Err(e) = rom_disasm::print(path, first, last, &load_symbols(&opts, path)) {
+            eprintln!("Failed to disassemble '{}': {}", path, e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
     // .gba は GBA モードで起動（GB とはコア・表示・ループがすべて別）
     if let Some(path) = rom_path.filter(|p| p.ends_with(".gba")) {
-        gba_run::run(path);
+        if opts.trace_log.is_some() {
+            eprintln!("Warning: --trace-log is only supported for Game Boy ROMs");
+        }
+        if opts.screenshot_at.is_some() {
+            eprintln!("Warning: --screenshot-at-frame is only supported for Game Boy ROMs");
+        }
+        gba_run::run(path, opts.gdb);
         return;
     }
 
-    let bootrom = load_bootrom();
-
     // ROM パス解決: 引数 → test_rom.gb → cpu_instrs.gb の順で探す
     let resolved_path = rom_path.or_else(|| {
         if std::path::Path::new("test_rom.gb").exists() {
@@ -46,7 +315,7 @@ pub fn main() {
                 std::process::exit(1);
             }
         };
-        let cart = match cartridge::Cartridge::new(path) {
+        let mut cart = match cartridge::Cartridge::new(path) {
             Ok(c) => {
                 println!("Loaded: {}", path);
                 c
@@ -56,10 +325,24 @@ pub fn main() {
                 std::process::exit(1);
             }
         };
-        let mmu = Mmu::new(bootrom, cart);
-        let mut gb = GameBoy::new(mmu, NullDisplay, NullAudio, NullInput);
-        run_headless(&mut gb);
+        warn_header(&cart);
+        attach_camera_image(&mut cart, &opts);
+        let bootrom = load_bootrom(&opts, cart.header().is_cgb());
+        let mut mmu = Mmu::new(bootrom, cart);
+        mmu.ppu.set_renderer(opts.renderer);
+        mmu.ppu.set_ly_stub(opts.ly_stub);
+        mmu.set_cgb_hardware(opts.cgb);
+        let syms = load_symbols(&opts, path);
+        let mut trace = open_trace_log(&opts, &syms);
+        let serial = TestSerial::new(SerialDevice::open(&opts));
+        let mut gb = GameBoy::with_serial(mmu, NullDisplay, NullAudio, NullInput, serial);
+        if opts.debug {
+            debug_repl::run(&mut gb, &syms);
+        } else {
+            run_headless(&mut gb, trace.as_mut(), opts.screenshot_at.as_ref());
+        }
     } else {
+        let serial = SerialDevice::open(&opts);
         let (display, audio, input) = lcd::create_sdl_backends();
         match resolved_path.and_then(|p| {
             cartridge::Cartridge::new(p)
@@ -67,37 +350,226 @@ pub fn main() {
                 .ok()
                 .map(|c| (p, c))
         }) {
-            Some((path, cart)) => {
+            Some((path, mut cart)) => {
                 println!("Loaded: {}", path);
-                let mmu = Mmu::new(bootrom, cart);
-                let mut gb = GameBoy::new(mmu, display, audio, input);
-                run_loop(|| gb.step());
+                let sav_path = std::path::Path::new(path).with_extension("sav");
+                warn_header(&cart);
+                load_battery(&mut cart, &sav_path);
+                attach_camera_image(&mut cart, &opts);
+                let bootrom = load_bootrom(&opts, cart.header().is_cgb());
+                let mut mmu = Mmu::new(bootrom, cart);
+                mmu.ppu.set_renderer(opts.renderer);
+                mmu.ppu.set_ly_stub(opts.ly_stub);
+                mmu.set_cgb_hardware(opts.cgb);
+                let syms = load_symbols(&opts, path);
+                let mut trace = open_trace_log(&opts, &syms);
+                let mut viewers = viewer::Viewers::new(display.video());
+                let mut gb = GameBoy::with_serial(mmu, display, audio, input, serial);
+                let state_path = std::path::Path::new(path).with_extension("state");
+                let mut frames: u32 = 0;
+                if opts.debug {
+                    // 停止中はウィンドウを更新しない（実行中は通常どおり描画・入力する）
+                    debug_repl::run(&mut gb, &syms);
+                } else {
+                    run_loop(|| {
+                        let result = trace_log::step(&mut gb, trace.as_mut());
+                        if result.frame_ready {
+                            let keys = gb.input_mut().take_hotkeys();
+                            handle_state_hotkeys(&mut gb, &keys, &state_path);
+                            if keys.screenshot {
+                                save_screenshot(gb.display_mut(), path);
+                            }
+                            viewers.update(&keys, &gb.mmu().ppu);
+                            frames += 1;
+                            if frames == BATTERY_FLUSH_FRAMES {
+                                frames = 0;
+                                flush_battery(gb.cart_mut(), &sav_path);
+                            }
+                        }
+                        result
+                    });
+                }
+                flush_battery(gb.cart_mut(), &sav_path);
             }
             None => {
                 println!("No ROM found, running without cartridge");
                 use gb_core::platform::NullCartridge;
-                let mmu = Mmu::new(bootrom, NullCartridge);
-                let mut gb = GameBoy::new(mmu, display, audio, input);
-                run_loop(|| gb.step());
+                let mut mmu = Mmu::new(load_bootrom(&opts, false), NullCartridge);
+                mmu.ppu.set_renderer(opts.renderer);
+                mmu.ppu.set_ly_stub(opts.ly_stub);
+                let mut trace = open_trace_log(&opts, &SymbolTable::default());
+                let mut gb = GameBoy::with_serial(mmu, display, audio, input, serial);
+                run_loop(|| trace_log::step(&mut gb, trace.as_mut()));
             }
         }
     }
 }
 
-fn load_bootrom() -> Bootrom {
-    match std::fs::read("dmg_bootrom.bin") {
-        Ok(bytes) if bytes.len() >= 0x100 => {
-            let mut arr = [0u8; 0x100];
-            arr.copy_from_slice(&bytes[..0x100]);
-            Bootrom::from_bytes(arr)
+/// `--camera-image` の静止画をポケットカメラの画像にする
+fn attach_camera_image(cart: &mut cartridge::Cartridge, opts: &Options) {
+    let Some(path) = &opts.camera_image else { return };
+    match FileImageSource::open(path) {
+        Ok(source) => {
+            if !cart.set_image_source(Box::new(source)) {
+                eprintln!("Warning: --camera-image ignored (not a Pocket Camera cartridge)");
+            }
+        }
+        Err(e) => eprintln!("Warning: --camera-image: {}", e),
+    }
+}
+
+/// `--trace-log` の出力ファイルを作る（作れなければ終了する）
+fn open_trace_log(opts: &Options, syms: &SymbolTable) -> Option<TraceLog> {
+    let path = opts.trace_log.as_deref()?;
+    match TraceLog::create(path, syms.clone()) {
+        Ok(log) => Some(log),
+        Err(e) => {
+            eprintln!("Failed to create '{}': {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `--sym` か `<rom>.sym` を読む。`--no-sym` や自動で見つからないときは空の表
+fn load_symbols(opts: &Options, rom_path: &str) -> SymbolTable {
+    if opts.no_sym {
+        return SymbolTable::default();
+    }
+    let path = match &opts.sym {
+        Some(path) => std::path::PathBuf::from(path),
+        None => std::path::Path::new(rom_path).with_extension("sym"),
+    };
+    if opts.sym.is_none() && !path.exists() {
+        return SymbolTable::default();
+    }
+    match SymbolTable::load(&path) {
+        Ok(syms) => {
+            println!("Loaded symbols: {} ({} labels)", path.display(), syms.len());
+            syms
         }
-        _ => {
-            eprintln!("Warning: dmg_bootrom.bin not found, using DMG init values");
+        Err(e) => {
+            eprintln!("Warning: failed to load symbols '{}': {}", path.display(), e);
+            SymbolTable::default()
+        }
+    }
+}
+
+/// ヘッダ検証の警告を表示する（起動は続ける）
+fn warn_header(cart: &cartridge::Cartridge) {
+    for w in cart.header().warnings() {
+        eprintln!("Warning: ROM header: {}", w);
+    }
+}
+
+/// BootROM を読み込む。`cgb_boot.bin` があれば常にそれを使う（DMG カートも CGB BootROM が
+/// 互換モードで色付けする）。`--cgb` なら DMG BootROM には戻らない
+/// （DMG BootROM は互換モードのパレットを設定しないため、BootROM なしの CGB 初期値で起動する）。
+/// `--trace-log` のときは BootROM なし。
+fn load_bootrom(opts: &Options, cgb: bool) -> Bootrom {
+    if opts.trace_log.is_some() {
+        return Bootrom::disabled();
+    }
+    if let Some(bytes) = read_bootrom::<CGB_BOOTROM_SIZE>("cgb_boot.bin") {
+        return Bootrom::from_cgb_bytes(bytes);
+    }
+    if opts.cgb {
+        eprintln!("Warning: no usable cgb_boot.bin, using CGB init values");
+        return Bootrom::disabled();
+    }
+    if cgb {
+        eprintln!("Warning: no usable cgb_boot.bin, trying dmg_bootrom.bin");
+    }
+    match read_bootrom::<DMG_BOOTROM_SIZE>("dmg_bootrom.bin") {
+        Some(bytes) => Bootrom::from_bytes(bytes),
+        None => {
+            eprintln!("Warning: no usable dmg_bootrom.bin, using DMG init values");
             Bootrom::disabled()
         }
     }
 }
 
+/// BootROM ファイルの先頭 `N` バイト。無ければ `None`、短い・読めないときは警告して `None`
+fn read_bootrom<const N: usize>(path: &str) -> Option<[u8; N]> {
+    match std::fs::read(path) {
+        Ok(bytes) if bytes.len() >= N => bytes[..N].try_into().ok(),
+        Ok(bytes) => {
+            eprintln!(
+                "Warning: {} is truncated ({} bytes, expected {})",
+                path,
+                bytes.len(),
+                N
+            );
+            None
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            eprintln!("Warning: {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// バッテリーバックアップ付きカートなら `<rom>.sav` を外部 RAM へ読み込む。
+fn load_battery(cart: &mut cartridge::Cartridge, path: &std::path::Path) {
+    if !cart.has_battery() {
+        return;
+    }
+    if let Ok(data) = std::fs::read(path) {
+        cart.load_save_data(&data);
+        println!("Loaded save: {}", path.display());
+    }
+}
+
+/// 外部 RAM に書き込みがあれば `<rom>.sav` へ書き出す。
+fn flush_battery(cart: &mut cartridge::Cartridge, path: &std::path::Path) {
+    if !cart.has_battery() || !cart.ram_dirty {
+        return;
+    }
+    match std::fs::write(path, cart.save_data()) {
+        Ok(_) => {
+            cart.ram_dirty = false;
+            println!("Saved: {}", path.display());
+        }
+        Err(e) => eprintln!("Failed to save '{}': {}", path.display(), e),
+    }
+}
+
+/// F5/F8 でセーブステートを `<rom>.state` へ書き出す / 読み込む。
+fn handle_state_hotkeys<C: CartridgeBus, D: Display, A: AudioSink, S: SerialPort>(
+    gb: &mut GameBoy<C, D, A, lcd::SdlInput, S>,
+    keys: &lcd::HostKeys,
+    path: &std::path::Path,
+) {
+    if keys.save_state {
+        let mut buf = vec![0u8; gb.state_size()];
+        match gb.save_state(&mut buf) {
+            Ok(n) => match std::fs::write(path, &buf[..n]) {
+                Ok(()) => println!("State saved: {}", path.display()),
+                Err(e) => eprintln!("Failed to write '{}': {}", path.display(), e),
+            },
+            Err(e) => eprintln!("Failed to save state: {:?}", e),
+        }
+    }
+    if keys.load_state {
+        match std::fs::read(path) {
+            Ok(buf) => match gb.load_state(&buf) {
+                Ok(()) => println!("State loaded: {}", path.display()),
+                Err(e) => eprintln!("Failed to load state '{}': {:?}", path.display(), e),
+            },
+            Err(e) => eprintln!("Failed to read '{}': {}", path.display(), e),
+        }
+    }
+}
+
+/// F12 で表示中の画面を `<rom>-001.png` から順に空いている名前で保存する。
+fn save_screenshot(display: &lcd::SdlDisplay, rom_path: &str) {
+    let path = screenshot::next_path(std::path::Path::new(rom_path));
+    match display.save_screenshot(&path) {
+        Ok(()) => println!("Screenshot saved: {}", path.display()),
+        Err(e) => eprintln!("Failed to save screenshot: {}", e),
+    }
+}
+
 /// wall-clock catch-up 方式のメインループ。
 /// step() を現実時間に追いつくペースで呼び出し、quit が立ったら終了する。
 /// CGB ダブルスピード時は M_CYCLE_NS を半分にしてタイミングを調整する。
@@ -125,13 +597,33 @@ fn run_loop(mut step: impl FnMut() -> StepResult) {
 
 /// テストハーネス付きヘッドレスループ。タイミング制約なしで全力実行する。
 /// gb-host は常に gb-core の test-harness フィーチャーを有効化しているため無条件に使用する。
-fn run_headless<C: CartridgeBus>(
-    gb: &mut GameBoy<C, NullDisplay, NullAudio, NullInput>,
+/// `screenshot` があれば N フレーム目（1 始まり）を保存した時点でも終了する。
+fn run_headless<C: CartridgeBus, S: SerialPort>(
+    gb: &mut GameBoy<C, NullDisplay, NullAudio, NullInput, TestSerial<S>>,
+    mut trace: Option<&mut TraceLog>,
+    screenshot: Option<&(u64, std::path::PathBuf)>,
 ) {
+    let mut frames = 0u64;
     loop {
-        gb.step();
-        if gb.mmu().test.test_done {
-            let log = &gb.mmu().test.serial_log;
+        let result = trace_log::step(gb, trace.as_deref_mut());
+        if result.frame_ready
+            && let Some((at, path)) = screenshot
+        {
+            frames += 1;
+            if frames == *at {
+                let frame = gb.mmu().ppu.pixel_buffer();
+                match screenshot::save(path, LCD_WIDTH, LCD_HEIGHT, frame) {
+                    Ok(()) => println!("Screenshot saved: {}", path.display()),
+                    Err(e) => eprintln!("Failed to save screenshot: {}", e),
+                }
+                break;
+            }
+        }
+        if gb.mmu().test.test_done || gb.serial().finished() {
+            if let Some((at, _)) = screenshot {
+                eprintln!("Warning: test finished before frame {}, no screenshot saved", at);
+            }
+            let log = &gb.serial().log;
             if !log.is_empty() {
                 let text = core::str::from_utf8(log).unwrap_or("(invalid utf8)");
                 print!("{}", text);
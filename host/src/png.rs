@@ -0,0 +1,315 @@
+//! 依存クレートなしの最小 PNG エンコーダ / デコーダ。
+//!
+//! エンコードは圧縮を行わず、zlib の無圧縮 (stored) ブロックで IDAT を組み立てる。
+//! 出力サイズは大きくなるが、プリンタ出力やスクリーンショットなど画面サイズの画像には十分。
+//! デコードはインターレースなしの全カラータイプに対応し、RGB（適合テストの参照画像）か
+//! グレースケール（カメラの静止画入力）に変換して返す。
+
+/// 8 bit グレースケール画像を PNG にエンコードする（`pixels` は行優先で width*height バイト）
+pub fn encode_gray(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    encode(width, height, COLOR_GRAY, 1, pixels)
+}
+
+/// 8 bit RGB 画像を PNG にエンコードする（`pixels` は行優先で width*height*3 バイト）
+pub fn encode_rgb(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    encode(width, height, COLOR_RGB, 3, pixels)
+}
+
+/// RGB555 のフレームバッファ（GB の `Ppu::pixel_buffer`・GBA の `Gba::framebuffer`）を
+/// PNG にエンコードする。各 5 bit は上位ビットを下に複製して 0–255 に広げる。
+pub fn encode_rgb555(width: u32, height: u32, buffer: &[u16]) -> Vec<u8> {
+    let expand = |v: u16| ((v & 0x1F) << 3 | (v & 0x1F) >> 2) as u8;
+    let pixels: Vec<u8> =
+        buffer.iter().flat_map(|&px| [expand(px), expand(px >> 5), expand(px >> 10)]).collect();
+    encode_rgb(width, height, &pixels)
+}
+
+/// PNG を 8 bit グレースケールにデコードする。(幅, 高さ, 行優先のピクセル) を返す。
+/// アルファは無視し、カラーは輝度 (BT.601) に変換する。
+pub fn decode_gray(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let luma =
+        |[r, g, b]: [u8; 3]| ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8;
+    let (width, height, pixels) = decode_rgb(data)?;
+    Ok((width, height, pixels.into_iter().map(luma).collect()))
+}
+
+/// PNG を 8 bit RGB にデコードする。(幅, 高さ, 行優先のピクセル) を返す。アルファは無視する。
+pub fn decode_rgb(data: &[u8]) -> Result<(u32, u32, Vec<[u8; 3]>), String> {
+    if data.get(..8) != Some(&SIGNATURE[..]) {
+        return Err("PNG: bad signature".into());
+    }
+    let mut pos = 8;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut idat = Vec::new();
+    loop {
+        let len_bytes = data.get(pos..pos + 8).ok_or("PNG: truncated chunk")?;
+        let len =
+            u32::from_be_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let body = data.get(pos + 8..pos + 8 + len).ok_or("PNG: truncated chunk")?;
+        let crc = data.get(pos + 8 + len..pos + 12 + len).ok_or("PNG: truncated chunk")?;
+        if crc32(&data[pos + 4..pos + 8 + len]).to_be_bytes() != crc {
+            return Err("PNG: CRC mismatch".into());
+        }
+        match kind {
+            b"IHDR" if body.len() == 13 => header = Some(body),
+            b"PLTE" => palette = body,
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos += 12 + len;
+    }
+    let ihdr = header.ok_or("PNG: missing IHDR")?;
+    let width = u32::from_be_bytes([ihdr[0], ihdr[1], ihdr[2], ihdr[3]]);
+    let height = u32::from_be_bytes([ihdr[4], ihdr[5], ihdr[6], ihdr[7]]);
+    let (depth, color_type) = (ihdr[8], ihdr[9]);
+    if ihdr[12] != 0 {
+        return Err("PNG: interlaced images are not supported".into());
+    }
+    let channels = match (color_type, depth) {
+        (COLOR_GRAY, 1 | 2 | 4 | 8 | 16) | (COLOR_PALETTE, 1 | 2 | 4 | 8) => 1,
+        (COLOR_GRAY_ALPHA, 8 | 16) => 2,
+        (COLOR_RGB, 8 | 16) => 3,
+        (COLOR_RGBA, 8 | 16) => 4,
+        _ => return Err(format!("PNG: unsupported color type {} / depth {}", color_type, depth)),
+    };
+    let bits_per_pixel = channels * depth as usize;
+    let stride = (width as usize * bits_per_pixel).div_ceil(8);
+    let raw = crate::inflate::zlib_decompress(&idat)?;
+    if raw.len() < (stride + 1) * height as usize {
+        return Err("PNG: image data too short".into());
+    }
+    let rows = unfilter(&raw, stride, height as usize, bits_per_pixel.div_ceil(8))?;
+
+    // 各サンプルの上位 8 bit（1/2/4 bit は 0–255 に引き伸ばす）
+    let sample = |row: &[u8], index: usize| -> u8 {
+        match depth {
+            8 => row[index],
+            16 => row[index * 2],
+            _ => {
+                let bit = index * depth as usize;
+                let v = (row[bit / 8] >> (8 - depth as usize - bit % 8)) & ((1 << depth) - 1);
+                if color_type == COLOR_PALETTE {
+                    v
+                } else {
+                    (v as u16 * 255 / ((1 << depth) - 1)) as u8
+                }
+            }
+        }
+    };
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for row in rows.chunks_exact(stride) {
+        for x in 0..width as usize {
+            let i = x * channels;
+            pixels.push(match color_type {
+                COLOR_GRAY | COLOR_GRAY_ALPHA => [sample(row, i); 3],
+                COLOR_PALETTE => {
+                    let p = sample(row, i) as usize * 3;
+                    let rgb = palette.get(p..p + 3).ok_or("PNG: palette index out of range")?;
+                    [rgb[0], rgb[1], rgb[2]]
+                }
+                _ => [sample(row, i), sample(row, i + 1), sample(row, i + 2)],
+            });
+        }
+    }
+    Ok((width, height, pixels))
+}
+
+/// 各行のフィルタを外す（`bpp` はフィルタの「左のピクセル」までのバイト数）
+fn unfilter(raw: &[u8], stride: usize, height: usize, bpp: usize) -> Result<Vec<u8>, String> {
+    let mut out = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let src = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, rest) = out.split_at_mut(y * stride);
+        let prev = if y > 0 { &done[(y - 1) * stride..] } else { &[][..] };
+        let cur = &mut rest[..stride];
+        for x in 0..stride {
+            let a = if x >= bpp { cur[x - bpp] } else { 0 };
+            let b = prev.get(x).copied().unwrap_or(0);
+            let c = if x >= bpp { prev.get(x - bpp).copied().unwrap_or(0) } else { 0 };
+            let predictor = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(format!("PNG: invalid filter type {}", filter)),
+            };
+            cur[x] = src[x].wrapping_add(predictor);
+        }
+    }
+    Ok(out)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const COLOR_GRAY: u8 = 0;
+const COLOR_RGB: u8 = 2;
+const COLOR_PALETTE: u8 = 3;
+const COLOR_GRAY_ALPHA: u8 = 4;
+const COLOR_RGBA: u8 = 6;
+/// stored ブロック 1 つに入る最大バイト数
+const STORED_MAX: usize = 0xFFFF;
+
+fn encode(width: u32, height: u32, color_type: u8, channels: usize, pixels: &[u8]) -> Vec<u8> {
+    let stride = width as usize * channels;
+    assert_eq!(pixels.len(), stride * height as usize, "pixel buffer size mismatch");
+
+    // 各行の先頭にフィルタ種別 0 (None) を付ける
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    for row in pixels.chunks_exact(stride.max(1)).take(height as usize) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // bit depth 8, color type, compression 0, filter 0, interlace 0
+    ihdr.extend_from_slice(&[8, color_type, 0, 0, 0]);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&SIGNATURE);
+    write_chunk(&mut out, b"IHDR", &ihdr);
+    write_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// 無圧縮ブロックだけの zlib ストリーム
+pub(crate) fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(STORED_MAX).max(1);
+    let mut out = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    // CMF: deflate / 32K window, FLG: FCHECK で (CMF*256+FLG) % 31 == 0
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut chunks = data.chunks(STORED_MAX).peekable();
+    if chunks.peek().is_none() {
+        out.extend_from_slice(&[0x01, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        out.push(last as u8); // BFINAL, BTYPE=00
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+pub(crate) fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_and_layout() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+
+        let png = encode_gray(3, 2, &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(png[16..20], 3u32.to_be_bytes());
+        assert_eq!(png[20..24], 2u32.to_be_bytes());
+        // 末尾は空の IEND チャンク
+        assert_eq!(png[png.len() - 12..], [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]);
+    }
+
+    #[test]
+    fn large_images_split_stored_blocks() {
+        let data = vec![0x55u8; 200_000];
+        let z = zlib_stored(&data);
+        // ヘッダ 2 + ブロックヘッダ 5 x 4 + データ + adler 4
+        assert_eq!(z.len(), 2 + 5 * 4 + data.len() + 4);
+        assert_eq!(z[2], 0);
+        assert_eq!(z[2 + 5 + STORED_MAX], 0);
+        assert_eq!(z[2 + 3 * (5 + STORED_MAX)], 1);
+    }
+
+    #[test]
+    fn decode_round_trip_and_filters() {
+        let pixels: Vec<u8> = (0..35).map(|i| (i * 7) as u8).collect();
+        assert_eq!(decode_gray(&encode_gray(7, 5, &pixels)).unwrap(), (7, 5, pixels));
+        // RGB555: 黒, 白, 赤, 中間の青
+        let frame = [0x0000, 0x7FFF, 0x001F, 0x10 << 10];
+        let expected = vec![[0, 0, 0], [255, 255, 255], [255, 0, 0], [0, 0, 0x84]];
+        assert_eq!(decode_rgb(&encode_rgb555(2, 2, &frame)).unwrap(), (2, 2, expected));
+
+        // RGB 2x2（1 行目 Sub フィルタ, 2 行目 Paeth フィルタ）: 赤, 緑 / 青, 白
+        let rgb = [
+            0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x08, 0x02, 0x00, 0x00,
+            0x00, 0xFD, 0xD4, 0x9A, 0x73, 0x00, 0x00, 0x00, 0x15, 0x49, 0x44, 0x41, 0x54, 0x78,
+            0xDA, 0x63, 0xFC, 0xCF, 0xC0, 0xC0, 0xF8, 0x9F, 0x81, 0x85, 0x91, 0xE1, 0x3F, 0x90,
+            0x05, 0x00, 0x1D, 0x2B, 0x04, 0x04, 0x34, 0x41, 0x20, 0xC6, 0x00, 0x00, 0x00, 0x00,
+            0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+        ];
+        assert_eq!(decode_gray(&rgb).unwrap(), (2, 2, vec![76, 149, 29, 255]));
+        let colors = vec![[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+        assert_eq!(decode_rgb(&rgb).unwrap(), (2, 2, colors));
+
+        // 2 bit パレット 3x1: 黒, 灰, 白
+        let pal = [
+            0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x00, 0x00,
+            0x00, 0x66, 0x8E, 0xFC, 0x27, 0x00, 0x00, 0x00, 0x09, 0x50, 0x4C, 0x54, 0x45, 0x00,
+            0x00, 0x00, 0x80, 0x80, 0x80, 0xFF, 0xFF, 0xFF, 0xC1, 0xD2, 0xDD, 0xA3, 0x00, 0x00,
+            0x00, 0x0A, 0x49, 0x44, 0x41, 0x54, 0x78, 0xDA, 0x63, 0x90, 0x00, 0x00, 0x00, 0x1A,
+            0x00, 0x19, 0x80, 0x00, 0x8E, 0xBB, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44,
+            0xAE, 0x42, 0x60, 0x82,
+        ];
+        assert_eq!(decode_gray(&pal).unwrap(), (3, 1, vec![0, 128, 255]));
+
+        let mut broken = rgb;
+        broken[20] ^= 1;
+        assert!(decode_gray(&broken).is_err());
+    }
+}
@@ -0,0 +1,384 @@
+//! Game Boy Printer（シリアル接続の周辺機器）。
+//!
+//! GB がマスター（内部クロック）でパケットを送り、プリンタは 1 バイトごとに応答を返す。
+//!
+//! パケット: `88 33` `cmd` `圧縮` `長さ(LE 2)` `データ` `チェックサム(LE 2)` `00` `00`
+//! - チェックサムは cmd からデータ末尾までのバイト和
+//! - 最後の 2 バイトの応答が `0x81`（接続確認）とステータス。それ以外の応答は `0x00`
+//!
+//! 印字した画像は紙送り（PRINT の後マージン）で 1 枚に区切り、PNG として書き出す。
+
+use gb_core::platform::SerialPort;
+use std::path::PathBuf;
+
+use crate::png;
+
+const CMD_INIT: u8 = 0x01;
+const CMD_PRINT: u8 = 0x02;
+const CMD_DATA: u8 = 0x04;
+const CMD_STATUS: u8 = 0x0F;
+
+/// ステータス bit0: チェックサム不一致
+pub const STATUS_CHECKSUM: u8 = 1 << 0;
+/// ステータス bit1: 印字中
+pub const STATUS_PRINTING: u8 = 1 << 1;
+/// ステータス bit2: 画像データ受信完了（印字待ち）
+pub const STATUS_FULL: u8 = 1 << 2;
+/// ステータス bit3: 未印字のデータあり
+pub const STATUS_UNPROCESSED: u8 = 1 << 3;
+
+/// 用紙幅（ピクセル）
+pub const WIDTH: usize = 160;
+/// 1 バンド = 2 タイル行 (40 タイル x 16 バイト)
+const BAND_BYTES: usize = 640;
+/// プリンタの画像バッファ (8 KiB)
+const BUFFER_BYTES: usize = 0x2000;
+/// マージン 1 単位の紙送り量（ピクセル行）
+const MARGIN_ROWS: usize = 8;
+/// 1 ピクセル行の印字にかかる時間（M-cycle）
+const ROW_CYCLES: u32 = 2048;
+
+/// 階調 0..3 の出力輝度
+const SHADES: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Magic1,
+    Magic2,
+    Command,
+    Compression,
+    LenLo,
+    LenHi,
+    Data,
+    ChecksumLo,
+    ChecksumHi,
+    Alive,
+    Status,
+}
+
+pub struct Printer {
+    out_dir: PathBuf,
+    state: State,
+    command: u8,
+    compressed: bool,
+    len: u16,
+    packet: Vec<u8>,
+    sum: u16,
+    checksum: u16,
+    status: u8,
+    /// 受信済みの 2bpp タイルデータ
+    buffer: Vec<u8>,
+    /// 印字中の残り M-cycle
+    busy: u32,
+    /// 切り離していない用紙（階調値 0..3 の行）
+    paper: Vec<u8>,
+    /// 書き出した枚数
+    pages: u32,
+}
+
+impl Printer {
+    /// 印字結果を `out_dir` に `print_NNNN.png` として書き出すプリンタ
+    pub fn new(out_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            out_dir: out_dir.into(),
+            state: State::Magic1,
+            command: 0,
+            compressed: false,
+            len: 0,
+            packet: Vec::new(),
+            sum: 0,
+            checksum: 0,
+            status: 0,
+            buffer: Vec::new(),
+            busy: 0,
+            paper: Vec::new(),
+            pages: 0,
+        }
+    }
+
+    /// 現在のステータスバイト
+    pub fn status(&self) -> u8 {
+        self.status
+    }
+
+    /// 1 バイト受信し、同時に送り返すバイトを返す
+    fn receive(&mut self, byte: u8) -> u8 {
+        match self.state {
+            State::Magic1 => {
+                if byte == 0x88 {
+                    self.state = State::Magic2;
+                }
+            }
+            State::Magic2 => {
+                self.state = match byte {
+                    0x33 => State::Command,
+                    0x88 => State::Magic2,
+                    _ => State::Magic1,
+                };
+            }
+            State::Command => {
+                self.command = byte;
+                self.sum = byte as u16;
+                self.state = State::Compression;
+            }
+            State::Compression => {
+                self.compressed = byte & 1 != 0;
+                self.sum = self.sum.wrapping_add(byte as u16);
+                self.state = State::LenLo;
+            }
+            State::LenLo => {
+                self.len = byte as u16;
+                self.sum = self.sum.wrapping_add(byte as u16);
+                self.state = State::LenHi;
+            }
+            State::LenHi => {
+                self.len |= (byte as u16) << 8;
+                self.sum = self.sum.wrapping_add(byte as u16);
+                self.packet.clear();
+                self.state = if self.len == 0 { State::ChecksumLo } else { State::Data };
+            }
+            State::Data => {
+                self.packet.push(byte);
+                self.sum = self.sum.wrapping_add(byte as u16);
+                if self.packet.len() == self.len as usize {
+                    self.state = State::ChecksumLo;
+                }
+            }
+            State::ChecksumLo => {
+                self.checksum = byte as u16;
+                self.state = State::ChecksumHi;
+            }
+            State::ChecksumHi => {
+                self.checksum |= (byte as u16) << 8;
+                if self.checksum == self.sum {
+                    self.status &= !STATUS_CHECKSUM;
+                    self.execute();
+                } else {
+                    self.status |= STATUS_CHECKSUM;
+                }
+                self.state = State::Alive;
+            }
+            State::Alive => {
+                self.state = State::Status;
+                return 0x81;
+            }
+            State::Status => {
+                self.state = State::Magic1;
+                return self.status;
+            }
+        }
+        0x00
+    }
+
+    fn execute(&mut self) {
+        match self.command {
+            CMD_INIT => {
+                self.buffer.clear();
+                self.status = 0;
+            }
+            CMD_DATA => {
+                if self.packet.is_empty() {
+                    // 長さ 0 の DATA はデータ終端
+                    if !self.buffer.is_empty() {
+                        self.status |= STATUS_FULL;
+                    }
+                } else {
+                    if self.compressed {
+                        decompress(&self.packet, &mut self.buffer);
+                    } else {
+                        self.buffer.extend_from_slice(&self.packet);
+                    }
+                    self.buffer.truncate(BUFFER_BYTES);
+                    self.status |= STATUS_UNPROCESSED;
+                }
+            }
+            CMD_PRINT if self.packet.len() >= 4 => {
+                let (sheets, margins, palette) = (self.packet[0], self.packet[1], self.packet[2]);
+                // packet[3]（濃度）は出力に反映しない
+                let rows = self.print(sheets, margins >> 4, margins & 0x0F, palette);
+                self.buffer.clear();
+                self.status = (self.status & !(STATUS_UNPROCESSED | STATUS_FULL)) | STATUS_PRINTING;
+                self.busy = (rows as u32).max(1) * ROW_CYCLES;
+            }
+            CMD_STATUS => {}
+            _ => {}
+        }
+    }
+
+    /// バッファの画像を用紙に印字し、印字した行数を返す。
+    /// 後マージンがあれば紙を切り離して 1 枚として書き出す。
+    fn print(&mut self, sheets: u8, before: u8, after: u8, palette: u8) -> usize {
+        let start = self.paper.len();
+        self.feed(before as usize * MARGIN_ROWS);
+        // 枚数 0 は紙送りのみ
+        for _ in 0..sheets {
+            self.render(palette);
+        }
+        self.feed(after as usize * MARGIN_ROWS);
+        let rows = (self.paper.len() - start) / WIDTH;
+        if after > 0 {
+            self.cut();
+        }
+        rows
+    }
+
+    fn feed(&mut self, rows: usize) {
+        let len = self.paper.len() + rows * WIDTH;
+        self.paper.resize(len, 0);
+    }
+
+    /// タイルデータ（20 タイル x 2 行のバンドの並び）を階調値の行に展開する
+    fn render(&mut self, palette: u8) {
+        // パレット 0 は既定値 (BGP と同じ 0xE4) として扱うゲームがある
+        let palette = if palette == 0 { 0xE4 } else { palette };
+        let rows = self.buffer.len() / BAND_BYTES * 16;
+        let start = self.paper.len();
+        self.paper.resize(start + rows * WIDTH, 0);
+        let (tiles, _) = self.buffer.as_chunks::<16>();
+        for (tile, data) in tiles.iter().enumerate().take(rows / 8 * 20) {
+            let (tx, ty) = (tile % 20, tile / 20);
+            for (y, &[lo, hi]) in data.as_chunks::<2>().0.iter().enumerate() {
+                let row = start + (ty * 8 + y) * WIDTH + tx * 8;
+                for x in 0..8 {
+                    let bit = 7 - x;
+                    let color = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+                    self.paper[row + x] = (palette >> (color * 2)) & 0x03;
+                }
+            }
+        }
+    }
+
+    /// 用紙を切り離して PNG に書き出す
+    fn cut(&mut self) {
+        if self.paper.is_empty() {
+            return;
+        }
+        let paper = std::mem::take(&mut self.paper);
+        let pixels: Vec<u8> = paper.iter().map(|&s| SHADES[s as usize]).collect();
+        let png = png::encode_gray(WIDTH as u32, (paper.len() / WIDTH) as u32, &pixels);
+        let path = loop {
+            self.pages += 1;
+            let path = self.out_dir.join(format!("print_{:04}.png", self.pages));
+            if !path.exists() {
+                break path;
+            }
+        };
+        match std::fs::write(&path, png) {
+            Ok(_) => println!("Printed: {}", path.display()),
+            Err(e) => eprintln!("Failed to write '{}': {}", path.display(), e),
+        }
+    }
+}
+
+/// RLE 展開。制御バイト bit7=1 なら次の 1 バイトを (下位7bit + 2) 回、
+/// bit7=0 なら続く (値 + 1) バイトをそのままコピーする。
+fn decompress(data: &[u8], out: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < data.len() {
+        let ctrl = data[i];
+        i += 1;
+        if ctrl & 0x80 != 0 {
+            let Some(&byte) = data.get(i) else { break };
+            out.extend(std::iter::repeat_n(byte, (ctrl & 0x7F) as usize + 2));
+            i += 1;
+        } else {
+            let end = (i + ctrl as usize + 1).min(data.len());
+            out.extend_from_slice(&data[i..end]);
+            i = end;
+        }
+    }
+}
+
+impl SerialPort for Printer {
+    fn transfer(&mut self, out: u8) -> u8 {
+        self.receive(out)
+    }
+
+    fn poll_external(&mut self, _out: u8) -> Option<u8> {
+        // プリンタがクロックを出すことはない
+        None
+    }
+
+    fn tick(&mut self) {
+        if self.busy > 0 {
+            self.busy -= 1;
+            if self.busy == 0 {
+                self.status &= !STATUS_PRINTING;
+            }
+        }
+    }
+}
+
+impl Drop for Printer {
+    fn drop(&mut self) {
+        // 切り離していない印字も残す
+        self.cut();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// パケットを送り、最後の 2 バイトの応答（0x81, ステータス）を返す
+    fn send(p: &mut Printer, cmd: u8, compressed: bool, data: &[u8]) -> (u8, u8) {
+        let mut body = vec![cmd, compressed as u8, data.len() as u8, (data.len() >> 8) as u8];
+        body.extend_from_slice(data);
+        let sum = body.iter().fold(0u16, |s, &b| s.wrapping_add(b as u16));
+        let mut bytes = vec![0x88, 0x33];
+        bytes.extend_from_slice(&body);
+        bytes.extend_from_slice(&sum.to_le_bytes());
+        let replies: Vec<u8> = bytes.iter().map(|&b| p.transfer(b)).collect();
+        assert!(replies.iter().all(|&r| r == 0));
+        (p.transfer(0), p.transfer(0))
+    }
+
+    #[test]
+    fn rle_decompression() {
+        let mut out = Vec::new();
+        decompress(&[0x81, 0xAA, 0x02, 1, 2, 3, 0x80, 0x55], &mut out);
+        assert_eq!(out, [0xAA, 0xAA, 0xAA, 1, 2, 3, 0x55, 0x55]);
+    }
+
+    #[test]
+    fn print_writes_png_with_margins() {
+        let dir = std::env::temp_dir().join(format!("gb-printer-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut p = Printer::new(&dir);
+
+        assert_eq!(send(&mut p, CMD_INIT, false, &[]), (0x81, 0x00));
+        // 1 バンドを全ピクセル色 3 で（RLE 圧縮: 640 バイトの 0xFF）
+        let mut band = Vec::new();
+        for _ in 0..4 {
+            band.extend_from_slice(&[0xFF, 0xFF]); // 0x7F + 2 = 129 バイト
+        }
+        band.extend_from_slice(&[0xFA, 0xFF]); // 残り 124 バイト
+        assert_eq!(send(&mut p, CMD_DATA, true, &band), (0x81, STATUS_UNPROCESSED));
+        assert_eq!(p.buffer.len(), BAND_BYTES);
+        assert_eq!(send(&mut p, CMD_DATA, false, &[]).1, STATUS_UNPROCESSED | STATUS_FULL);
+
+        // チェックサム不一致はステータスで通知し、コマンドは実行しない
+        for b in [0x88, 0x33, CMD_INIT, 0, 0, 0, 0xFF, 0xFF] {
+            p.transfer(b);
+        }
+        assert_eq!((p.transfer(0), p.transfer(0)), (0x81, STATUS_UNPROCESSED | STATUS_FULL | STATUS_CHECKSUM));
+
+        // 1 枚・前マージン 1・後マージン 2・色 3 を階調 1 に
+        assert_eq!(send(&mut p, CMD_PRINT, false, &[1, 0x12, 0x40, 0x40]).1, STATUS_PRINTING);
+        let busy = p.busy;
+        for _ in 0..busy {
+            p.tick();
+        }
+        assert_eq!(send(&mut p, CMD_STATUS, false, &[]).1, 0);
+
+        let png = std::fs::read(dir.join("print_0001.png")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        assert_eq!(height as usize, 3 * MARGIN_ROWS + 16);
+        // 先頭の画像行は階調 1 (IDAT の zlib ヘッダ・stored ヘッダ・フィルタバイトの後)
+        let idat = png.windows(4).position(|w| w == b"IDAT").unwrap() + 4;
+        let first_image_row = idat + 2 + 5 + MARGIN_ROWS * (WIDTH + 1) + 1;
+        assert_eq!(png[first_image_row], SHADES[1]);
+        assert_eq!(png[idat + 2 + 5 + 1], SHADES[0]);
+    }
+}
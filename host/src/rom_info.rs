@@ -0,0 +1,118 @@
+//! `--info`: ROM ヘッダを解析して表示する（ROM は実行しない）。
+
+use gb_core::cartridge::{CartridgeType, Header, new_licensee_name};
+use gba_core::header::Header as GbaHeader;
+use std::error::Error;
+use std::fmt::{self, Write};
+
+/// 拡張子が `.gba` なら GBA、それ以外は GB / GBC のヘッダとして表示する
+pub fn print(path: &str) -> Result<(), Box<dyn Error>> {
+    let rom = std::fs::read(path)?;
+    let mut out = String::new();
+    writeln!(out, "File:         {} ({} bytes)", path, rom.len())?;
+    if path.ends_with(".gba") {
+        write_gba(&mut out, &GbaHeader::parse(&rom)?)?;
+    } else {
+        write_gb(&mut out, &Header::parse(&rom)?)?;
+    }
+    print!("{}", out);
+    Ok(())
+}
+
+/// 対応していないカートリッジタイプの名前
+fn unsupported_type_name(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0x08 => "ROM+RAM",
+        0x09 => "ROM+RAM+BATTERY",
+        0x1C => "MBC5+RUMBLE",
+        0x1D => "MBC5+RUMBLE+RAM",
+        0x1E => "MBC5+RUMBLE+RAM+BATTERY",
+        0x20 => "MBC6",
+        0xFD => "TAMA5",
+        0xFE => "HuC3",
+        _ => return None,
+    })
+}
+
+fn write_gb(out: &mut String, h: &Header) -> fmt::Result {
+    writeln!(out, "Title:        {}", h.title())?;
+    match h.cartridge_type {
+        CartridgeType::Unknown(code) => writeln!(
+            out,
+            "Type:         {} (0x{:02X}, unsupported)",
+            unsupported_type_name(code).unwrap_or("unknown"),
+            code
+        )?,
+        t => writeln!(out, "Type:         {:?} (0x{:02X})", t, t.code())?,
+    }
+    match h.rom_bytes() {
+        Some(n) => writeln!(out, "ROM size:     {} KiB (0x{:02X})", n / 1024, h.rom_size)?,
+        None => writeln!(out, "ROM size:     unknown (0x{:02X})", h.rom_size)?,
+    }
+    writeln!(out, "RAM size:     {} KiB (0x{:02X})", h.ram_bytes() / 1024, h.ram_size)?;
+    let cgb = match h.cgb_flag {
+        0x80 => "supported",
+        0xC0 => "required",
+        _ => "no",
+    };
+    writeln!(out, "CGB:          {} (0x{:02X})", cgb, h.cgb_flag)?;
+    let sgb = if h.is_sgb() { "yes" } else { "no" };
+    writeln!(out, "SGB:          {} (0x{:02X})", sgb, h.sgb_flag)?;
+    let dest = if h.is_japanese() { "Japan" } else { "Overseas" };
+    writeln!(out, "Destination:  {} (0x{:02X})", dest, h.destination)?;
+    writeln!(out, "Licensee:     {}", h.licensee)?;
+    writeln!(out, "Version:      {}", h.version)?;
+    writeln!(out, "Header sum:   0x{:02X}", h.header_checksum)?;
+    writeln!(out, "Global sum:   0x{:04X}", h.global_checksum)?;
+    write_warnings(out, h.warnings())
+}
+
+fn write_gba(out: &mut String, h: &GbaHeader) -> fmt::Result {
+    writeln!(out, "Title:        {}", h.title())?;
+    writeln!(out, "Game code:    {}", h.game_code())?;
+    let [a, b] = h.maker_code;
+    match new_licensee_name(h.maker_code) {
+        Some(name) => writeln!(out, "Maker:        \"{}{}\" ({})", a as char, b as char, name)?,
+        None => writeln!(out, "Maker:        \"{}{}\"", a as char, b as char)?,
+    }
+    writeln!(out, "Version:      {}", h.version)?;
+    writeln!(out, "Complement:   0x{:02X}", h.complement)?;
+    write_warnings(out, h.warnings())
+}
+
+fn write_warnings<W: fmt::Display>(out: &mut String, warnings: &[W]) -> fmt::Result {
+    if warnings.is_empty() {
+        writeln!(out, "Header OK")?;
+    }
+    for w in warnings {
+        writeln!(out, "Warning:      {}", w)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(cartridge_type: u8) -> Header {
+        let mut rom = vec![0; 0x8000];
+        rom[0x134..0x138].copy_from_slice(b"TEST");
+        rom[0x147] = cartridge_type;
+        Header::parse(&rom).unwrap()
+    }
+
+    #[test]
+    fn shows_supported_and_unsupported_types() {
+        let mut out = String::new();
+        write_gb(&mut out, &header(0x01)).unwrap();
+        assert!(out.starts_with("Title:        TEST\nType:         Mbc1 (0x01)\n"));
+        assert!(out.contains("ROM size:     32 KiB (0x00)\n"));
+
+        let mut out = String::new();
+        write_gb(&mut out, &header(0xFE)).unwrap();
+        assert!(out.contains("Type:         HuC3 (0xFE, unsupported)\n"));
+        let mut out = String::new();
+        write_gb(&mut out, &header(0x42)).unwrap();
+        assert!(out.contains("Type:         unknown (0x42, unsupported)\n"));
+    }
+}
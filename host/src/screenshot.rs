@@ -0,0 +1,40 @@
+//! スクリーンショット: RGB555 のフレームバッファを PNG で保存する。
+//!
+//! GB（F12・`--screenshot-at-frame`）と GBA（F12）のフロントエンドが共用する。
+
+use crate::png;
+use std::path::{Path, PathBuf};
+
+/// `frame`（width×height、行優先の RGB555）を `path` に PNG で書き出す
+pub fn save(path: &Path, width: usize, height: usize, frame: &[u16]) -> Result<(), String> {
+    let data = png::encode_rgb555(width as u32, height as u32, frame);
+    std::fs::write(path, data).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// ホットキー用の保存先。`<rom>-001.png` から順にまだ存在しない名前を返す
+pub fn next_path(rom_path: &Path) -> PathBuf {
+    let stem = rom_path.with_extension("");
+    (1..)
+        .map(|n| PathBuf::from(format!("{}-{:03}.png", stem.display(), n)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_screenshots_after_the_rom() {
+        let dir = std::env::temp_dir().join(format!("gb-screenshot-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rom = dir.join("game.gb");
+        assert_eq!(next_path(&rom), dir.join("game-001.png"));
+
+        save(&next_path(&rom), 2, 1, &[0x7FFF, 0x0000]).unwrap();
+        assert_eq!(next_path(&rom), dir.join("game-002.png"));
+        let data = std::fs::read(dir.join("game-001.png")).unwrap();
+        assert_eq!(png::decode_rgb(&data).unwrap(), (2, 1, vec![[255; 3], [0; 3]]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
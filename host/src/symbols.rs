@@ -0,0 +1,160 @@
+//! RGBDS / no$gmb 形式のシンボルファイル（`<rom>.sym`）。
+//!
+//! 1 行 1 ラベルで `BANK:ADDR Label`（いずれも 16 進）。`;` 以降はコメント、
+//! `[labels]` のようなセクション見出しは読み飛ばす。
+//!
+//! アドレスからの逆引きは、同じバンク・同じメモリ領域にある直前のラベルを使って
+//! `Label+$12` の形にする。バンクは呼び出し側が [`GameBoy::bank_at`] で渡す
+//! （ROM 0x4000–0x7FFF・VRAM・外部 RAM・WRAM 0xD000–0xDFFF 以外はバンクを区別しない）。
+//!
+//! [`GameBoy::bank_at`]: gb_core::gameboy::GameBoy::bank_at
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+#[derive(Default, Clone)]
+pub struct SymbolTable {
+    /// (バンク, アドレス) → ラベル。同じ位置に複数あれば先に書かれたもの
+    by_addr: BTreeMap<(usize, u16), String>,
+    by_name: HashMap<String, (usize, u16)>,
+}
+
+impl SymbolTable {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut table = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split(';').next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+            let parsed = line.split_once(char::is_whitespace).and_then(|(loc, name)| {
+                let (bank, addr) = loc.split_once(':')?;
+                let bank = usize::from_str_radix(bank, 16).ok()?;
+                let addr = u16::from_str_radix(addr, 16).ok()?;
+                Some((bank, addr, name.trim()))
+            });
+            let Some((bank, addr, name)) = parsed else {
+                return Err(format!("line {}: expected 'BANK:ADDR Label'", i + 1));
+            };
+            let key = (bank_key(bank, addr), addr);
+            table.by_addr.entry(key).or_insert_with(|| name.to_string());
+            table.by_name.insert(name.to_string(), (bank, addr));
+        }
+        Ok(table)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    /// ラベル名から (バンク, アドレス)
+    pub fn resolve(&self, name: &str) -> Option<(usize, u16)> {
+        self.by_name.get(name).copied()
+    }
+
+    /// ちょうど `addr` に置かれたラベル
+    pub fn label_at(&self, bank: usize, addr: u16) -> Option<&str> {
+        self.by_addr.get(&(bank_key(bank, addr), addr)).map(String::as_str)
+    }
+
+    /// `addr` を `Label` / `Label+$12` で表す。同じ領域の手前にラベルがなければ None
+    pub fn name(&self, bank: usize, addr: u16) -> Option<String> {
+        let bank = bank_key(bank, addr);
+        let ((_, base), label) =
+            self.by_addr.range((bank, region_start(addr))..=(bank, addr)).next_back()?;
+        Some(match addr - base {
+            0 => label.clone(),
+            offset => format!("{}+${:X}", label, offset),
+        })
+    }
+}
+
+/// バンク切替のある領域（ROMX・VRAM・外部 RAM・WRAMX）か
+pub fn is_banked(addr: u16) -> bool {
+    matches!(addr, 0x4000..=0xBFFF | 0xD000..=0xDFFF)
+}
+
+/// バンク切替のある領域だけバンク番号を区別する
+fn bank_key(bank: usize, addr: u16) -> usize {
+    if is_banked(addr) { bank } else { 0 }
+}
+
+/// 逆引きでラベルを探す範囲の先頭（領域をまたいだ `Label+$off` にしない）
+fn region_start(addr: u16) -> u16 {
+    match addr {
+        0x0000..=0x3FFF => 0x0000,
+        0x4000..=0x7FFF => 0x4000,
+        0x8000..=0x9FFF => 0x8000,
+        0xA000..=0xBFFF => 0xA000,
+        0xC000..=0xCFFF => 0xC000,
+        0xD000..=0xDFFF => 0xD000,
+        0xFF80..=0xFFFE => 0xFF80,
+        // エコー RAM・OAM・I/O・IE は完全一致のみ
+        _ => addr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYM: &str = "\
+; File generated by rgblink
+[labels]
+00:0150 Main
+00:0158 Main.loop
+01:4000 BankedFunc
+02:4000 OtherBank
+00:C000 wCounter
+01:8800 vBankedTiles ; VRAM bank 1
+01:A000 sSlot1
+02:A000 sSlot2
+01:D000 wBanked ; WRAMX bank 1
+00:FF80 hDMA
+";
+
+    #[test]
+    fn resolves_names_and_bank_aware_addresses() {
+        let syms = SymbolTable::parse(SYM).unwrap();
+        assert_eq!(syms.len(), 10);
+        assert_eq!(syms.resolve("OtherBank"), Some((2, 0x4000)));
+        assert_eq!(syms.resolve("Main.loop"), Some((0, 0x0158)));
+        assert_eq!(syms.resolve("Missing"), None);
+
+        assert_eq!(syms.label_at(0, 0x0150), Some("Main"));
+        assert_eq!(syms.name(0, 0x015A).as_deref(), Some("Main.loop+$2"));
+        // ROMX は割り当て中のバンクで引き分ける
+        assert_eq!(syms.name(1, 0x4010).as_deref(), Some("BankedFunc+$10"));
+        assert_eq!(syms.name(2, 0x4000).as_deref(), Some("OtherBank"));
+        assert_eq!(syms.name(3, 0x4000), None);
+        // ROM0 のラベルを ROMX のアドレスに使わない
+        assert_eq!(syms.name(3, 0x4100), None);
+        assert_eq!(syms.name(1, 0xD004).as_deref(), Some("wBanked+$4"));
+        assert_eq!(syms.name(2, 0xD004), None);
+        // 外部 RAM・VRAM もバンクで引き分ける
+        assert_eq!(syms.name(1, 0xA010).as_deref(), Some("sSlot1+$10"));
+        assert_eq!(syms.name(2, 0xA010).as_deref(), Some("sSlot2+$10"));
+        assert_eq!(syms.name(0, 0xA010), None);
+        assert_eq!(syms.label_at(1, 0x8800), Some("vBankedTiles"));
+        assert_eq!(syms.label_at(0, 0x8800), None);
+        assert_eq!(syms.name(0, 0xFF81).as_deref(), Some("hDMA+$1"));
+        assert_eq!(syms.name(0, 0xFF40), None);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let err = SymbolTable::parse("00:0150 Main\nMain2\n").err().unwrap();
+        assert_eq!(err, "line 2: expected 'BANK:ADDR Label'");
+        assert!(SymbolTable::parse("zz:0150 Main").is_err());
+        assert!(SymbolTable::parse("").unwrap().is_empty());
+    }
+}
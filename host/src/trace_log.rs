@@ -0,0 +1,80 @@
+//! `--trace-log FILE`: 命令ごとの CPU 状態を gameboy-doctor 形式でファイルへ書き出す。
+//!
+//! BootROM なしで起動すると最初の行は `PC:0100` になる。gameboy-doctor と比べるときは
+//! `--ly-stub` を併用する（LY を常に 0x90 として読ませる）。
+//! シンボルがあれば行末に ` ; Label+$12` を添える（doctor と比べるときは `--no-sym`）。
+
+use gb_core::gameboy::{GameBoy, StepResult};
+use gb_core::input::InputSource;
+use gb_core::platform::{AudioSink, CartridgeBus, Display, SerialPort};
+use gb_host::symbols::SymbolTable;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+pub struct TraceLog {
+    out: BufWriter<File>,
+    symbols: SymbolTable,
+    /// コアが書いた 1 行（改行付き）
+    line: String,
+}
+
+impl TraceLog {
+    pub fn create(path: &str, symbols: SymbolTable) -> io::Result<Self> {
+        Ok(Self { out: BufWriter::new(File::create(path)?), symbols, line: String::new() })
+    }
+
+    /// 書き込みのエラーはトレースが欠けるだけなので無視する（最後の flush で報告する）
+    fn write_line(&mut self, bank: usize, pc: u16) {
+        let line = self.line.trim_end();
+        let _ = match self.symbols.name(bank, pc) {
+            Some(name) => writeln!(self.out, "{} ; {}", line, name),
+            None => writeln!(self.out, "{}", line),
+        };
+        self.line.clear();
+    }
+}
+
+impl Drop for TraceLog {
+    fn drop(&mut self) {
+        if let Err(e) = self.out.flush() {
+            eprintln!("Failed to write trace log: {}", e);
+        }
+    }
+}
+
+/// `log` があればトレースを書きながら、なければ通常どおり 1 M-cycle 進める
+pub fn step<C: CartridgeBus, D: Display, A: AudioSink, I: InputSource, S: SerialPort>(
+    gb: &mut GameBoy<C, D, A, I, S>,
+    log: Option<&mut TraceLog>,
+) -> StepResult {
+    let Some(log) = log else { return gb.step() };
+    // 書かれる行は命令境界の状態なので、進める前の PC とバンクがその命令の位置
+    let pc = gb.registers().pc;
+    let bank = gb.bank_at(pc);
+    let result = gb.step_traced(&mut log.line);
+    if !log.line.is_empty() {
+        log.write_line(bank, pc);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_symbol_to_doctor_line() {
+        let path = std::env::temp_dir().join(format!("gb-trace-{}.log", std::process::id()));
+        let symbols = SymbolTable::parse("00:0150 Main\n").unwrap();
+        let mut log = TraceLog::create(path.to_str().unwrap(), symbols).unwrap();
+        let doctor = "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0152 PCMEM:00,00,00,00";
+        log.line = format!("{}\n", doctor);
+        log.write_line(0, 0x0152);
+        log.line = format!("{}\n", doctor);
+        log.write_line(0, 0x0100);
+        drop(log);
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text, format!("{} ; Main+$2\n{}\n", doctor, doctor));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+//! F1-F4: VRAM タイル・タイルマップ・OAM・パレットのビューアウィンドウ。
+//!
+//! 画像は [`gb_host::vram_view`] が作り、ここは SDL のウィンドウの開閉と表示だけを受け持つ。
+//! 開いているウィンドウはフレーム完成ごとに描き直す（vsync は待たない）。
+
+use crate::lcd::{HostKeys, to_rgb24};
+use gb_core::ppu::Ppu;
+use gb_host::vram_view::{self, Image};
+use sdl2::VideoSubsystem;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+struct View {
+    title: &'static str,
+    render: fn(&Ppu) -> Image,
+    /// ウィンドウを開くときの拡大率
+    scale: u32,
+}
+
+/// 順番は F1-F4
+const VIEWS: [View; 4] = [
+    View { title: "VRAM tiles", render: vram_view::tiles, scale: 3 },
+    View { title: "BG maps", render: vram_view::maps, scale: 2 },
+    View { title: "OAM", render: vram_view::oam, scale: 3 },
+    View { title: "Palettes", render: vram_view::palettes, scale: 4 },
+];
+
+pub struct Viewers {
+    video: VideoSubsystem,
+    windows: [Option<Canvas<Window>>; 4],
+}
+
+impl Viewers {
+    pub fn new(video: VideoSubsystem) -> Self {
+        Self { video, windows: Default::default() }
+    }
+
+    /// ホットキーでウィンドウを開閉し、開いているものを描き直す（フレーム完成時に呼ぶ）
+    pub fn update(&mut self, keys: &HostKeys, ppu: &Ppu) {
+        for (i, &toggle) in keys.toggle_viewer.iter().enumerate() {
+            if toggle {
+                self.windows[i] = match self.windows[i].take() {
+                    Some(_) => None,
+                    None => self.open(i, ppu),
+                };
+            }
+        }
+        if let Some(id) = keys.closed_window {
+            for window in &mut self.windows {
+                if window.as_ref().is_some_and(|c| c.window().id() == id) {
+                    *window = None;
+                }
+            }
+        }
+        for (canvas, view) in self.windows.iter_mut().zip(&VIEWS) {
+            if let Some(canvas) = canvas {
+                draw(canvas, &(view.render)(ppu));
+            }
+        }
+    }
+
+    fn open(&self, i: usize, ppu: &Ppu) -> Option<Canvas<Window>> {
+        let View { title, render, scale } = VIEWS[i];
+        let img = render(ppu);
+        let window = self
+            .video
+            .window(title, img.width as u32 * scale, img.height as u32 * scale)
+            .resizable()
+            .build()
+            .map_err(|e| eprintln!("Failed to open {} viewer: {}", title, e))
+            .ok()?;
+        window
+            .into_canvas()
+            .accelerated()
+            .build()
+            .map_err(|e| eprintln!("Failed to open {} viewer: {}", title, e))
+            .ok()
+    }
+}
+
+fn draw(canvas: &mut Canvas<Window>, img: &Image) {
+    let texture_creator = canvas.texture_creator();
+    let mut texture = texture_creator
+        .create_texture_streaming(PixelFormatEnum::RGB24, img.width as u32, img.height as u32)
+        .unwrap();
+    texture.update(None, &to_rgb24(&img.pixels), img.width * 3).unwrap();
+    canvas.clear();
+    canvas.copy(&texture, None, None).unwrap();
+    canvas.present();
+}
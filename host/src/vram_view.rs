@@ -0,0 +1,216 @@
+//! VRAM・タイルマップ・OAM・パレットを画像 (RGB555) にする。
+//!
+//! SDL のビューアウィンドウ（F1-F4）が毎フレーム使うほか、ヘッドレスのツールからも使える。
+//! 読むのは [`Ppu`] の読み取り専用 API だけなので、エミュレーションには影響しない。
+
+use gb_core::ppu::Ppu;
+
+/// 区切りの隙間・OAM のセル背景
+const GAP_COLOR: u16 = 0x2108;
+/// スクロール位置の枠
+const VIEWPORT_COLOR: u16 = 0x001F;
+const TEXT_COLOR: u16 = 0x7FFF;
+
+/// OAM ビューの 1 エントリの大きさ（8 列 × 5 行）
+const OAM_CELL: (usize, usize) = (32, 28);
+
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// RGB555、行優先
+    pub pixels: Vec<u16>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Self {
+        Self { width, height, pixels: vec![GAP_COLOR; width * height] }
+    }
+
+    fn put(&mut self, x: usize, y: usize, color: u16) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u16 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// 0-9 A-F と X Y T・空白だけの 3×5 フォントで書く
+    fn text(&mut self, x: usize, y: usize, s: &str) {
+        for (i, ch) in s.chars().enumerate() {
+            for (row, bits) in glyph(ch).iter().enumerate() {
+                for col in 0..3 {
+                    if bits >> (2 - col) & 1 != 0 {
+                        self.put(x + i * 4 + col, y + row, TEXT_COLOR);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 両バンクのタイル 384 個ずつを 16 列 × 24 行で左右に並べる（BG パレット 0 の色）
+pub fn tiles(ppu: &Ppu) -> Image {
+    let mut img = Image::new(128 * 2 + 8, 192);
+    let colors = ppu.bg_colors(0);
+    for bank in 0..2 {
+        for tile in 0..384 {
+            let (x0, y0) = (bank * 136 + tile % 16 * 8, tile / 16 * 8);
+            for row in 0..8 {
+                for col in 0..8 {
+                    let c = ppu.tile_pixel(bank, tile, col as u8, row as u8);
+                    img.put(x0 + col, y0 + row, colors[c as usize]);
+                }
+            }
+        }
+    }
+    img
+}
+
+/// 0x9800 と 0x9C00 の 32×32 マップを左右に並べ、BG が使う方に SCX/SCY の表示範囲を枠で描く
+pub fn maps(ppu: &Ppu) -> Image {
+    let mut img = Image::new(256 * 2 + 8, 256);
+    for map in 0..2 {
+        let x0 = map * 264;
+        for row in 0..32 {
+            for col in 0..32 {
+                let t = ppu.map_tile(map, col, row);
+                let colors = ppu.bg_colors(t.palette());
+                for y in 0..8u8 {
+                    for x in 0..8u8 {
+                        let ty = if t.y_flip() { 7 - y } else { y };
+                        let tx = if t.x_flip() { 7 - x } else { x };
+                        let c = ppu.tile_pixel(t.vram_bank(), t.tile, tx, ty);
+                        let (px, py) = (x0 + col * 8 + x as usize, row * 8 + y as usize);
+                        img.put(px, py, colors[c as usize]);
+                    }
+                }
+            }
+        }
+    }
+    // 表示範囲はマップの端で折り返す
+    let (scx, scy) = ppu.scroll();
+    let x0 = ppu.bg_map() * 264;
+    let wrap = |base: u8, d: usize| (base as usize + d) % 256;
+    for dx in 0..160 {
+        img.put(x0 + wrap(scx, dx), wrap(scy, 0), VIEWPORT_COLOR);
+        img.put(x0 + wrap(scx, dx), wrap(scy, 143), VIEWPORT_COLOR);
+    }
+    for dy in 0..144 {
+        img.put(x0 + wrap(scx, 0), wrap(scy, dy), VIEWPORT_COLOR);
+        img.put(x0 + wrap(scx, 159), wrap(scy, dy), VIEWPORT_COLOR);
+    }
+    img
+}
+
+/// OAM 40 エントリを 8 列 × 5 行で並べる。各セルは OBJ（反転は適用しない）と
+/// X / Y / タイル番号 / 属性 (`A`) の 16 進値
+pub fn oam(ppu: &Ppu) -> Image {
+    let (w, h) = OAM_CELL;
+    let mut img = Image::new(w * 8, h * 5);
+    let height = ppu.sprite_height() as usize;
+    for i in 0..40 {
+        let e = ppu.oam_entry(i);
+        let (x0, y0) = (i % 8 * w, i / 8 * h);
+        let (bank, colors) = if ppu.cgb_mode {
+            (e.vram_bank(), ppu.obj_colors(e.cgb_palette()))
+        } else {
+            (0, ppu.obj_colors(e.dmg_palette()))
+        };
+        // 8×16 では bit0 を無視した偶数タイルから 2 枚
+        let first = if height == 16 { e.tile & 0xFE } else { e.tile } as usize;
+        for row in 0..height {
+            for col in 0..8 {
+                let c = ppu.tile_pixel(bank, first + row / 8, col as u8, (row % 8) as u8);
+                if c != 0 {
+                    img.put(x0 + 2 + col, y0 + 2 + row, colors[c as usize]);
+                }
+            }
+        }
+        img.text(x0 + 13, y0 + 2, &format!("X {:02X}", e.x));
+        img.text(x0 + 13, y0 + 8, &format!("Y {:02X}", e.y));
+        img.text(x0 + 13, y0 + 14, &format!("T {:02X}", e.tile));
+        img.text(x0 + 13, y0 + 20, &format!("A {:02X}", e.attrs));
+    }
+    img
+}
+
+/// CGB パレット RAM の BG（左）と OBJ（右）8 パレット × 4 色を 8×8 の色見本で並べる
+pub fn palettes(ppu: &Ppu) -> Image {
+    let mut img = Image::new(32 * 2 + 8, 64);
+    for (x0, ram) in [(0, ppu.bg_palette_ram()), (40, ppu.obj_palette_ram())] {
+        for (i, c) in ram.chunks(2).enumerate() {
+            let color = u16::from_le_bytes([c[0], c[1]]) & 0x7FFF;
+            let (sx, sy) = (x0 + i % 4 * 8, i / 4 * 8);
+            for y in 0..8 {
+                for x in 0..8 {
+                    img.put(sx + x, sy + y, color);
+                }
+            }
+        }
+    }
+    img
+}
+
+fn glyph(ch: char) -> [u8; 5] {
+    match ch {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        _ => [0; 5],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_tiles_maps_and_oam() {
+        // LCD オフ・BGP/OBP0=E4・0x8000 方式。タイル 1 の左上だけ色 3、マップ (1,0) にタイル 1
+        let mut ppu = Ppu::new();
+        ppu.write(0xFF47, 0xE4);
+        ppu.write(0xFF48, 0xE4);
+        ppu.write(0xFF40, 0x10);
+        ppu.write(0x8010, 0x80);
+        ppu.write(0x8011, 0x80);
+        ppu.write(0x9801, 0x01);
+        ppu.write(0xFF42, 0x10);
+        ppu.write(0xFF43, 0x08);
+        for (i, v) in [0x10, 0x08, 0x01, 0x00].into_iter().enumerate() {
+            ppu.write_oam_dma(i, v);
+        }
+        let [white, .., black] = ppu.bg_colors(0);
+
+        let img = tiles(&ppu);
+        assert_eq!((img.width, img.height), (264, 192));
+        assert_eq!((img.pixel(8, 0), img.pixel(9, 0)), (black, white));
+
+        // SCX=8, SCY=16 の枠は BG が使う 0x9800 側だけ
+        let img = maps(&ppu);
+        assert_eq!((img.pixel(8, 0), img.pixel(9, 0)), (black, white));
+        assert_eq!((img.pixel(9, 16), img.pixel(8, 20)), (VIEWPORT_COLOR, VIEWPORT_COLOR));
+        assert_eq!((img.pixel(264 + 8, 0), img.pixel(264 + 9, 16)), (white, white));
+
+        let img = oam(&ppu);
+        assert_eq!((img.pixel(2, 2), img.pixel(3, 2)), (black, GAP_COLOR));
+        // "X 08" の "0" の左上
+        assert_eq!(img.pixel(13 + 8, 2), TEXT_COLOR);
+        assert_eq!((palettes(&ppu).width, palettes(&ppu).height), (72, 64));
+    }
+}
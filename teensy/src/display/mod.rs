@@ -233,6 +233,18 @@ where
         }
     }
 
+    /// 画面を消去してエラーメッセージを 1 行ずつ描画する。起動できない時に
+    /// GB の描画を始める前に呼ぶ (以降のフレーム描画は想定しない)。
+    pub fn show_error(&mut self, lines: &[&[u8]]) {
+        self.clear_screen();
+        let line_h = (font::HEIGHT as u16 + 1) * OVERLAY_SCALE;
+        let mut y = OVERLAY_Y;
+        for line in lines {
+            self.draw_text(OVERLAY_X, y, line, OVERLAY_SCALE);
+            y += line_h;
+        }
+    }
+
     /// 1 フレーム分の実処理サイクル (step 群 + draw、ビジーウェイトを除く) を記録する。
     /// `budget` は 1 フレームの予算サイクル。main ループから毎フレーム呼ぶ。
     pub fn record_work(&mut self, cycles: u32, budget: u32) {
@@ -38,7 +38,7 @@ fn SAI1() {
 use display::panel::St7789;
 use display::DmaDisplay;
 use input::GpioInput;
-use sdcard::FlashCart;
+use sdcard::flash_cart;
 
 /// Teensy 4.1 全ピン割り当て (確定):
 ///
@@ -105,9 +105,6 @@ fn main() -> ! {
         cortex_m::peripheral::NVIC::unmask(bsp::interrupt::USB_OTG1);
     }
 
-    // ------- ROM (Flash 埋め込み) -------
-    let cart = FlashCart::new(ROM);
-
     // ------- ILI9341 ディスプレイ (LPSPI4) -------
     let spi: board::Lpspi4 = board::lpspi(
         lpspi4,
@@ -148,7 +145,24 @@ fn main() -> ! {
     let rst = gpio2.output(pins.p8);
     let dma_channel = dma[0].take().unwrap();
 
-    let display = DmaDisplay::<St7789, _, _, _>::new(spi, dc, rst, dma_channel);
+    let mut display = DmaDisplay::<St7789, _, _, _>::new(spi, dc, rst, dma_channel);
+
+    // ------- ROM (Flash 埋め込み) -------
+    // 未対応のカートリッジタイプは画面に表示して停止する (USB ログだけでは気付けないため)。
+    let cart = match flash_cart(ROM) {
+        Ok(cart) => cart,
+        Err(code) => {
+            log::error!("unsupported cartridge type 0x{:02X}", code);
+            const HEX: &[u8; 16] = b"0123456789ABCDEF";
+            let mut line = *b"TYPE: 0X00";
+            line[8] = HEX[(code >> 4) as usize];
+            line[9] = HEX[(code & 0x0F) as usize];
+            display.show_error(&[b"UNSUPPORTED CART", &line]);
+            loop {
+                cortex_m::asm::wfi();
+            }
+        }
+    };
 
     // ------- SAI1 オーディオ (MAX98357A/PCM5102A, I2S) -------
     // 既知の問題: 有効化するとランダムなタイミングで画面が真っ黒になる未解決バグがある。